@@ -1,3 +0,0 @@
-complete -c smart-term -s h -l help -d "Show help"
-complete -c smart-term -s v -l version -d "Show version"
-complete -c smart-term -s u -l ui -d "Start in UI mode"
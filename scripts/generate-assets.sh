@@ -0,0 +1,13 @@
+#!/bin/bash
+# Генерирует автодополнение и man страницу из описания командной строки
+# (src/cli.rs). Использование: scripts/generate-assets.sh [путь к smart-term]
+
+set -e
+
+BIN="${1:-target/release/smart-term}"
+
+mkdir -p completions
+"$BIN" completions bash > completions/smart-term.bash
+"$BIN" completions zsh > completions/_smart-term
+"$BIN" completions fish > completions/smart-term.fish
+"$BIN" man > assets/smart-term.1
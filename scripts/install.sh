@@ -25,6 +25,8 @@ fi
 # Сборка
 echo -e "${YELLOW}🔨 Сборка проекта...${NC}"
 cargo build --release
+# Автодополнение и man страница генерируются из описания командной строки
+scripts/generate-assets.sh target/release/smart-term
 
 # Создание директорий
 echo -e "${YELLOW}📁 Установка файлов...${NC}"
@@ -38,10 +40,8 @@ sudo cp target/release/smart-term /usr/local/bin/
 sudo chmod +x /usr/local/bin/smart-term
 
 # Установка документации
-if [ -f "assets/smart-term.1" ]; then
-    sudo cp assets/smart-term.1 /usr/share/man/man1/
-    sudo mandb > /dev/null 2>&1
-fi
+sudo cp assets/smart-term.1 /usr/share/man/man1/
+sudo mandb > /dev/null 2>&1 || true
 
 # Установка автодополнения
 if [ -d "/usr/share/bash-completion/completions" ]; then
@@ -49,7 +49,11 @@ if [ -d "/usr/share/bash-completion/completions" ]; then
 fi
 
 if [ -d "/usr/share/zsh/site-functions" ]; then
-    sudo cp completions/smart-term.zsh /usr/share/zsh/site-functions/_smart-term
+    sudo cp completions/_smart-term /usr/share/zsh/site-functions/_smart-term
+fi
+
+if [ -d "/usr/share/fish/vendor_completions.d" ]; then
+    sudo cp completions/smart-term.fish /usr/share/fish/vendor_completions.d/smart-term.fish
 fi
 
 echo -e "${GREEN}✅ Установка завершена!${NC}"
@@ -57,6 +61,7 @@ echo ""
 echo -e "${GREEN}💡 Используйте:${NC}"
 echo -e "   smart-term          # Текстовый режим"
 echo -e "   smart-term --ui     # Псевдографический режим"
+echo -e "   smart-term -c CMD   # Выполнить команду"
 echo -e "   smart-term --help   # Справка"
 echo ""
 echo -e "${YELLOW}📚 Документация: man smart-term${NC}"
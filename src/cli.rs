@@ -0,0 +1,149 @@
+//! Описание командной строки. Из него же генерируются автодополнение
+//! для bash, zsh, fish (`smart-term completions <shell>`) и man страница
+//! (`smart-term man`)
+
+use std::io::Write;
+use std::path::PathBuf;
+use clap::{ArgAction, CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+
+/// Разделы man страницы, которых нет в описании аргументов
+const AFTER_HELP: &str = "\
+Горячие клавиши в текстовом режиме:
+  Ctrl+U    Переключение в псевдографический режим
+  Ctrl+R    Поиск по истории команд
+  Tab       Автодополнение команд, путей и git
+
+Конфигурация: $XDG_CONFIG_HOME/smart_term/config.toml (~/.config/smart_term/config.toml)
+Стартовый файл: ~/.smarttermrc
+
+Отчет об ошибках: https://github.com/smart-term/smart-term/issues";
+
+/// Умный терминал с псевдографикой и Git интеграцией
+#[derive(Debug, Parser)]
+#[command(
+    name = "smart-term",
+    version,
+    disable_version_flag = true,
+    disable_help_subcommand = true,
+    subcommand_precedence_over_arg = true,
+    after_help = AFTER_HELP,
+)]
+pub struct Cli {
+    /// Использовать файл конфигурации FILE
+    #[arg(long, global = true, value_name = "FILE")]
+    pub config: Option<PathBuf>,
+
+    /// Запуск в псевдографическом режиме (то же, что `smart-term ui`)
+    #[arg(short, long)]
+    pub ui: bool,
+
+    /// Выполнить команду и выйти; аргументы после нее - $0, $1, ...
+    #[arg(short = 'c', value_name = "COMMAND")]
+    pub command: Option<String>,
+
+    /// Остановка на первой неудачной команде (как set -e)
+    #[arg(short = 'e')]
+    pub errexit: bool,
+
+    /// Печатать команды перед выполнением (как set -x)
+    #[arg(short = 'x')]
+    pub xtrace: bool,
+
+    /// Показать версию
+    #[arg(short = 'v', long, action = ArgAction::Version)]
+    #[allow(dead_code)]
+    version: Option<bool>,
+
+    /// Файл скрипта и его аргументы (без -c), или имя и аргументы для -c
+    #[arg(value_name = "SCRIPT", trailing_var_arg = true, allow_hyphen_values = true)]
+    pub args: Vec<String>,
+
+    #[command(subcommand)]
+    pub subcommand: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Запуск в псевдографическом режиме
+    Ui,
+    /// Открыть файл во встроенном редакторе
+    Edit {
+        file: PathBuf,
+    },
+    /// Показать статус git репозитория в текущей директории
+    GitStatus,
+    /// Справка по команде Bash
+    Help {
+        #[arg(value_name = "CMD")]
+        command: String,
+    },
+    /// Подобрать команды по описанию задачи
+    Suggest {
+        query: String,
+    },
+    /// Вывести скрипт автодополнения для оболочки
+    #[command(hide = true)]
+    Completions {
+        shell: Shell,
+    },
+    /// Вывести man страницу
+    #[command(hide = true)]
+    Man,
+}
+
+/// Скрипт автодополнения для shell
+pub fn write_completions(shell: Shell, out: &mut impl Write) {
+    clap_complete::generate(shell, &mut Cli::command(), "smart-term", out);
+}
+
+/// man страница в формате roff
+pub fn write_man_page(out: &mut impl Write) -> std::io::Result<()> {
+    clap_mangen::Man::new(Cli::command()).render(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_command_and_script_args() {
+        let cli = Cli::parse_from(["smart-term", "-ex", "-c", "echo $1", "name", "arg"]);
+        assert_eq!(cli.command.as_deref(), Some("echo $1"));
+        assert!(cli.errexit && cli.xtrace);
+        assert_eq!(cli.args, ["name", "arg"]);
+
+        let cli = Cli::parse_from(["smart-term", "script.st", "-v", "x"]);
+        assert_eq!(cli.args, ["script.st", "-v", "x"]);
+        assert!(cli.subcommand.is_none());
+
+        // Имя подкоманды после файла скрипта - аргумент скрипта
+        let cli = Cli::parse_from(["smart-term", "script.st", "suggest"]);
+        assert_eq!(cli.args, ["script.st", "suggest"]);
+        assert!(cli.subcommand.is_none());
+    }
+
+    #[test]
+    fn test_subcommands() {
+        let cli = Cli::parse_from(["smart-term", "edit", "notes.md"]);
+        assert!(matches!(cli.subcommand, Some(Command::Edit { file }) if file == Path::new("notes.md")));
+        let cli = Cli::parse_from(["smart-term", "--config", "a.toml", "suggest", "найти файл"]);
+        assert_eq!(cli.config.as_deref(), Some(Path::new("a.toml")));
+        assert!(matches!(cli.subcommand, Some(Command::Suggest { query }) if query == "найти файл"));
+    }
+
+    #[test]
+    fn test_generated_completions() {
+        let mut bash = Vec::new();
+        write_completions(Shell::Bash, &mut bash);
+        let bash = String::from_utf8(bash).unwrap();
+        assert!(bash.contains("git-status"));
+        assert!(!bash.contains("true false"));
+    }
+}
@@ -0,0 +1,600 @@
+//! Конфигурационный файл ~/.config/smart_term/config.toml
+//!
+//! Все секции и ключи необязательны, отсутствующие значения берутся по умолчанию:
+//! [terminal], [history], [colors], [aliases], [keybindings], [editor], [guard],
+//! [runners.<имя>]
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use serde::Deserialize;
+use toml::Spanned;
+use crate::editor::EditorSettings;
+use crate::runners::{RunnerConfig, RunnerMode};
+use crate::terminal::TerminalConfig;
+use crate::terminal::prompt::PromptTemplate;
+use crate::terminal::guard::{self, Action, CustomRule, GuardConfig};
+use crate::ui::keybindings::{KeyBindings, KeyPress};
+use crate::utils::helpers::{Color, ColorScheme};
+
+const CONFIG_DIR: &str = "smart_term";
+const CONFIG_FILE: &str = "config.toml";
+
+/// Загруженная конфигурация
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub terminal: TerminalConfig,
+    pub colors: ColorScheme,
+    pub aliases: HashMap<String, String>,
+    pub keybindings: KeyBindings,
+    pub editor: EditorSettings,
+    pub guard: GuardConfig,
+    /// Исполнители кода !<имя> в порядке объявления
+    pub runners: Vec<RunnerConfig>,
+    /// Файл, из которого прочитана конфигурация; None - значения по умолчанию
+    pub path: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            terminal: TerminalConfig::default(),
+            colors: ColorScheme::default(),
+            aliases: HashMap::new(),
+            keybindings: KeyBindings::new(),
+            editor: EditorSettings::default(),
+            guard: GuardConfig::default(),
+            runners: Vec::new(),
+            path: None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    terminal: TerminalSection,
+    history: HistorySection,
+    colors: ColorsSection,
+    aliases: HashMap<String, String>,
+    keybindings: HashMap<String, Spanned<String>>,
+    editor: EditorSection,
+    guard: GuardSection,
+    runners: HashMap<String, Spanned<RunnerSection>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct TerminalSection {
+    /// Шаблон приглашения; пустая строка - шаблон по умолчанию
+    prompt: Option<Spanned<String>>,
+    /// Секунды ожидания git status для сегмента {git}
+    prompt_git_timeout: Option<Spanned<f64>>,
+    show_welcome: Option<bool>,
+    /// Пустая строка - не выполнять стартовый файл
+    rc_file: Option<String>,
+    syntax_highlighting: Option<bool>,
+    auto_completion: Option<bool>,
+    /// Секунды; 0 - не показывать длительность команд в приглашении
+    slow_command_threshold: Option<Spanned<f64>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct HistorySection {
+    size: Option<usize>,
+    /// Пустая строка - история только в памяти
+    file: Option<String>,
+    ignore_space: Option<bool>,
+    ignore: Option<Vec<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ColorsSection {
+    prompt_user: Option<Spanned<String>>,
+    prompt_directory: Option<Spanned<String>>,
+    error: Option<Spanned<String>>,
+    success: Option<Spanned<String>>,
+    warning: Option<Spanned<String>>,
+    info: Option<Spanned<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct EditorSection {
+    tab_size: Option<Spanned<usize>>,
+    line_numbers: Option<bool>,
+    syntax_highlighting: Option<bool>,
+    word_wrap: Option<bool>,
+    auto_indent: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct GuardSection {
+    enabled: Option<bool>,
+    /// Пустая строка - не вести журнал
+    log_file: Option<String>,
+    /// Встроенное правило = действие для обычного пользователя
+    rules: HashMap<String, Spanned<String>>,
+    /// Встроенное правило = действие под root
+    elevated: HashMap<String, Spanned<String>>,
+    custom: Vec<CustomRuleSection>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CustomRuleSection {
+    /// По умолчанию - имя команды
+    name: Option<String>,
+    command: String,
+    #[serde(default)]
+    args: Vec<String>,
+    action: Spanned<String>,
+    /// По умолчанию - то же, что action
+    elevated: Option<Spanned<String>>,
+    reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RunnerSection {
+    /// Интерпретатор и его аргументы; в режиме oneshot последним добавляется файл фрагмента
+    command: Spanned<Vec<String>>,
+    /// По умолчанию - имя исполнителя
+    extension: Option<String>,
+    /// "oneshot" (по умолчанию) или "repl"
+    mode: Option<Spanned<String>>,
+    /// Секунды; 0 - без ограничения
+    timeout: Option<Spanned<f64>>,
+    description: Option<String>,
+    /// Для repl: инструкция, печатающая {marker}
+    marker: Option<Spanned<String>>,
+}
+
+/// Префиксы встроенных команд, которые нельзя занять исполнителем
+const RESERVED_RUNNER_NAMES: &[&str] = &["bash", "edit", "micro", "crypt"];
+
+/// Пути поиска конфигурации в порядке приоритета:
+/// $XDG_CONFIG_HOME (или ~/.config), затем каталоги из $XDG_CONFIG_DIRS
+pub fn search_paths() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    match std::env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        Some(dir) => dirs.push(PathBuf::from(dir)),
+        None => {
+            if let Some(home) = std::env::var_os("HOME") {
+                dirs.push(PathBuf::from(home).join(".config"));
+            }
+        }
+    }
+
+    let system_dirs = std::env::var("XDG_CONFIG_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/etc/xdg".to_string());
+    dirs.extend(system_dirs.split(':').filter(|dir| !dir.is_empty()).map(PathBuf::from));
+
+    dirs.into_iter().map(|dir| dir.join(CONFIG_DIR).join(CONFIG_FILE)).collect()
+}
+
+/// Первый существующий файл конфигурации
+pub fn find_config() -> Option<PathBuf> {
+    search_paths().into_iter().find(|path| path.is_file())
+}
+
+impl Config {
+    /// Загружает конфигурацию: явно указанный файл (--config) обязан существовать,
+    /// без него используется первый найденный по XDG или значения по умолчанию
+    pub fn load(explicit: Option<&Path>) -> Result<Self, String> {
+        match explicit {
+            Some(path) => Self::load_file(path),
+            None => match find_config() {
+                Some(path) => Self::load_file(&path),
+                None => Ok(Self::default()),
+            },
+        }
+    }
+
+    /// Читает и разбирает файл конфигурации
+    pub fn load_file(path: &Path) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| format!("{}: не удалось прочитать конфигурацию: {}", path.display(), e))?;
+        let mut config = Self::parse(&source)
+            .map_err(|e| format!("{}:{}", path.display(), e))?;
+        config.path = Some(path.to_path_buf());
+        Ok(config)
+    }
+
+    /// Разбирает текст конфигурации. Ошибка начинается с "строка:столбец: "
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let file: ConfigFile = toml::from_str(source).map_err(|e| {
+            let message = e.message().trim_end().to_string();
+            match e.span() {
+                Some(span) => located(source, span, &message),
+                None => format!(" {}", message),
+            }
+        })?;
+
+        let mut config = Self::default();
+
+        let terminal = &mut config.terminal;
+        if let Some(prompt) = file.terminal.prompt {
+            PromptTemplate::parse(prompt.get_ref())
+                .map_err(|e| located(source, prompt.span(), &format!("prompt: {}", e)))?;
+            terminal.prompt = prompt.into_inner();
+        }
+        if let Some(timeout) = file.terminal.prompt_git_timeout {
+            let seconds = *timeout.get_ref();
+            if !seconds.is_finite() || seconds <= 0.0 {
+                return Err(located(source, timeout.span(), "prompt_git_timeout должен быть положительным числом секунд"));
+            }
+            terminal.prompt_git_timeout = Duration::from_secs_f64(seconds);
+        }
+        if let Some(show_welcome) = file.terminal.show_welcome {
+            terminal.show_welcome = show_welcome;
+        }
+        if let Some(rc_file) = file.terminal.rc_file {
+            terminal.rc_file = if rc_file.is_empty() { None } else { Some(expand_tilde(&rc_file)) };
+        }
+        if let Some(highlighting) = file.terminal.syntax_highlighting {
+            terminal.enable_syntax_highlighting = highlighting;
+        }
+        if let Some(auto_completion) = file.terminal.auto_completion {
+            terminal.auto_completion = auto_completion;
+        }
+        if let Some(threshold) = file.terminal.slow_command_threshold {
+            let seconds = *threshold.get_ref();
+            if !seconds.is_finite() || seconds < 0.0 {
+                return Err(located(source, threshold.span(), "slow_command_threshold должен быть неотрицательным числом секунд"));
+            }
+            terminal.slow_command_threshold = (seconds > 0.0).then(|| Duration::from_secs_f64(seconds));
+        }
+
+        if let Some(size) = file.history.size {
+            terminal.history_size = size;
+        }
+        if let Some(history_file) = file.history.file {
+            terminal.history_file = if history_file.is_empty() {
+                None
+            } else {
+                Some(expand_tilde(&history_file))
+            };
+        }
+        if let Some(ignore_space) = file.history.ignore_space {
+            terminal.history_ignore_space = ignore_space;
+        }
+        if let Some(ignore) = file.history.ignore {
+            terminal.history_ignore = ignore;
+        }
+
+        let colors = file.colors;
+        let scheme = &mut config.colors;
+        for (value, target) in [
+            (colors.prompt_user, &mut scheme.prompt_user),
+            (colors.prompt_directory, &mut scheme.prompt_directory),
+            (colors.error, &mut scheme.error),
+            (colors.success, &mut scheme.success),
+            (colors.warning, &mut scheme.warning),
+            (colors.info, &mut scheme.info),
+        ] {
+            if let Some(value) = value {
+                *target = Color::parse(value.get_ref())
+                    .ok_or_else(|| located(source, value.span(), &format!("неизвестный цвет '{}'", value.get_ref())))?;
+            }
+        }
+
+        config.aliases = file.aliases;
+
+        // Сортировка делает ошибки воспроизводимыми при нескольких неверных ключах
+        let mut keybindings: Vec<_> = file.keybindings.into_iter().collect();
+        keybindings.sort_by_key(|(_, key)| key.span().start);
+        for (action, key) in keybindings {
+            let key_press = KeyPress::parse(key.get_ref())
+                .ok_or_else(|| located(source, key.span(), &format!("неизвестная клавиша '{}'", key.get_ref())))?;
+            config.keybindings.rebind(&action, key_press)
+                .map_err(|e| located(source, key.span(), &e))?;
+        }
+
+        let editor = &mut config.editor;
+        if let Some(tab_size) = file.editor.tab_size {
+            if *tab_size.get_ref() == 0 || *tab_size.get_ref() > 16 {
+                return Err(located(source, tab_size.span(), "tab_size должен быть от 1 до 16"));
+            }
+            editor.tab_size = *tab_size.get_ref();
+        }
+        if let Some(line_numbers) = file.editor.line_numbers {
+            editor.show_line_numbers = line_numbers;
+        }
+        if let Some(highlighting) = file.editor.syntax_highlighting {
+            editor.syntax_highlighting = highlighting;
+        }
+        if let Some(word_wrap) = file.editor.word_wrap {
+            editor.word_wrap = word_wrap;
+        }
+        if let Some(auto_indent) = file.editor.auto_indent {
+            editor.auto_indent = auto_indent;
+        }
+
+        let guard = &mut config.guard;
+        if let Some(enabled) = file.guard.enabled {
+            guard.enabled = enabled;
+        }
+        if let Some(log_file) = file.guard.log_file {
+            guard.log_file = if log_file.is_empty() { None } else { Some(expand_tilde(&log_file)) };
+        }
+        for (rules, target) in [(file.guard.rules, &mut guard.rules), (file.guard.elevated, &mut guard.elevated_rules)] {
+            let mut rules: Vec<_> = rules.into_iter().collect();
+            rules.sort_by_key(|(_, action)| action.span().start);
+            for (name, action) in rules {
+                if !guard::builtin_rule_names().any(|rule| rule == name) {
+                    let names: Vec<_> = guard::builtin_rule_names().collect();
+                    return Err(located(source, action.span(), &format!("неизвестное правило '{}' ({})", name, names.join(", "))));
+                }
+                target.insert(name, parse_action(source, &action)?);
+            }
+        }
+        for rule in file.guard.custom {
+            let action = parse_action(source, &rule.action)?;
+            let elevated = match &rule.elevated {
+                Some(elevated) => parse_action(source, elevated)?,
+                None => action,
+            };
+            guard.custom.push(CustomRule {
+                name: rule.name.unwrap_or_else(|| rule.command.clone()),
+                reason: rule.reason.unwrap_or_else(|| format!("правило для {}", rule.command)),
+                command: rule.command,
+                args: rule.args,
+                action,
+                elevated,
+            });
+        }
+
+        let mut runners: Vec<_> = file.runners.into_iter().collect();
+        runners.sort_by_key(|(_, runner)| runner.span().start);
+        for (name, runner) in runners {
+            config.runners.push(parse_runner(source, name, runner)?);
+        }
+
+        Ok(config)
+    }
+}
+
+fn parse_runner(source: &str, name: String, runner: Spanned<RunnerSection>) -> Result<RunnerConfig, String> {
+    let span = runner.span();
+    let runner = runner.into_inner();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '+')) {
+        return Err(located(source, span, &format!("недопустимое имя исполнителя '{}': буквы, цифры, _, - и +", name)));
+    }
+    if RESERVED_RUNNER_NAMES.contains(&name.as_str()) {
+        return Err(located(source, span, &format!("!{} - встроенная команда, выберите другое имя", name)));
+    }
+    if runner.command.get_ref().first().is_none_or(|program| program.is_empty()) {
+        return Err(located(source, runner.command.span(), "command должен содержать интерпретатор"));
+    }
+
+    let mode = match &runner.mode {
+        Some(mode) => RunnerMode::parse(mode.get_ref()).ok_or_else(|| {
+            located(source, mode.span(), &format!("неизвестный режим '{}' (oneshot, repl)", mode.get_ref()))
+        })?,
+        None => RunnerMode::OneShot,
+    };
+    let marker = match runner.marker {
+        Some(marker) if !marker.get_ref().contains("{marker}") => {
+            return Err(located(source, marker.span(), "marker должен содержать {marker}"));
+        }
+        Some(marker) => marker.into_inner(),
+        None if mode == RunnerMode::Repl => {
+            return Err(located(source, span, "для mode = \"repl\" нужен marker - инструкция, печатающая {marker}"));
+        }
+        None => String::new(),
+    };
+    let timeout = match runner.timeout {
+        Some(timeout) => {
+            let seconds = *timeout.get_ref();
+            if !seconds.is_finite() || seconds < 0.0 {
+                return Err(located(source, timeout.span(), "timeout должен быть неотрицательным числом секунд"));
+            }
+            (seconds > 0.0).then(|| Duration::from_secs_f64(seconds))
+        }
+        None => None,
+    };
+
+    Ok(RunnerConfig {
+        description: runner.description.unwrap_or_else(|| format!("выполнить код через {}", runner.command.get_ref()[0])),
+        extension: runner.extension.unwrap_or_else(|| name.clone()),
+        command: runner.command.into_inner(),
+        name,
+        mode,
+        timeout,
+        marker,
+    })
+}
+
+fn parse_action(source: &str, action: &Spanned<String>) -> Result<Action, String> {
+    Action::parse(action.get_ref()).ok_or_else(|| {
+        located(source, action.span(), &format!("неизвестное действие '{}' (allow, confirm, block)", action.get_ref()))
+    })
+}
+
+/// Форматирует сообщение с номером строки и столбца по смещению в тексте
+fn located(source: &str, span: Range<usize>, message: &str) -> String {
+    let before = &source[..span.start.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |s| s.chars().count()) + 1;
+    format!("{}:{}: {}", line, column, message)
+}
+
+fn expand_tilde(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => std::env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join(rest))
+            .unwrap_or_else(|| PathBuf::from(path)),
+        None => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_config_uses_defaults() {
+        let config = Config::parse("").unwrap();
+        assert_eq!(config.terminal.history_size, TerminalConfig::default().history_size);
+        assert_eq!(config.colors, ColorScheme::default());
+        assert!(config.aliases.is_empty());
+    }
+
+    #[test]
+    fn test_sections() {
+        let config = Config::parse(r#"
+[terminal]
+prompt = "{cwd:short}( {git|yellow})$ "
+prompt_git_timeout = 0.1
+show_welcome = false
+slow_command_threshold = 0.5
+
+[history]
+size = 500
+file = ""
+ignore = ["ls"]
+
+[colors]
+error = "bright-red"
+prompt_user = "bold #ff8800"
+
+[aliases]
+k = "kubectl"
+
+[keybindings]
+refresh = "F5"
+
+[editor]
+tab_size = 2
+auto_indent = false
+
+[guard]
+log_file = ""
+
+[guard.rules]
+git-force-push = "allow"
+
+[guard.elevated]
+rm-recursive = "block"
+
+[[guard.custom]]
+command = "terraform"
+args = ["destroy"]
+action = "confirm"
+"#).unwrap();
+        assert_eq!(config.terminal.prompt, "{cwd:short}( {git|yellow})$ ");
+        assert_eq!(config.terminal.prompt_git_timeout, Duration::from_millis(100));
+        assert!(!config.terminal.show_welcome);
+        assert_eq!(config.terminal.slow_command_threshold, Some(Duration::from_millis(500)));
+        assert_eq!(config.terminal.history_size, 500);
+        assert_eq!(config.terminal.history_file, None);
+        assert_eq!(config.terminal.history_ignore, vec!["ls".to_string()]);
+        assert_eq!(config.colors.error, "\x1b[91m");
+        assert_eq!(config.colors.prompt_user, "\x1b[1;38;2;255;136;0m");
+        assert_eq!(config.aliases.get("k").map(String::as_str), Some("kubectl"));
+        let refresh = config.keybindings.find_binding(&KeyPress::F(5)).unwrap();
+        assert_eq!(refresh.action, "refresh");
+        assert_eq!(config.editor.tab_size, 2);
+        assert!(!config.editor.auto_indent);
+        assert_eq!(config.guard.log_file, None);
+        assert_eq!(config.guard.rules.get("git-force-push"), Some(&Action::Allow));
+        assert_eq!(config.guard.elevated_rules.get("rm-recursive"), Some(&Action::Block));
+        let custom = &config.guard.custom[0];
+        assert_eq!((custom.name.as_str(), custom.action, custom.elevated), ("terraform", Action::Confirm, Action::Confirm));
+    }
+
+    #[test]
+    fn test_errors_report_line_numbers() {
+        let err = Config::parse("[terminal]\nprompt = \"x\"\nhistory_size = 5\n").unwrap_err();
+        assert!(err.starts_with("3:1:"), "{}", err);
+
+        let err = Config::parse("[colors]\n\nerror = \"purple\"\n").unwrap_err();
+        assert!(err.starts_with("3:9:") && err.contains("purple"), "{}", err);
+
+        let err = Config::parse("[keybindings]\nno_such_action = \"F5\"\n").unwrap_err();
+        assert!(err.starts_with("2:18:") && err.contains("no_such_action"), "{}", err);
+
+        let err = Config::parse("[editor]\ntab_size = 0\n").unwrap_err();
+        assert!(err.starts_with("2:12:"), "{}", err);
+
+        let err = Config::parse("[terminal]\nslow_command_threshold = -1\n").unwrap_err();
+        assert!(err.starts_with("2:26:"), "{}", err);
+
+        let err = Config::parse("[terminal]\nprompt = \"{user} {gti}\"\n").unwrap_err();
+        assert!(err.starts_with("2:10:") && err.contains("gti"), "{}", err);
+
+        let err = Config::parse("[guard.rules]\nrm-root = \"sometimes\"\n").unwrap_err();
+        assert!(err.starts_with("2:11:") && err.contains("sometimes"), "{}", err);
+
+        let err = Config::parse("[guard.elevated]\nrm-everything = \"block\"\n").unwrap_err();
+        assert!(err.starts_with("2:17:") && err.contains("rm-everything"), "{}", err);
+
+        let err = Config::parse("[history\n").unwrap_err();
+        assert!(err.starts_with("1:"), "{}", err);
+    }
+
+    #[test]
+    fn test_runners() {
+        let config = Config::parse(r#"
+[runners.node]
+command = ["node"]
+extension = "js"
+timeout = 10
+
+[runners.go]
+command = ["go", "run"]
+timeout = 0
+
+[runners.sh]
+command = ["sh"]
+mode = "repl"
+marker = "echo '{marker}'"
+"#).unwrap();
+        let names: Vec<_> = config.runners.iter().map(|runner| runner.name.as_str()).collect();
+        assert_eq!(names, vec!["node", "go", "sh"]);
+        let node = &config.runners[0];
+        assert_eq!((node.extension.as_str(), node.mode, node.timeout), ("js", RunnerMode::OneShot, Some(Duration::from_secs(10))));
+        assert_eq!(node.description, "выполнить код через node");
+        let go = &config.runners[1];
+        assert_eq!((go.command.clone(), go.extension.as_str(), go.timeout), (vec!["go".to_string(), "run".to_string()], "go", None));
+        assert_eq!((config.runners[2].mode, config.runners[2].marker.as_str()), (RunnerMode::Repl, "echo '{marker}'"));
+
+        let err = Config::parse("[runners.bash]\ncommand = [\"bash\"]\n").unwrap_err();
+        assert!(err.starts_with("1:") && err.contains("!bash"), "{}", err);
+
+        let err = Config::parse("[runners.node]\ncommand = []\n").unwrap_err();
+        assert!(err.starts_with("2:11:"), "{}", err);
+
+        let err = Config::parse("[runners.node]\ncommand = [\"node\"]\nmode = \"daemon\"\n").unwrap_err();
+        assert!(err.starts_with("3:8:") && err.contains("daemon"), "{}", err);
+
+        let err = Config::parse("[runners.sh]\ncommand = [\"sh\"]\nmode = \"repl\"\nmarker = \"echo done\"\n").unwrap_err();
+        assert!(err.starts_with("4:10:") && err.contains("{marker}"), "{}", err);
+
+        let err = Config::parse("[runners.sh]\ncommand = [\"sh\"]\nmode = \"repl\"\n").unwrap_err();
+        assert!(err.contains("marker"), "{}", err);
+
+        let err = Config::parse("[runners.node]\ncommand = [\"node\"]\ntimeout = -1\n").unwrap_err();
+        assert!(err.starts_with("3:11:"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_key_and_color() {
+        assert_eq!(KeyPress::parse("Ctrl-R"), Some(KeyPress::Ctrl('r')));
+        assert_eq!(KeyPress::parse("alt+h"), Some(KeyPress::Alt('h')));
+        assert_eq!(KeyPress::parse("F12"), Some(KeyPress::F(12)));
+        assert_eq!(KeyPress::parse("PgUp"), Some(KeyPress::PageUp));
+        assert_eq!(KeyPress::parse("F13"), None);
+        assert_eq!(Color::parse("bold green"), Some("\x1b[1;32m".to_string()));
+        assert_eq!(Color::parse("208"), Some("\x1b[38;5;208m".to_string()));
+        assert_eq!(Color::parse("nope"), None);
+    }
+}
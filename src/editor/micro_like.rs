@@ -1,4 +1,4 @@
-use crate::editor::EditorResult;
+use crate::editor::{EditorResult, EditorSettings};
 use crate::ui::KeyPress;
 use crossterm::{
     cursor::{Hide, MoveTo, Show},
@@ -19,10 +19,16 @@ pub struct MicroEditor {
     screen_width: u16,
     screen_height: u16,
     should_quit: bool,
+    settings: EditorSettings,
 }
 
 impl MicroEditor {
     pub fn new() -> Self {
+        Self::with_settings(EditorSettings::default())
+    }
+    
+    /// Редактор с настройками из секции [editor] конфигурации
+    pub fn with_settings(settings: EditorSettings) -> Self {
         let (screen_width, screen_height) = terminal::size().unwrap_or((80, 24));
         
         Self {
@@ -35,6 +41,7 @@ impl MicroEditor {
             screen_width,
             screen_height,
             should_quit: false,
+            settings,
         }
     }
 
@@ -164,18 +171,16 @@ impl MicroEditor {
             KeyPress::Char('q') if KeyModifiers::CONTROL.bits() != 0 => {
                 self.should_quit = true;
             }
-            KeyPress::Up => {
-                if self.cursor_y > 0 {
+            KeyPress::Up
+                if self.cursor_y > 0 => {
                     self.cursor_y -= 1;
                     self.cursor_x = self.cursor_x.min(self.content[self.cursor_y].len());
                 }
-            }
-            KeyPress::Down => {
-                if self.cursor_y < self.content.len() - 1 {
+            KeyPress::Down
+                if self.cursor_y < self.content.len() - 1 => {
                     self.cursor_y += 1;
                     self.cursor_x = self.cursor_x.min(self.content[self.cursor_y].len());
                 }
-            }
             KeyPress::Left => {
                 if self.cursor_x > 0 {
                     self.cursor_x -= 1;
@@ -208,11 +213,23 @@ impl MicroEditor {
                 let current_line = self.content[self.cursor_y].clone();
                 let (left, right) = current_line.split_at(self.cursor_x);
                 
+                // auto_indent: новая строка получает отступ текущей
+                let indent: String = if self.settings.auto_indent {
+                    left.chars().take_while(|c| *c == ' ' || *c == '\t').collect()
+                } else {
+                    String::new()
+                };
+                
                 self.content[self.cursor_y] = left.to_string();
-                self.content.insert(self.cursor_y + 1, right.to_string());
+                self.content.insert(self.cursor_y + 1, format!("{}{}", indent, right));
                 
                 self.cursor_y += 1;
-                self.cursor_x = 0;
+                self.cursor_x = indent.len();
+            }
+            KeyPress::Tab => {
+                let spaces = " ".repeat(self.settings.tab_size.max(1));
+                self.content[self.cursor_y].insert_str(self.cursor_x, &spaces);
+                self.cursor_x += spaces.len();
             }
             KeyPress::Char(c) => {
                 let current_line = &mut self.content[self.cursor_y];
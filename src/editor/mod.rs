@@ -270,10 +270,7 @@ pub mod utils {
         }
 
         // Проверяем по расширению
-        match FileType::from_path(path) {
-            FileType::Unknown => false,
-            _ => true,
-        }
+        !matches!(FileType::from_path(path), FileType::Unknown)
     }
 
     /// Читает файл с проверкой кодировки
@@ -1,4 +1,5 @@
 use crate::editor::FileType;
+use std::path::Path;
 
 #[derive(Debug, Clone)]
 pub struct SyntaxHighlighter {
@@ -137,10 +138,10 @@ impl SyntaxHighlighter {
                 }
                 in_comment = true;
                 current_token.push(ch);
-            } else if ch.is_digit(10) && current_token.is_empty() {
+            } else if ch.is_ascii_digit() && current_token.is_empty() {
                 in_number = true;
                 current_token.push(ch);
-            } else if in_number && (ch.is_digit(10) || ch == '.' || ch == 'x' || ch == 'b') {
+            } else if in_number && (ch.is_ascii_digit() || ch == '.' || ch == 'x' || ch == 'b') {
                 current_token.push(ch);
             } else {
                 if in_number {
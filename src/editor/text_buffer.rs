@@ -24,6 +24,13 @@ impl Default for TextBuffer {
     }
 }
 
+/// Содержимое буфера как строка
+impl std::fmt::Display for TextBuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.lines.join("\n"))
+    }
+}
+
 impl TextBuffer {
     /// Создает новый пустой текстовый буфер
     pub fn new() -> Self {
@@ -44,11 +51,6 @@ impl TextBuffer {
         }
     }
 
-    /// Возвращает содержимое буфера как строку
-    pub fn to_string(&self) -> String {
-        self.lines.join("\n")
-    }
-
     /// Возвращает ссылку на строки буфера
     pub fn lines(&self) -> &[String] {
         &self.lines
@@ -1,5 +1,11 @@
-use std::process::{Command, Stdio};
+use std::process::{Command, Output, Stdio};
 use std::path::{Path, PathBuf};
+use std::io::Read;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Как часто проверять, завершился ли git с ограничением по времени
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(5);
 
 /// Ошибки Git операций
 #[derive(Debug, Clone)]
@@ -8,6 +14,8 @@ pub enum GitError {
     ParseError(String),
     NoRepository,
     IOError(String),
+    /// Команда не уложилась в отведенное время и была остановлена
+    Timeout,
 }
 
 impl std::fmt::Display for GitError {
@@ -17,6 +25,7 @@ impl std::fmt::Display for GitError {
             GitError::ParseError(msg) => write!(f, "Parse error: {}", msg),
             GitError::NoRepository => write!(f, "Not a git repository"),
             GitError::IOError(msg) => write!(f, "IO error: {}", msg),
+            GitError::Timeout => write!(f, "Git command timed out"),
         }
     }
 }
@@ -103,13 +112,21 @@ impl GitManager {
 
     /// Выполняет git команду
     fn run_git_command(&self, args: &[&str]) -> Result<String, GitError> {
-        let output = Command::new("git")
-            .args(args)
+        self.run_git_command_until(args, None)
+    }
+
+    /// Выполняет git команду; после deadline процесс git останавливается
+    fn run_git_command_until(&self, args: &[&str], deadline: Option<Instant>) -> Result<String, GitError> {
+        let mut command = Command::new("git");
+        command.args(args)
             .current_dir(&self.repo_path)
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .map_err(|e| GitError::IOError(format!("Failed to execute git command: {}", e)))?;
+            .stderr(Stdio::piped());
+        let output = match deadline {
+            Some(deadline) => Self::output_until(command, deadline)?,
+            None => command.output()
+                .map_err(|e| GitError::IOError(format!("Failed to execute git command: {}", e)))?,
+        };
 
         if output.status.success() {
             String::from_utf8(output.stdout)
@@ -120,6 +137,43 @@ impl GitManager {
         }
     }
 
+    /// Вывод процесса, если он завершился до deadline. Вывод читается в
+    /// отдельных потоках, чтобы большой вывод не заблокировал git
+    fn output_until(mut command: Command, deadline: Instant) -> Result<Output, GitError> {
+        let io_error = |e: std::io::Error| GitError::IOError(format!("Failed to execute git command: {}", e));
+        let mut child = command.spawn().map_err(io_error)?;
+        let stdout = Self::read_in_background(child.stdout.take());
+        let stderr = Self::read_in_background(child.stderr.take());
+        let status = loop {
+            if let Some(status) = child.try_wait().map_err(io_error)? {
+                break status;
+            }
+            if Instant::now() >= deadline {
+                // Процесс еще не собран, поэтому kill не заденет чужой pid
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(GitError::Timeout);
+            }
+            thread::sleep(WAIT_POLL_INTERVAL);
+        };
+        Ok(Output {
+            status,
+            stdout: stdout.join().unwrap_or_default(),
+            stderr: stderr.join().unwrap_or_default(),
+        })
+    }
+
+    /// Читает канал процесса до конца в отдельном потоке
+    fn read_in_background(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+        thread::spawn(move || {
+            let mut buffer = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut buffer);
+            }
+            buffer
+        })
+    }
+
     /// Получает историю коммитов
     pub fn get_log(&self, limit: Option<usize>) -> Result<Vec<GitCommit>, GitError> {
         let mut args = vec!["log", "--oneline", "--decorate", "--format=%H|%an|%ae|%ad|%s"];
@@ -152,20 +206,32 @@ impl GitManager {
 
     /// Получает текущий статус репозитория
     pub fn get_status(&self) -> Result<RepositoryStatus, GitError> {
+        self.get_status_until(None)
+    }
+
+    /// Статус репозитория не дольше timeout (для приглашения: в огромных
+    /// репозиториях git status может работать секундами)
+    pub fn get_status_within(&self, timeout: Duration) -> Result<RepositoryStatus, GitError> {
+        self.get_status_until(Some(Instant::now() + timeout))
+    }
+
+    fn get_status_until(&self, deadline: Option<Instant>) -> Result<RepositoryStatus, GitError> {
+        let run = |args: &[&str]| self.run_git_command_until(args, deadline);
+
         // Получаем информацию о ветке
-        let branch_output = self.run_git_command(&["branch", "--show-current"])?;
+        let branch_output = run(&["branch", "--show-current"])?;
         let branch = branch_output.trim().to_string();
 
         // Получаем информацию об upstream
-        let upstream_output = self.run_git_command(&["rev-parse", "--abbrev-ref", "@{upstream}"]);
+        let upstream_output = run(&["rev-parse", "--abbrev-ref", "@{upstream}"]);
         let upstream = upstream_output.ok().map(|s| s.trim().to_string());
 
         // Получаем информацию о расхождении с upstream
         let (ahead, behind) = if upstream.is_some() {
-            let count_output = self.run_git_command(&["rev-list", "--count", "--left-right", "@{upstream}...HEAD"]).unwrap_or_default();
+            let count_output = run(&["rev-list", "--count", "--left-right", "@{upstream}...HEAD"]).unwrap_or_default();
             let counts: Vec<&str> = count_output.trim().split('\t').collect();
             (
-                counts.get(0).and_then(|s| s.parse().ok()).unwrap_or(0),
+                counts.first().and_then(|s| s.parse().ok()).unwrap_or(0),
                 counts.get(1).and_then(|s| s.parse().ok()).unwrap_or(0),
             )
         } else {
@@ -173,7 +239,8 @@ impl GitManager {
         };
 
         // Получаем статус файлов
-        let status_output = self.run_git_command(&["status", "--porcelain=v1"])?;
+        // --no-optional-locks: параллельный git в другом терминале не ждет блокировку индекса
+        let status_output = run(&["--no-optional-locks", "status", "--porcelain=v1"])?;
         let mut staged_files = Vec::new();
         let mut unstaged_files = Vec::new();
         let mut untracked_files = Vec::new();
@@ -449,4 +516,19 @@ mod tests {
         fs::create_dir_all(repo_path.join(".git")).unwrap();
         assert!(GitManager::is_repository(repo_path));
     }
+
+    #[test]
+    fn test_output_until_deadline() {
+        let started = Instant::now();
+        let mut command = Command::new("sleep");
+        command.arg("5").stdout(Stdio::piped());
+        let result = GitManager::output_until(command, Instant::now() + Duration::from_millis(100));
+        assert!(matches!(result, Err(GitError::Timeout)));
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        let mut command = Command::new("echo");
+        command.arg("ok").stdout(Stdio::piped());
+        let output = GitManager::output_until(command, Instant::now() + Duration::from_secs(5)).unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "ok");
+    }
 }
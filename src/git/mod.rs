@@ -1,6 +1,6 @@
 pub mod manager;
 
-pub use manager::{GitManager};
+pub use manager::{GitManager, RepositoryStatus};
 
 /// Git команды и их описание
 pub const GIT_COMMANDS: &[(&str, &str)] = &[
@@ -1,4 +1,71 @@
-use std::collections::HashMap;
+use crate::help::HelpCategory;
+use crate::help::HelpCategory::*;
+
+/// Справка по командам: имя, категория и описание (первая строка - кратко,
+/// дальше опции и примеры). По ней же работают подсказки suggest
+pub const COMMANDS: &[(&str, HelpCategory, &str)] = &[
+    // Файловые операции
+    ("ls", FileOperations, "Показать содержимое директории\nОпции:\n  -l детальный список\n  -a показать скрытые\n  -h человеко-читаемый размер\nПример: ls -la /home"),
+    ("cd", FileOperations, "Сменить текущую директорию\nПримеры:\n  cd /absolute/path\n  cd relative/path\n  cd ~ (домашняя директория)\n  cd .. (на уровень выше)"),
+    ("pwd", FileOperations, "Показать текущую рабочую директорию"),
+    ("cp", FileOperations, "Копировать файлы и директории\nОпции:\n  -r рекурсивно (для папок)\n  -i подтверждение перезаписи\nПримеры:\n  cp file1 file2\n  cp -r dir1 dir2"),
+    ("mv", FileOperations, "Переместить или переименовать файлы\nПримеры:\n  mv oldname newname\n  mv file /target/directory/"),
+    ("rm", FileOperations, "Удалить файлы и директории\nОпции:\n  -r рекурсивно\n  -f принудительно\n  -i интерактивно\nПример: rm -rf folder/"),
+    ("mkdir", FileOperations, "Создать директорию\nОпции:\n  -p создать родительские директории\nПример: mkdir -p project/src/main"),
+    ("rmdir", FileOperations, "Удалить пустую директорию"),
+    ("touch", FileOperations, "Создать пустой файл или обновить время доступа"),
+
+    // Работа с текстом
+    ("cat", TextProcessing, "Показать содержимое файла\nПримеры:\n  cat file.txt\n  cat file1 file2 > combined"),
+    ("more", TextProcessing, "Показать файл постранично"),
+    ("less", TextProcessing, "Показать файл с возможностью прокрутки"),
+    ("head", TextProcessing, "Показать первые N строк\nОпции:\n  -n число строк\nПример: head -n 10 file.txt"),
+    ("tail", TextProcessing, "Показать последние N строк\nОпции:\n  -n число строк\n  -f следить за изменениями\nПример: tail -f logfile"),
+    ("grep", TextProcessing, "Поиск текста по шаблону\nОпции:\n  -r рекурсивный поиск\n  -i игнорировать регистр\n  -n показать номера строк\nПример: grep -rn 'error' /var/log/"),
+    ("find", TextProcessing, "Поиск файлов и директорий\nПримеры:\n  find . -name '*.txt'\n  find /home -type f -size +1M\n  find . -mtime -7 (изменены за 7 дней)"),
+    ("locate", TextProcessing, "Быстрый поиск файлов по имени в базе (обновляется через updatedb)\nПример: locate nginx.conf"),
+    ("fd", TextProcessing, "Простой и быстрый поиск файлов (требует установки)\nПримеры:\n  fd config\n  fd -e rs src/"),
+    ("wc", TextProcessing, "Подсчет строк, слов, байт\nОпции:\n  -l только строки\n  -w только слова\n  -c только байты\nПример: wc -l file.txt"),
+
+    // Системные команды
+    ("ps", ProcessManagement, "Информация о процессах\nОпции:\n  aux все процессы\n  -ef все процессы\nПример: ps aux | grep python"),
+    ("top", ProcessManagement, "Интерактивный монитор процессов"),
+    ("htop", ProcessManagement, "Улучшенный монитор процессов (требует установки)"),
+    ("kill", ProcessManagement, "Завершить процесс\nСигналы:\n  -9 KILL (принудительно)\n  -15 TERM (корректно)\nПример: kill -9 1234"),
+    ("killall", ProcessManagement, "Завершить процессы по имени\nПример: killall firefox"),
+    ("df", SystemOperations, "Информация о дисковом пространстве\nОпции:\n  -h человеко-читаемый\nПример: df -h"),
+    ("du", SystemOperations, "Размер файлов/директорий\nОпции:\n  -sh суммарный размер\nПример: du -sh /home/user/"),
+    ("free", SystemOperations, "Информация о памяти\nОпции:\n  -h человеко-читаемый\nПример: free -h"),
+
+    // Сетевые команды
+    ("ping", NetworkCommands, "Проверка доступности хоста\nПример: ping google.com"),
+    ("curl", NetworkCommands, "Передача данных по URL\nПримеры:\n  curl -O http://example.com/file\n  curl -X POST -d 'data' http://api.example.com"),
+    ("wget", NetworkCommands, "Скачивание файлов\nПример: wget http://example.com/file.zip"),
+    ("ssh", NetworkCommands, "Подключение к удаленному хосту\nПример: ssh user@hostname"),
+    ("scp", NetworkCommands, "Копирование файлов через SSH\nПример: scp file.txt user@host:/path/"),
+    ("netstat", NetworkCommands, "Сетевые соединения\nОпции:\n  -tunlp все соединения\nПример: netstat -tunlp"),
+
+    // Права доступа
+    ("chmod", FileOperations, "Изменение прав доступа\nПримеры:\n  chmod 755 script.sh\n  chmod +x executable\n  chmod u=rw,go=r file.txt"),
+    ("chown", FileOperations, "Изменение владельца\nПример: chown user:group file.txt"),
+    ("sudo", UserManagement, "Выполнить команду с правами root\nПример: sudo apt update"),
+
+    // Архивация
+    ("tar", FileOperations, "Архивация файлов\nОпции:\n  -c создать архив\n  -x распаковать\n  -z gzip сжатие\n  -f указать файл\nПримеры:\n  tar -czf archive.tar.gz folder/ (создать архив)\n  tar -xzf archive.tar.gz (распаковать)"),
+    ("gzip", FileOperations, "Сжатие файлов\nПример: gzip file.txt"),
+    ("zip", FileOperations, "Создание zip архива\nПример: zip -r archive.zip folder/"),
+
+    // Переменные окружения
+    ("export", BuiltinCommands, "Установка переменной окружения\nПример: export PATH=$PATH:/new/path"),
+    ("echo", BuiltinCommands, "Вывод текста\nПример: echo $HOME"),
+    ("env", BuiltinCommands, "Показать переменные окружения"),
+
+    // Встроенные команды Bash
+    ("alias", BuiltinCommands, "Создание псевдонимов\nПримеры:\n  alias ll='ls -la'\n  alias rm='rm -i'"),
+    ("unalias", BuiltinCommands, "Удаление псевдонима\nПример: unalias ll"),
+    ("history", BuiltinCommands, "История команд\nПримеры:\n  history\n  history 10 (последние 10)\n  history -d 5, history -c\n  !123 (выполнить команду №123)\n  !! (предыдущая команда), sudo !!\n  !$ (последний аргумент), !!:2, !-3\n  ^old^new (заменить в предыдущей команде)"),
+    ("source", BuiltinCommands, "Выполнить команды из файла\nПример: source ~/.bashrc"),
+];
 
 pub struct BashHelp;
 
@@ -56,71 +123,9 @@ impl BashHelp {
     
     /// Найти справку по конкретной команде
     pub fn search_command(command: &str) -> Option<String> {
-        let commands: HashMap<&str, &str> = HashMap::from([
-            // Файловые операции
-            ("ls", "Показать содержимое директории\nОпции:\n  -l детальный список\n  -a показать скрытые\n  -h человеко-читаемый размер\nПример: ls -la /home"),
-            ("cd", "Сменить текущую директорию\nПримеры:\n  cd /absolute/path\n  cd relative/path\n  cd ~ (домашняя директория)\n  cd .. (на уровень выше)"),
-            ("pwd", "Показать текущую рабочую директорию"),
-            ("cp", "Копировать файлы и директории\nОпции:\n  -r рекурсивно (для папок)\n  -i подтверждение перезаписи\nПримеры:\n  cp file1 file2\n  cp -r dir1 dir2"),
-            ("mv", "Переместить или переименовать файлы\nПримеры:\n  mv oldname newname\n  mv file /target/directory/"),
-            ("rm", "Удалить файлы и директории\nОпции:\n  -r рекурсивно\n  -f принудительно\n  -i интерактивно\nПример: rm -rf folder/"),
-            ("mkdir", "Создать директорию\nОпции:\n  -p создать родительские директории\nПример: mkdir -p project/src/main"),
-            ("rmdir", "Удалить пустую директорию"),
-            ("touch", "Создать пустой файл или обновить время доступа"),
-            
-            // Работа с текстом
-            ("cat", "Показать содержимое файла\nПримеры:\n  cat file.txt\n  cat file1 file2 > combined"),
-            ("more", "Показать файл постранично"),
-            ("less", "Показать файл с возможностью прокрутки"),
-            ("head", "Показать первые N строк\nОпции:\n  -n число строк\nПример: head -n 10 file.txt"),
-            ("tail", "Показать последние N строк\nОпции:\n  -n число строк\n  -f следить за изменениями\nПример: tail -f logfile"),
-            ("grep", "Поиск текста по шаблону\nОпции:\n  -r рекурсивный поиск\n  -i игнорировать регистр\n  -n показать номера строк\nПример: grep -rn 'error' /var/log/"),
-            ("find", "Поиск файлов и директорий\nПримеры:\n  find . -name '*.txt'\n  find /home -type f -size +1M\n  find . -mtime -7 (изменены за 7 дней)"),
-            ("wc", "Подсчет строк, слов, байт\nОпции:\n  -l только строки\n  -w только слова\n  -c только байты\nПример: wc -l file.txt"),
-            
-            // Системные команды
-            ("ps", "Информация о процессах\nОпции:\n  aux все процессы\n  -ef все процессы\nПример: ps aux | grep python"),
-            ("top", "Интерактивный монитор процессов"),
-            ("htop", "Улучшенный монитор процессов (требует установки)"),
-            ("kill", "Завершить процесс\nСигналы:\n  -9 KILL (принудительно)\n  -15 TERM (корректно)\nПример: kill -9 1234"),
-            ("killall", "Завершить процессы по имени\nПример: killall firefox"),
-            ("df", "Информация о дисковом пространстве\nОпции:\n  -h человеко-читаемый\nПример: df -h"),
-            ("du", "Размер файлов/директорий\nОпции:\n  -sh суммарный размер\nПример: du -sh /home/user/"),
-            ("free", "Информация о памяти\nОпции:\n  -h человеко-читаемый\nПример: free -h"),
-            
-            // Сетевые команды
-            ("ping", "Проверка доступности хоста\nПример: ping google.com"),
-            ("curl", "Передача данных по URL\nПримеры:\n  curl -O http://example.com/file\n  curl -X POST -d 'data' http://api.example.com"),
-            ("wget", "Скачивание файлов\nПример: wget http://example.com/file.zip"),
-            ("ssh", "Подключение к удаленному хосту\nПример: ssh user@hostname"),
-            ("scp", "Копирование файлов через SSH\nПример: scp file.txt user@host:/path/"),
-            ("netstat", "Сетевые соединения\nОпции:\n  -tunlp все соединения\nПример: netstat -tunlp"),
-            
-            // Права доступа
-            ("chmod", "Изменение прав доступа\nПримеры:\n  chmod 755 script.sh\n  chmod +x executable\n  chmod u=rw,go=r file.txt"),
-            ("chown", "Изменение владельца\nПример: chown user:group file.txt"),
-            ("sudo", "Выполнить команду с правами root\nПример: sudo apt update"),
-            
-            // Архивация
-            ("tar", "Архивация файлов\nОпции:\n  -c создать архив\n  -x распаковать\n  -z gzip сжатие\n  -f указать файл\nПримеры:\n  tar -czf archive.tar.gz folder/\n  tar -xzf archive.tar.gz"),
-            ("gzip", "Сжатие файлов\nПример: gzip file.txt"),
-            ("zip", "Создание zip архива\nПример: zip -r archive.zip folder/"),
-            
-            // Переменные окружения
-            ("export", "Установка переменной окружения\nПример: export PATH=$PATH:/new/path"),
-            ("echo", "Вывод текста\nПример: echo $HOME"),
-            ("env", "Показать переменные окружения"),
-            
-            // Встроенные команды Bash
-            ("alias", "Создание псевдонимов\nПримеры:\n  alias ll='ls -la'\n  alias rm='rm -i'"),
-            ("unalias", "Удаление псевдонима\nПример: unalias ll"),
-            ("history", "История команд\nПримеры:\n  history\n  history 10 (последние 10)\n  !123 (выполнить команду №123)"),
-            ("source", "Выполнить команды из файла\nПример: source ~/.bashrc"),
-        ]);
-        
-        commands.get(command).map(|desc| {
-            format!("📖 {}\n{}\n", command, desc)
-        })
+        COMMANDS.iter()
+            .find(|(name, _, _)| *name == command)
+            .map(|(name, _, desc)| format!("📖 {}\n{}\n", name, desc))
     }
     
     fn get_file_operations() -> String {
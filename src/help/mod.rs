@@ -1,9 +1,10 @@
 pub mod bash_help;
+pub mod suggest;
 
 pub use bash_help::BashHelp;
 
 /// Основные категории справки
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HelpCategory {
     FileOperations,
     TextProcessing,
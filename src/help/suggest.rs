@@ -0,0 +1,351 @@
+//! Подсказки команд по описанию задачи на русском или английском
+//! ("как найти файл" -> find, locate, fd). Работает без сети: ищет по
+//! таблице справки BashHelp - именам, описаниям, примерам и категориям
+
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Write};
+use crate::help::bash_help::COMMANDS;
+use crate::utils::helpers;
+
+/// Сколько подсказок показывать
+pub const SUGGESTION_LIMIT: usize = 5;
+
+/// Вес совпадения в имени команды, первой строке описания, остальном
+/// тексте (опции, примеры) и названии категории
+const NAME_WEIGHT: f64 = 4.0;
+const SUMMARY_WEIGHT: f64 = 2.0;
+const DETAILS_WEIGHT: f64 = 1.0;
+const CATEGORY_WEIGHT: f64 = 0.5;
+/// Совпадение только через синоним весит меньше прямого
+const SYNONYM_FACTOR: f64 = 0.8;
+
+/// Слова запроса, не влияющие на поиск
+const STOP_WORDS: &[&str] = &[
+    "как", "мне", "можно", "нужно", "надо", "хочу", "что", "это", "для", "все", "или",
+    "по", "из", "на", "не", "во", "от", "до", "за",
+    "how", "do", "does", "can", "to", "the", "an", "in", "of", "on", "for", "with",
+    "my", "is", "and", "or", "what", "want", "need", "me", "by", "all",
+];
+
+/// Группы синонимов: слово запроса совпадает с любым словом своей группы
+const SYNONYMS: &[&[&str]] = &[
+    &["найти", "найди", "искать", "ищу", "поиск", "где", "find", "search", "locate", "look", "where"],
+    &["файл", "документ", "file", "document"],
+    &["директория", "папка", "каталог", "directory", "folder", "dir"],
+    &["удалить", "удаление", "стереть", "delete", "remove", "erase"],
+    &["копировать", "скопировать", "копия", "копирование", "copy", "duplicate"],
+    &["переместить", "переименовать", "move", "rename"],
+    &["создать", "новый", "create", "make", "new"],
+    &["показать", "посмотреть", "вывести", "просмотр", "содержимое", "show", "view", "display", "print", "list", "see"],
+    &["процесс", "программа", "process", "program", "running"],
+    &["завершить", "убить", "остановить", "kill", "stop", "terminate"],
+    &["диск", "место", "дисковый", "disk", "space"],
+    &["память", "memory", "ram"],
+    &["размер", "большой", "size", "big", "large"],
+    &["скачать", "скачивание", "загрузить", "download", "fetch"],
+    &["подключиться", "подключение", "удаленный", "сервер", "connect", "remote", "server"],
+    &["права", "доступ", "исполняемый", "permission", "access", "executable"],
+    &["архив", "архивация", "сжать", "сжатие", "archive", "compress"],
+    &["распаковать", "распаковка", "извлечь", "extract", "unpack"],
+    &["текст", "строка", "слово", "text", "line", "word", "string"],
+    &["подсчет", "посчитать", "количество", "сколько", "count", "number"],
+    &["следить", "лог", "журнал", "follow", "watch", "log"],
+    &["владелец", "owner"],
+    &["переменная", "окружение", "variable", "environment"],
+    &["история", "предыдущий", "history", "previous"],
+    &["первые", "начало", "first", "beginning"],
+    &["последние", "конец", "last", "end"],
+    &["текущий", "current"],
+    &["сеть", "сетевой", "соединение", "порт", "network", "connection", "port"],
+    &["хост", "доступность", "host", "reachable", "available"],
+    &["псевдоним", "сокращение", "alias", "shortcut"],
+    &["администратор", "суперпользователь", "root", "admin"],
+];
+
+/// Окончания, отбрасываемые при сравнении слов; длинные проверяются первыми
+const RUSSIAN_ENDINGS: &[&str] = &[
+    "ание", "ение", "ания", "ения", "иями",
+    "ями", "ами", "ого", "его", "ому", "ему", "ыми", "ими", "ать", "ять", "ить", "еть",
+    "уть", "ешь", "ает", "яет", "ует",
+    "ют", "ут", "ов", "ев", "ей", "ий", "ый", "ой", "ая", "яя", "ое", "ее", "ые", "ие",
+    "ую", "юю", "ах", "ях", "ом", "ем", "ам", "ям", "ет", "ит", "ат", "ят", "ти", "ть",
+    "ы", "и", "а", "я", "о", "е", "у", "ю", "ь", "й",
+];
+const ENGLISH_ENDINGS: &[&str] = &["ing", "ed", "es", "ly", "s", "e"];
+
+/// Подходящая команда
+#[derive(Debug, Clone)]
+pub struct Suggestion {
+    pub name: &'static str,
+    /// Первая строка описания из справки
+    pub summary: &'static str,
+    /// Что запустить: наиболее подходящий к запросу пример или просто имя
+    pub command: String,
+}
+
+/// Основа слова: без окончания, если остается хотя бы 3 буквы
+fn stem(word: &str) -> String {
+    let russian = word.chars().any(|c| ('а'..='я').contains(&c));
+    if !russian {
+        if let Some(base) = word.strip_suffix("ies") {
+            return format!("{}y", base);
+        }
+        // process, class - не множественное число
+        if word.ends_with("ss") {
+            return word.to_string();
+        }
+    }
+    let endings = if russian { RUSSIAN_ENDINGS } else { ENGLISH_ENDINGS };
+    endings.iter()
+        .filter_map(|ending| word.strip_suffix(ending))
+        .find(|base| base.chars().count() >= 3)
+        .unwrap_or(word)
+        .to_string()
+}
+
+/// Значимые слова текста в нижнем регистре
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(|word| word.to_lowercase().replace('ё', "е"))
+        .filter(|word| word.chars().count() >= 2 && !STOP_WORDS.contains(&word.as_str()))
+}
+
+/// Основа слова -> номер группы синонимов
+fn synonym_index() -> HashMap<String, usize> {
+    let mut index = HashMap::new();
+    for (group, synonyms) in SYNONYMS.iter().enumerate() {
+        for word in *synonyms {
+            index.entry(stem(word)).or_insert(group);
+        }
+    }
+    index
+}
+
+/// Слово запроса
+struct QueryWord {
+    text: String,
+    stem: String,
+    synonym_group: Option<usize>,
+}
+
+/// Основы слов и группы синонимов одного поля справки
+#[derive(Default)]
+struct Terms {
+    stems: HashSet<String>,
+    synonym_groups: HashSet<usize>,
+}
+
+impl Terms {
+    fn new(text: &str, synonyms: &HashMap<String, usize>) -> Self {
+        let mut terms = Terms::default();
+        for word in words(text) {
+            let stem = stem(&word);
+            if let Some(&group) = synonyms.get(&stem) {
+                terms.synonym_groups.insert(group);
+            }
+            terms.stems.insert(stem);
+        }
+        terms
+    }
+
+    /// 1 - слово есть в поле, SYNONYM_FACTOR - есть его синоним
+    fn matches(&self, word: &QueryWord) -> Option<f64> {
+        if self.stems.contains(&word.stem) {
+            Some(1.0)
+        } else if word.synonym_group.is_some_and(|group| self.synonym_groups.contains(&group)) {
+            Some(SYNONYM_FACTOR)
+        } else {
+            None
+        }
+    }
+}
+
+/// Проиндексированная команда из справки
+struct Document {
+    name: &'static str,
+    summary: &'static str,
+    examples: Vec<&'static str>,
+    /// Поля с весами: имя, первая строка, остальное описание, категория
+    fields: Vec<(Terms, f64)>,
+}
+
+impl Document {
+    /// Лучший вес слова среди полей; имя засчитывается и по началу
+    /// (chm -> chmod)
+    fn weight(&self, word: &QueryWord) -> Option<f64> {
+        if self.name == word.text || (word.text.len() >= 3 && self.name.starts_with(&word.text)) {
+            return Some(NAME_WEIGHT);
+        }
+        self.fields.iter()
+            .filter_map(|(terms, weight)| terms.matches(word).map(|factor| weight * factor))
+            .reduce(f64::max)
+    }
+}
+
+fn index(synonyms: &HashMap<String, usize>) -> Vec<Document> {
+    COMMANDS.iter()
+        .map(|&(name, category, description)| {
+            let (summary, details) = description.split_once('\n').unwrap_or((description, ""));
+            Document {
+                name,
+                summary,
+                examples: examples(description),
+                fields: vec![
+                    (Terms::new(name, synonyms), NAME_WEIGHT),
+                    (Terms::new(summary, synonyms), SUMMARY_WEIGHT),
+                    (Terms::new(details, synonyms), DETAILS_WEIGHT),
+                    (Terms::new(category.name(), synonyms), CATEGORY_WEIGHT),
+                ],
+            }
+        })
+        .collect()
+}
+
+/// Примеры из описания: строка "Пример: ..." или строки с отступом после
+/// "Примеры:". Пояснение в скобках в конце сохраняется - по нему тоже ищется
+fn examples(description: &'static str) -> Vec<&'static str> {
+    let mut examples = Vec::new();
+    let mut in_examples = false;
+    for line in description.lines().skip(1) {
+        if let Some(example) = line.strip_prefix("Пример: ") {
+            examples.push(example.trim());
+        } else if !line.starts_with(' ') {
+            in_examples = line == "Примеры:";
+        } else if in_examples {
+            examples.push(line.trim());
+        }
+    }
+    examples
+}
+
+/// Команда примера без пояснения: "cd ~ (домашняя директория)" -> "cd ~"
+fn strip_note(example: &str) -> &str {
+    match example.find(" (") {
+        Some(position) if example.ends_with(')') => &example[..position],
+        _ => example,
+    }
+}
+
+/// Команды, подходящие к запросу, от лучшей к худшей (не больше limit)
+pub fn suggest(query: &str, limit: usize) -> Vec<Suggestion> {
+    let synonyms = synonym_index();
+    let documents = index(&synonyms);
+    let query: Vec<QueryWord> = words(query)
+        .map(|text| {
+            let stem = stem(&text);
+            let synonym_group = synonyms.get(&stem).copied();
+            QueryWord { text, stem, synonym_group }
+        })
+        .collect();
+
+    let mut scores = vec![0.0; documents.len()];
+    for word in &query {
+        let matches: Vec<(usize, f64)> = documents.iter()
+            .enumerate()
+            .filter_map(|(i, document)| document.weight(word).map(|weight| (i, weight)))
+            .collect();
+        if matches.is_empty() {
+            continue;
+        }
+        // Редкое слово ("архив") важнее частого ("файл")
+        let rarity = (1.0 + documents.len() as f64 / matches.len() as f64).ln();
+        for (i, weight) in matches {
+            scores[i] += weight * rarity;
+        }
+    }
+
+    let mut ranked: Vec<usize> = (0..documents.len()).filter(|&i| scores[i] > 0.0).collect();
+    // Сортировка устойчивая: при равенстве - порядок справки
+    ranked.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+    ranked.into_iter()
+        .take(limit)
+        .map(|i| {
+            let document = &documents[i];
+            Suggestion {
+                name: document.name,
+                summary: document.summary,
+                command: best_example(&document.examples, &query, &synonyms)
+                    .map_or(document.name, strip_note)
+                    .to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Пример, лучше всего совпадающий с запросом; при равенстве - первый
+fn best_example(examples: &[&'static str], query: &[QueryWord], synonyms: &HashMap<String, usize>) -> Option<&'static str> {
+    let mut best: Option<(&'static str, f64)> = None;
+    for example in examples {
+        let terms = Terms::new(example, synonyms);
+        let matched: f64 = query.iter().filter_map(|word| terms.matches(word)).sum();
+        if best.is_none_or(|(_, best_matched)| matched > best_matched) {
+            best = Some((example, matched));
+        }
+    }
+    best.map(|(example, _)| example)
+}
+
+/// Нумерованный список подсказок
+pub fn format_suggestions(suggestions: &[Suggestion]) -> String {
+    suggestions.iter()
+        .enumerate()
+        .map(|(i, suggestion)| format!("{:>2}. {:<8} {}\n      $ {}", i + 1, suggestion.name, suggestion.summary, suggestion.command))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Показывает подсказки и спрашивает номер команды.
+/// None - пользователь отказался
+pub fn pick(suggestions: &[Suggestion]) -> Option<&Suggestion> {
+    println!("{}", format_suggestions(suggestions));
+    print!("Номер команды для редактирования (Enter - отмена): ");
+    let _ = io::stdout().flush();
+    let number: usize = helpers::read_input().ok()?.parse().ok()?;
+    suggestions.get(number.checked_sub(1)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(query: &str) -> Vec<&'static str> {
+        suggest(query, SUGGESTION_LIMIT).iter().map(|s| s.name).collect()
+    }
+
+    #[test]
+    fn test_stem() {
+        assert_eq!(stem("файлов"), "файл");
+        assert_eq!(stem("файлы"), stem("файл"));
+        assert_eq!(stem("директорию"), stem("директория"));
+        assert_eq!(stem("files"), stem("file"));
+        assert_eq!(stem("processes"), "process");
+        assert_eq!(stem("directories"), "directory");
+    }
+
+    #[test]
+    fn test_find_file() {
+        for query in ["как найти файл", "how do I find a file"] {
+            let names = names(query);
+            assert_eq!(names[0], "find", "{}", query);
+            assert!(names[..3].contains(&"locate") && names[..3].contains(&"fd"), "{}: {:?}", query, names);
+        }
+    }
+
+    #[test]
+    fn test_synonyms_and_partial_names() {
+        assert_eq!(names("сколько места на диске")[0], "df");
+        assert_eq!(names("распаковать архив")[0], "tar");
+        assert_eq!(names("chm")[0], "chmod");
+        assert!(names("kill process")[..2].contains(&"kill"));
+        assert!(names("абракадабра").is_empty());
+    }
+
+    #[test]
+    fn test_examples() {
+        let &(_, _, cd) = COMMANDS.iter().find(|(name, _, _)| *name == "cd").unwrap();
+        let commands: Vec<&str> = examples(cd).into_iter().map(strip_note).collect();
+        assert_eq!(commands, vec!["cd /absolute/path", "cd relative/path", "cd ~", "cd .."]);
+        let suggestion = &suggest("распаковать архив", 1)[0];
+        assert_eq!(suggestion.command, "tar -xzf archive.tar.gz");
+    }
+}
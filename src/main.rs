@@ -1,60 +1,182 @@
 mod terminal;
+// Часть API полноэкранного режима пока не используется из терминала
+#[allow(dead_code, unused_imports)]
 mod ui;
+#[allow(dead_code, unused_imports)]
 mod editor;
+#[allow(dead_code, unused_imports)]
 mod git;
+#[allow(dead_code, unused_imports)]
 mod help;
+#[allow(dead_code, unused_imports)]
 mod utils;
+mod config;
+mod cli;
+mod runners;
 
-use terminal::Terminal;
+use std::io::{IsTerminal, Read, Write};
+use std::path::Path;
+use clap::Parser;
+use cli::{Cli, Command};
+use editor::micro_like::MicroEditor;
+use git::GitManager;
+use help::{suggest, BashHelp};
+use terminal::{CommandHistory, Terminal};
+use terminal::line_editor::{LineEditor, ReadResult};
 
 fn main() {
     // Установка красивого вывода паники
     better_panic::install();
-    
-    // Обработка аргументов командной строки
-    let args: Vec<String> = std::env::args().collect();
-    
-    if args.len() > 1 {
-        match args[1].as_str() {
-            "--version" | "-v" => {
-                println!("smart-term v{}", env!("CARGO_PKG_VERSION"));
-                println!("Умный терминал с псевдографикой и Git интеграцией");
-                return;
+
+    // Ошибка в аргументах - код 2, --help и --version - 0
+    let mut cli = Cli::parse();
+
+    // Генерация файлов для пакетов не зависит от конфигурации
+    match &cli.subcommand {
+        Some(Command::Completions { shell }) => {
+            cli::write_completions(*shell, &mut std::io::stdout());
+            return;
+        }
+        Some(Command::Man) => {
+            if let Err(e) = cli::write_man_page(&mut std::io::stdout()) {
+                eprintln!("smart-term: {}", e);
+                std::process::exit(1);
             }
-            "--help" | "-h" => {
-                print_help();
-                return;
+            return;
+        }
+        _ => {}
+    }
+
+    let config = match config::Config::load(cli.config.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Ошибка конфигурации: {}", e);
+            // Явно указанный файл обязателен, ошибку в найденном файле можно пережить
+            if cli.config.is_some() {
+                std::process::exit(2);
             }
-            "--ui" | "-u" => {
-                // Запуск в псевдографическом режиме
-                if let Err(e) = ui::ncurses_like::NcursesLikeUI::new().and_then(|mut ui| ui.run()) {
-                    eprintln!("Ошибка UI: {}", e);
-                }
-                return;
+            eprintln!("Используется конфигурация по умолчанию");
+            config::Config::default()
+        }
+    };
+
+    let result = match cli.subcommand.take() {
+        Some(Command::Ui) => run_ui(&config),
+        _ if cli.ui => run_ui(&config),
+        Some(Command::Edit { file }) => {
+            let mut editor = MicroEditor::with_settings(config.editor.clone());
+            editor.open_file(&file.to_string_lossy()).and_then(|()| editor.run().map(|_| ()))
+        }
+        Some(Command::GitStatus) => GitManager::new(Path::new("."))
+            .and_then(|git| git.get_visual_status())
+            .map_err(|e| e.to_string())
+            .and_then(|status| print_output(&status)),
+        Some(Command::Help { command }) => BashHelp::search_command(&command)
+            .ok_or_else(|| format!("Команда '{}' не найдена в справке", command))
+            .and_then(|help| print_output(&help)),
+        Some(Command::Suggest { query }) => std::process::exit(run_suggest(&query, config)),
+        Some(Command::Completions { .. }) | Some(Command::Man) => Ok(()),
+        None => std::process::exit(run_terminal(cli, config)),
+    };
+
+    if let Err(e) = result {
+        eprintln!("smart-term: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Показывает подсказки; в терминале предлагает выбрать и запустить команду
+fn run_suggest(query: &str, config: config::Config) -> i32 {
+    let suggestions = suggest::suggest(query, suggest::SUGGESTION_LIMIT);
+    if suggestions.is_empty() {
+        eprintln!("smart-term: нет подходящих команд для '{}'", query);
+        return 1;
+    }
+    if !std::io::stdin().is_terminal() {
+        return match print_output(&suggest::format_suggestions(&suggestions)) {
+            Ok(()) => 0,
+            Err(e) => {
+                eprintln!("smart-term: {}", e);
+                1
             }
-            _ => {}
+        };
+    }
+    let Some(suggestion) = suggest::pick(&suggestions) else {
+        return 0;
+    };
+    // Пример с заглушками (<файл>, PATTERN) не запускается как есть -
+    // пользователь сначала редактирует его в строке ввода
+    let mut editor = LineEditor::new();
+    editor.set_initial_text(&suggestion.command);
+    match editor.read_line("$ ", &mut CommandHistory::new(0)) {
+        Ok(ReadResult::Line(line)) if !line.trim().is_empty() => Terminal::from_config(config).run_script(&line),
+        Ok(ReadResult::Interrupted) => 130,
+        Ok(_) => 0,
+        Err(e) => {
+            eprintln!("smart-term: {}", e);
+            1
         }
     }
-    
-    // Запуск основного терминала
-    let mut terminal = Terminal::new();
-    terminal.run();
 }
 
-fn print_help() {
-    println!("smart-term - Умный терминал v{}", env!("CARGO_PKG_VERSION"));
-    println!();
-    println!("Использование:");
-    println!("  smart-term              Запуск в текстовом режиме");
-    println!("  smart-term --ui         Запуск в псевдографическом режиме");
-    println!("  smart-term --version    Показать версию");
-    println!("  smart-term --help       Показать эту справку");
-    println!();
-    println!("Горячие клавиши в текстовом режиме:");
-    println!("  Ctrl+U    Переключение в псевдографический режим");
-    println!("  Tab       Автодополнение (в разработке)");
-    println!("  Стрелки   Навигация по истории команд");
-    println!();
-    println!("Документация: https://github.com/smart-term/smart-term");
-    println!("Отчет об ошибках: https://github.com/smart-term/smart-term/issues");
+/// Печатает результат подкоманды. Закрытый канал (`| head`) - не ошибка
+fn print_output(text: &str) -> Result<(), String> {
+    match writeln!(std::io::stdout().lock(), "{}", text) {
+        Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
+
+fn run_ui(config: &config::Config) -> Result<(), String> {
+    // Запуск в псевдографическом режиме
+    ui::ncurses_like::NcursesLikeUI::new()
+        .and_then(|mut ui| {
+            ui.set_keybindings(config.keybindings.clone());
+            ui.run()
+        })
+        .map_err(|e| format!("Ошибка UI: {}", e))
+}
+
+/// Интерактивный терминал или выполнение команд из -c, файла скрипта или
+/// stdin (не терминала). Возвращает код завершения процесса
+fn run_terminal(cli: Cli, config: config::Config) -> i32 {
+    let mut args = cli.args.into_iter();
+    let source = match &cli.command {
+        Some(command) => Some(command.clone()),
+        None => match args.as_slice().first() {
+            Some(path) => match std::fs::read_to_string(path) {
+                Ok(source) => Some(source),
+                Err(e) => {
+                    eprintln!("smart-term: {}: {}", path, e);
+                    return 127;
+                }
+            },
+            None if !std::io::stdin().is_terminal() => {
+                let mut source = String::new();
+                if let Err(e) = std::io::stdin().read_to_string(&mut source) {
+                    eprintln!("smart-term: ошибка чтения stdin: {}", e);
+                    return 1;
+                }
+                Some(source)
+            }
+            None => None,
+        },
+    };
+
+    let mut terminal = Terminal::from_config(config);
+    for (enabled, option) in [(cli.errexit, "errexit"), (cli.xtrace, "xtrace")] {
+        if enabled {
+            let _ = terminal.set_option(option, true);
+        }
+    }
+    match source {
+        Some(source) => {
+            // $0 - имя скрипта (или первый аргумент после -c КОМАНДА)
+            let name = args.next().unwrap_or_else(|| "smart-term".to_string());
+            terminal.set_script_args(name, args.collect());
+            terminal.run_script(&source)
+        }
+        // Запуск основного терминала
+        None => terminal.run(),
+    }
 }
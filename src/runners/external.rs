@@ -0,0 +1,271 @@
+//! Исполнители из секций [runners.<имя>] конфигурации: любой интерпретатор
+//! (node, ruby, lua, go run, sh). В режиме oneshot фрагмент записывается в
+//! файл с заданным расширением и передается команде последним аргументом,
+//! в режиме repl интерпретатор живет всю сессию (см. repl.rs). Интерпретатор
+//! не видит cd и export терминала, поэтому после их изменения сессия
+//! перезапускается в новом каталоге и окружении
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::runners::{self, repl::Repl};
+use crate::terminal::signals;
+use crate::terminal::{CommandResult, ExitStatus};
+use crate::utils::helpers;
+
+/// Как часто проверяется завершение процесса
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunnerMode {
+    /// Новый процесс на каждый фрагмент
+    OneShot,
+    /// Один процесс на сессию: переменные сохраняются
+    Repl,
+}
+
+impl RunnerMode {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "oneshot" => Some(RunnerMode::OneShot),
+            "repl" => Some(RunnerMode::Repl),
+            _ => None,
+        }
+    }
+}
+
+/// Определение исполнителя !<name>
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunnerConfig {
+    /// Префикс без '!'
+    pub name: String,
+    pub description: String,
+    /// Интерпретатор и его аргументы
+    pub command: Vec<String>,
+    /// Расширение файла фрагмента в режиме oneshot
+    pub extension: String,
+    pub mode: RunnerMode,
+    /// None - без ограничения
+    pub timeout: Option<Duration>,
+    /// Инструкция, печатающая {marker}, для режима repl: echo '{marker}'
+    pub marker: String,
+}
+
+/// Процесс режима repl и каталог с окружением, в которых он запущен
+struct Session {
+    repl: Repl,
+    cwd: String,
+    env: HashMap<String, String>,
+}
+
+pub struct ExternalRunner {
+    config: RunnerConfig,
+    /// Процесс режима repl, запускается при первом фрагменте
+    session: Option<Session>,
+    /// Каталог файлов фрагментов режима oneshot
+    dir: PathBuf,
+}
+
+impl ExternalRunner {
+    pub fn new(config: RunnerConfig) -> Self {
+        let dir = runners::cache_dir(&config.name).join(format!("session-{}", std::process::id()));
+        Self { config, session: None, dir }
+    }
+
+    pub fn config(&self) -> &RunnerConfig {
+        &self.config
+    }
+
+    /// Интерпретатор из конфигурации, как он записан
+    pub fn interpreter(&self) -> &str {
+        &self.config.command[0]
+    }
+
+    /// Путь к интерпретатору в PATH сессии
+    pub fn locate(&self, env: &HashMap<String, String>) -> Result<PathBuf, String> {
+        let program = &self.config.command[0];
+        let found = if program.contains('/') {
+            Some(PathBuf::from(program)).filter(|path| path.is_file())
+        } else {
+            runners::find_in_path(program, env.get("PATH").map_or("", String::as_str))
+        };
+        found.ok_or_else(|| format!("{} не найден", program))
+    }
+
+    /// Выполняет фрагмент в каталоге cwd. `:reset` - перезапустить интерпретатор
+    pub fn run(&mut self, code: &str, env: &HashMap<String, String>, cwd: &str) -> Result<CommandResult, String> {
+        if code.trim() == ":reset" {
+            self.session = None;
+            return Ok(CommandResult::success(format!("Сессия !{} сброшена", self.config.name)));
+        }
+        let program = self.locate(env)?;
+        match self.config.mode {
+            RunnerMode::OneShot => self.run_file(&program, code, env, cwd),
+            RunnerMode::Repl => self.run_repl(&program, code, env, cwd),
+        }
+    }
+
+    fn command(&self, program: &Path, env: &HashMap<String, String>, cwd: &str) -> Command {
+        let mut command = Command::new(program);
+        command.args(&self.config.command[1..])
+            .current_dir(cwd)
+            .env_clear()
+            .envs(env);
+        command
+    }
+
+    fn run_repl(&mut self, program: &Path, code: &str, env: &HashMap<String, String>, cwd: &str) -> Result<CommandResult, String> {
+        let name = format!("!{}", self.config.name);
+        if self.session.as_ref().is_some_and(|session| session.cwd != cwd || session.env != *env) {
+            self.session = None;
+            helpers::print_info(&format!("{}: каталог или окружение изменились, сессия перезапущена", name));
+        }
+        let session = match &mut self.session {
+            Some(session) => session,
+            None => self.session.insert(Session {
+                repl: Repl::start(&name, self.command(program, env, cwd), &self.config.marker)?,
+                cwd: cwd.to_string(),
+                env: env.clone(),
+            }),
+        };
+        match session.repl.eval(code, self.config.timeout) {
+            Ok(output) => Ok(CommandResult::success(output)),
+            Err(e) => {
+                self.session = None;
+                Err(e)
+            }
+        }
+    }
+
+    fn run_file(&self, program: &Path, code: &str, env: &HashMap<String, String>, cwd: &str) -> Result<CommandResult, String> {
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("{}: {}", self.dir.display(), e))?;
+        let file = self.dir.join(format!("snippet.{}", self.config.extension));
+        std::fs::write(&file, code)
+            .map_err(|e| format!("{}: {}", file.display(), e))?;
+
+        let mut command = self.command(program, env, cwd);
+        // Группа процессов не владеет терминалом, поэтому stdin не передается
+        command.arg(&file)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        // Своя группа процессов: по timeout завершаются и дочерние процессы (go run)
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
+        let mut child = command.spawn()
+            .map_err(|e| format!("Не удалось запустить {}: {}", program.display(), e))?;
+        let pgid = child.id() as i32;
+
+        // Вывод читается в потоках, чтобы заполненный канал не остановил процесс
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+        let stdout = thread::spawn(move || read_all(stdout));
+        let stderr = thread::spawn(move || read_all(stderr));
+
+        signals::set_foreground(Some(pgid));
+        let deadline = self.config.timeout.map(|timeout| Instant::now() + timeout);
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break Ok(status),
+                Ok(None) if deadline.is_some_and(|deadline| Instant::now() >= deadline) => {
+                    unsafe {
+                        libc::kill(-pgid, libc::SIGKILL);
+                    }
+                    let _ = child.wait();
+                    break Err(format!("!{}: превышено время выполнения", self.config.name));
+                }
+                Ok(None) => thread::sleep(POLL_INTERVAL),
+                Err(e) => break Err(format!("Ошибка выполнения !{}: {}", self.config.name, e)),
+            }
+        };
+        signals::set_foreground(None);
+
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+        match status {
+            Ok(status) => Ok(CommandResult::from_output(std::process::Output { status, stdout, stderr })),
+            Err(error) => Ok(CommandResult {
+                output: String::from_utf8_lossy(&stdout).to_string(),
+                stderr: String::from_utf8_lossy(&stderr).to_string(),
+                status: ExitStatus::Code(124),
+                ..CommandResult::error(error)
+            }),
+        }
+    }
+}
+
+fn read_all(stream: Option<impl std::io::Read>) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    if let Some(mut stream) = stream {
+        let _ = stream.read_to_end(&mut buffer);
+    }
+    buffer
+}
+
+impl Drop for ExternalRunner {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(mode: RunnerMode, timeout: Option<Duration>) -> RunnerConfig {
+        RunnerConfig {
+            name: "sh".to_string(),
+            description: "выполнить sh".to_string(),
+            command: vec!["sh".to_string()],
+            extension: "sh".to_string(),
+            mode,
+            timeout,
+            marker: "echo '{marker}'".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_oneshot() {
+        let env: HashMap<String, String> = std::env::vars().collect();
+        let dir = tempfile::tempdir().unwrap();
+        let cwd = dir.path().display().to_string();
+        let mut runner = ExternalRunner::new(RunnerConfig { name: "sh-test-oneshot".to_string(), ..config(RunnerMode::OneShot, Some(Duration::from_millis(300))) });
+
+        let result = runner.run("x=5\necho $x; pwd", &env, &cwd).unwrap();
+        assert_eq!(result.output, format!("5\n{}\n", cwd));
+        assert!(runner.run("echo $x", &env, &cwd).unwrap().output.trim().is_empty());
+        assert_eq!(runner.run("exit 3", &env, &cwd).unwrap().exit_code(), 3);
+
+        let started = Instant::now();
+        let result = runner.run("echo start; sleep 5", &env, &cwd).unwrap();
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(result.exit_code(), 124);
+        assert_eq!(result.output, "start\n");
+    }
+
+    #[test]
+    fn test_repl_and_missing_interpreter() {
+        let env: HashMap<String, String> = std::env::vars().collect();
+        let mut runner = ExternalRunner::new(RunnerConfig { name: "sh-test-repl".to_string(), ..config(RunnerMode::Repl, None) });
+        assert!(runner.run("x=5", &env, "/").unwrap().success);
+        assert_eq!(runner.run("echo $x", &env, "/").unwrap().output, "5");
+        runner.run(":reset", &env, "/").unwrap();
+        assert_eq!(runner.run("echo \"[$x]\"", &env, "/").unwrap().output, "[]");
+
+        // cd и export в терминале перезапускают сессию
+        assert!(runner.run("x=5", &env, "/").unwrap().success);
+        assert_eq!(runner.run("echo \"[$x]\"; pwd", &env, "/tmp").unwrap().output, "[]\n/tmp");
+        let mut exported = env.clone();
+        exported.insert("ST_RUNNER_VAR".to_string(), "1".to_string());
+        assert_eq!(runner.run("echo $ST_RUNNER_VAR", &exported, "/tmp").unwrap().output, "1");
+
+        let missing = ExternalRunner::new(RunnerConfig { command: vec!["no-such-interpreter".to_string()], ..config(RunnerMode::OneShot, None) });
+        assert_eq!(missing.locate(&env).unwrap_err(), "no-such-interpreter не найден");
+    }
+}
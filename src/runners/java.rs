@@ -0,0 +1,438 @@
+//! !java: фрагменты выполняются в долгоживущем jshell, поэтому переменные и
+//! методы остаются доступны между вызовами. Без jshell (JDK 8) фрагмент
+//! оборачивается в класс и компилируется javac; объявления переменных и
+//! методы повторяются в следующих фрагментах. JDK ищется в $JAVA_HOME/bin,
+//! затем в PATH
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use crate::runners::{self, split_statements};
+use crate::runners::repl::Repl;
+use crate::terminal::CommandResult;
+
+/// Инструкция, печатающая метку конца вывода фрагмента
+const JSHELL_MARKER: &str = "System.out.println(\"{marker}\")";
+
+/// Краткие ответы jshell ($1 ==> 6) без приглашения
+const JSHELL_SETUP: &[&str] = &[
+    "/set mode smartterm concise -quiet",
+    "/set prompt smartterm \"\" \"\"",
+    "/set feedback smartterm",
+];
+
+const INSTALL_HINT: &str = "установите JDK (sudo apt install default-jdk) или укажите JAVA_HOME";
+
+/// Класс, в который оборачивается фрагмент для javac
+const CLASS_NAME: &str = "Snippet";
+
+/// Слова, с которых начинаются инструкции, а не объявления методов
+const STATEMENT_KEYWORDS: &[&str] = &[
+    "if", "for", "while", "do", "switch", "try", "synchronized", "return", "throw", "new", "else",
+];
+
+/// Инструменты найденного JDK
+#[derive(Debug, Clone, PartialEq)]
+struct Jdk {
+    jshell: Option<PathBuf>,
+    javac: Option<PathBuf>,
+    java: Option<PathBuf>,
+}
+
+/// Инструменты JDK: сначала $JAVA_HOME/bin, затем PATH
+fn find_jdk(env: &HashMap<String, String>) -> Result<Jdk, String> {
+    let java_home = env.get("JAVA_HOME").filter(|home| !home.is_empty());
+    let search_path = env.get("PATH").map_or("", String::as_str);
+    let find = |tool: &str| {
+        java_home
+            .map(|home| Path::new(home).join("bin").join(tool))
+            .filter(|path| path.is_file())
+            .or_else(|| runners::find_in_path(tool, search_path))
+    };
+    let jdk = Jdk { jshell: find("jshell"), javac: find("javac"), java: find("java") };
+    if jdk.jshell.is_some() || (jdk.javac.is_some() && jdk.java.is_some()) {
+        return Ok(jdk);
+    }
+    Err(match java_home {
+        Some(home) => format!("JDK не найден: в JAVA_HOME={} нет bin/jshell и bin/javac; {}", home, INSTALL_HINT),
+        None => format!("JDK не найден: {}", INSTALL_HINT),
+    })
+}
+
+fn start_jshell(path: &Path, env: &HashMap<String, String>) -> Result<Repl, String> {
+    let mut command = Command::new(path);
+    command.arg("-q").env_clear().envs(env);
+    let mut repl = Repl::start("jshell", command, JSHELL_MARKER)?;
+    // Приглашения до /set prompt отбрасываются вместе с выводом настройки
+    repl.eval(&JSHELL_SETUP.join("\n"), None)?;
+    Ok(repl)
+}
+
+fn eval_jshell(repl: &mut Repl, code: &str) -> Result<CommandResult, String> {
+    let output = repl.eval(code, None)?;
+    let failed = output.lines().any(|line| line.starts_with("|  Error:") || line.starts_with("|  Exception"));
+    Ok(if failed { CommandResult::error(output) } else { CommandResult::success(output) })
+}
+
+/// Сборка фрагмента классом через javac и запуск java
+struct Compiler {
+    javac: PathBuf,
+    java: PathBuf,
+    dir: PathBuf,
+    /// Методы и вложенные классы из предыдущих фрагментов
+    members: Vec<String>,
+    /// Объявления переменных, повторяемые в начале main
+    declarations: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SnippetKind {
+    Member,
+    Declarations,
+    /// Инструкции; последнее выражение без ';' печатается
+    Statements,
+}
+
+impl Compiler {
+    fn run(&mut self, code: &str, env: &HashMap<String, String>) -> Result<CommandResult, String> {
+        let kind = classify(code);
+        let statements = split_statements(code);
+        let printed = kind == SnippetKind::Statements && statements.last().is_some_and(|last| !last.ends_with(';') && !last.ends_with('}'));
+
+        let mut result = self.compile_and_run(code, kind, printed, env)?;
+        // void-метод нельзя напечатать - выполняем как инструкцию
+        if printed && result.error.as_deref().is_some_and(|error| error.contains("'void' type not allowed here")) {
+            result = self.compile_and_run(code, kind, false, env)?;
+        }
+        if result.success {
+            match kind {
+                SnippetKind::Member => self.members.push(member_code(code)),
+                SnippetKind::Declarations => self.declarations.push(code.trim().to_string()),
+                SnippetKind::Statements => {}
+            }
+        }
+        Ok(result)
+    }
+
+    fn compile_and_run(&self, code: &str, kind: SnippetKind, printed: bool, env: &HashMap<String, String>) -> Result<CommandResult, String> {
+        let (source, first_line) = self.generate(code, kind, printed);
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("{}: {}", self.dir.display(), e))?;
+        let source_path = self.dir.join(format!("{}.java", CLASS_NAME));
+        std::fs::write(&source_path, source)
+            .map_err(|e| format!("{}: {}", source_path.display(), e))?;
+
+        let compiled = Command::new(&self.javac)
+            .arg("-d")
+            .arg(&self.dir)
+            .arg(&source_path)
+            .env_clear()
+            .envs(env)
+            .output()
+            .map_err(|e| format!("Не удалось запустить {}: {}", self.javac.display(), e))?;
+        if !compiled.status.success() {
+            let errors = map_errors(&String::from_utf8_lossy(&compiled.stderr), &source_path, first_line, code.lines().count());
+            return Ok(CommandResult::error(errors));
+        }
+        if kind == SnippetKind::Member {
+            return Ok(CommandResult::success(String::new()));
+        }
+
+        let output = Command::new(&self.java)
+            .arg("-cp")
+            .arg(&self.dir)
+            .arg(CLASS_NAME)
+            .env_clear()
+            .envs(env)
+            .stdin(Stdio::inherit())
+            .output()
+            .map_err(|e| format!("Не удалось запустить {}: {}", self.java.display(), e))?;
+        Ok(CommandResult::from_output(output))
+    }
+
+    /// Текст класса и номер строки, с которой начинается фрагмент
+    fn generate(&self, code: &str, kind: SnippetKind, printed: bool) -> (String, usize) {
+        let mut source = format!("public class {} {{\n", CLASS_NAME);
+        for member in &self.members {
+            source.push_str(member);
+            source.push('\n');
+        }
+        if kind == SnippetKind::Member {
+            let first_line = source.lines().count() + 1;
+            source.push_str(&member_code(code));
+            source.push_str("\npublic static void main(String[] args) {}\n}\n");
+            return (source, first_line);
+        }
+
+        source.push_str("public static void main(String[] args) throws Exception {\n");
+        for declaration in &self.declarations {
+            source.push_str(declaration);
+            source.push('\n');
+        }
+        let first_line = source.lines().count() + 1;
+        if printed {
+            // Последнее выражение - аргумент println, строки фрагмента не сдвигаются
+            let code = code.trim_end();
+            let last = split_statements(code).last().copied().unwrap_or("");
+            source.push_str(&code[..code.len() - last.len()]);
+            source.push_str(&format!("System.out.println({});", last));
+        } else {
+            // Как в jshell, ';' после последней инструкции необязательна
+            let code = code.trim_end();
+            source.push_str(code);
+            if !code.ends_with(';') && !code.ends_with('}') {
+                source.push(';');
+            }
+        }
+        source.push_str("\n}\n}\n");
+        (source, first_line)
+    }
+}
+
+impl Drop for Compiler {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+enum Backend {
+    Shell(Repl),
+    Compiler(Compiler),
+}
+
+pub struct JavaRunner {
+    /// Запускается при первом !java
+    backend: Option<Backend>,
+}
+
+impl JavaRunner {
+    pub fn new() -> Self {
+        Self { backend: None }
+    }
+
+    /// jshell или, для JDK 8, javac
+    pub fn locate(env: &HashMap<String, String>) -> Result<PathBuf, String> {
+        let jdk = find_jdk(env)?;
+        jdk.jshell.or(jdk.javac).ok_or_else(|| format!("JDK не найден: {}", INSTALL_HINT))
+    }
+
+    /// Выполняет фрагмент. `:reset` - начать новую сессию
+    pub fn run(&mut self, code: &str, env: &HashMap<String, String>) -> Result<CommandResult, String> {
+        if code.trim() == ":reset" {
+            self.backend = None;
+            return Ok(CommandResult::success("Сессия Java сброшена".to_string()));
+        }
+        if !runners::is_complete(code) {
+            return Err("Незавершенный Java код: не закрыты скобки или кавычки".to_string());
+        }
+
+        let backend = match &mut self.backend {
+            Some(backend) => backend,
+            None => self.backend.insert(Self::start(env)?),
+        };
+        let result = match backend {
+            // jshell читает построчно: многострочный фрагмент отдается целиком
+            Backend::Shell(repl) => eval_jshell(repl, code),
+            Backend::Compiler(compiler) => compiler.run(code, env),
+        };
+        if result.is_err() {
+            // Процесс завершился - следующий вызов начнет новую сессию
+            self.backend = None;
+        }
+        result
+    }
+
+    fn start(env: &HashMap<String, String>) -> Result<Backend, String> {
+        let jdk = find_jdk(env)?;
+        if let Some(jshell) = &jdk.jshell {
+            return start_jshell(jshell, env).map(Backend::Shell);
+        }
+        match (jdk.javac, jdk.java) {
+            (Some(javac), Some(java)) => Ok(Backend::Compiler(Compiler {
+                javac,
+                java,
+                dir: runners::cache_dir("java").join(format!("session-{}", std::process::id())),
+                members: Vec::new(),
+                declarations: Vec::new(),
+            })),
+            _ => Err(format!("JDK не найден: {}", INSTALL_HINT)),
+        }
+    }
+}
+
+fn classify(code: &str) -> SnippetKind {
+    let code = code.trim();
+    let statements = split_statements(code);
+    if statements.len() == 1 && is_member(code) {
+        return SnippetKind::Member;
+    }
+    if !statements.is_empty() && statements.iter().all(|statement| is_declaration(statement)) {
+        return SnippetKind::Declarations;
+    }
+    SnippetKind::Statements
+}
+
+/// int square(int x) { ... }, class Point { ... }, record P(int x) {}
+fn is_member(code: &str) -> bool {
+    let Some((head, _)) = code.split_once('{') else {
+        return false;
+    };
+    if !code.ends_with('}') {
+        return false;
+    }
+    let words: Vec<&str> = head.split_whitespace()
+        .filter(|word| !matches!(*word, "public" | "private" | "protected" | "static" | "final" | "abstract"))
+        .collect();
+    if words.iter().any(|word| ["class", "interface", "enum", "record"].contains(word)) {
+        return true;
+    }
+    // Тип и имя метода перед списком параметров
+    let Some((signature, _)) = head.split_once('(') else {
+        return false;
+    };
+    let names: Vec<&str> = signature.split_whitespace()
+        .filter(|word| !matches!(*word, "public" | "private" | "protected" | "static" | "final" | "abstract"))
+        .collect();
+    names.len() >= 2 && !STATEMENT_KEYWORDS.contains(&names[0]) && !signature.contains('=')
+}
+
+/// int x = 5;  List<String> names = new ArrayList<>();  var s = "a";
+fn is_declaration(statement: &str) -> bool {
+    let Some(statement) = statement.strip_suffix(';') else {
+        return false;
+    };
+    let Some(index) = statement.find('=') else {
+        return false;
+    };
+    if statement[index + 1..].starts_with('=') {
+        return false;
+    }
+    let target = &statement[..index];
+    let words: Vec<&str> = target.split_whitespace().filter(|word| *word != "final").collect();
+    words.len() >= 2 && !target.contains(['(', '.', '[']) && !STATEMENT_KEYWORDS.contains(&words[0])
+}
+
+/// Методы вызываются из static main, поэтому тоже должны быть static
+fn member_code(code: &str) -> String {
+    let code = code.trim();
+    if code.split_whitespace().take_while(|word| !word.contains('(') && !word.contains('{')).any(|word| word == "static") {
+        code.to_string()
+    } else {
+        format!("static {}", code)
+    }
+}
+
+/// Сообщения javac "Snippet.java:5: error: ..." с номерами строк фрагмента
+fn map_errors(stderr: &str, source_path: &Path, first_line: usize, line_count: usize) -> String {
+    let prefix = format!("{}:", source_path.display());
+    stderr.lines()
+        .filter(|line| !line.ends_with(" error") && !line.ends_with(" errors"))
+        .map(|line| {
+            let Some(rest) = line.strip_prefix(&prefix) else {
+                return line.to_string();
+            };
+            let Some((line_number, message)) = rest.split_once(':') else {
+                return line.to_string();
+            };
+            match line_number.parse::<usize>() {
+                Ok(number) if number >= first_line && number < first_line + line_count.max(1) => {
+                    format!("!java:{}:{}", number - first_line + 1, message)
+                }
+                _ => format!("(определения сессии):{}", message),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify("int square(int x) { return x * x; }"), SnippetKind::Member);
+        assert_eq!(classify("static class Point { int x; }"), SnippetKind::Member);
+        assert_eq!(classify("record P(int x) {}"), SnippetKind::Member);
+        assert_eq!(classify("int x = 5;"), SnippetKind::Declarations);
+        assert_eq!(classify("Map<String, Integer> m = new HashMap<>(); var s = \"a=b\";"), SnippetKind::Declarations);
+        assert_eq!(classify("x = 5;"), SnippetKind::Statements);
+        assert_eq!(classify("if (x == 5) { y(); }"), SnippetKind::Statements);
+        assert_eq!(classify("for (int i = 0; i < 3; i++) { f(i); }"), SnippetKind::Statements);
+        assert_eq!(classify("x + 1"), SnippetKind::Statements);
+    }
+
+    #[test]
+    fn test_find_jdk() {
+        let home = tempfile::tempdir().unwrap();
+        let mut env = HashMap::new();
+        env.insert("PATH".to_string(), String::new());
+        env.insert("JAVA_HOME".to_string(), home.path().display().to_string());
+        let error = find_jdk(&env).unwrap_err();
+        assert!(error.contains("JAVA_HOME") && error.contains("default-jdk"), "{}", error);
+
+        std::fs::create_dir(home.path().join("bin")).unwrap();
+        std::fs::write(home.path().join("bin/jshell"), "").unwrap();
+        let jdk = find_jdk(&env).unwrap();
+        assert_eq!(jdk.jshell, Some(home.path().join("bin/jshell")));
+        assert_eq!(jdk.javac, None);
+    }
+
+    #[test]
+    fn test_generate_and_map_errors() {
+        let compiler = Compiler {
+            javac: PathBuf::from("javac"),
+            java: PathBuf::from("java"),
+            dir: PathBuf::from("/tmp/s"),
+            members: vec!["static int one() { return 1; }".to_string()],
+            declarations: vec!["int x = 2;".to_string()],
+        };
+        let (source, first_line) = compiler.generate("int y = x;\ny + z", SnippetKind::Statements, true);
+        let lines: Vec<&str> = source.lines().collect();
+        assert_eq!(&lines[first_line - 1..=first_line], &["int y = x;", "System.out.println(y + z);"]);
+        assert_eq!(member_code("int sq(int v) { return v * v; }"), "static int sq(int v) { return v * v; }");
+
+        let stderr = format!("/tmp/s/Snippet.java:{}: error: cannot find symbol\n1 error\n", first_line);
+        assert_eq!(
+            map_errors(&stderr, Path::new("/tmp/s/Snippet.java"), first_line, 2),
+            "!java:1: error: cannot find symbol"
+        );
+    }
+
+    #[test]
+    fn test_jshell_session() {
+        let env: HashMap<String, String> = std::env::vars().collect();
+        let Ok(Jdk { jshell: Some(_), .. }) = find_jdk(&env) else {
+            return;
+        };
+        let mut runner = JavaRunner::new();
+        assert!(runner.run("int x = 20;", &env).unwrap().success);
+        assert!(runner.run("int square(int v) { return v * v; }", &env).unwrap().success);
+        assert!(runner.run("square(x) + 1", &env).unwrap().output.contains("401"));
+        assert_eq!(runner.run("System.out.print(\"no newline\")", &env).unwrap().output, "no newline");
+        assert!(!runner.run("nope(1)", &env).unwrap().success);
+        assert!(runner.run("if (x > 1) {", &env).is_err());
+    }
+
+    #[test]
+    fn test_compiler_fallback() {
+        let env: HashMap<String, String> = std::env::vars().collect();
+        let Ok(Jdk { javac: Some(javac), java: Some(java), .. }) = find_jdk(&env) else {
+            return;
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let mut compiler = Compiler {
+            javac,
+            java,
+            dir: dir.path().to_path_buf(),
+            members: Vec::new(),
+            declarations: Vec::new(),
+        };
+        assert!(compiler.run("int x = 20;", &env).unwrap().success);
+        assert!(compiler.run("int square(int v) { return v * v; }", &env).unwrap().success);
+        assert_eq!(compiler.run("square(x) + 1", &env).unwrap().output, "401\n");
+        assert_eq!(compiler.run("System.out.println(\"hi\")", &env).unwrap().output, "hi\n");
+        let failed = compiler.run("int y = 1;\nnope(y)", &env).unwrap();
+        assert!(failed.error.unwrap().starts_with("!java:2:"));
+    }
+}
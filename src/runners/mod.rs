@@ -0,0 +1,138 @@
+//! Выполнение фрагментов кода на других языках: встроенные !rust, !java,
+//! !python и исполнители из секций [runners.<имя>] конфигурации
+
+pub mod rust;
+pub mod java;
+pub mod python;
+pub mod repl;
+pub mod external;
+pub mod registry;
+
+pub use rust::RustRunner;
+pub use java::JavaRunner;
+pub use python::PythonRunner;
+pub use external::{ExternalRunner, RunnerConfig, RunnerMode};
+pub use registry::{RunnerRegistry, BUILTIN_RUNNERS};
+
+use std::path::{Path, PathBuf};
+
+/// Каталог кэша для языка: $XDG_CACHE_HOME/smart-term/<lang>
+/// (~/.cache/smart-term/<lang>), без HOME - во временном каталоге
+pub fn cache_dir(language: &str) -> PathBuf {
+    std::env::var("XDG_CACHE_HOME")
+        .ok()
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var("HOME").ok().map(|home| PathBuf::from(home).join(".cache")))
+        .unwrap_or_else(std::env::temp_dir)
+        .join("smart-term")
+        .join(language)
+}
+
+/// Исполняемый файл program в каталогах search_path
+pub fn find_in_path(program: &str, search_path: &str) -> Option<PathBuf> {
+    use std::os::unix::fs::PermissionsExt;
+    search_path.split(':')
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| Path::new(dir).join(program))
+        .find(|path| path.metadata().is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0))
+}
+
+/// Проходит код, пропуская строки и символьные литералы, и вызывает
+/// on_statement_end для каждой ';' вне скобок. Возвращает глубину скобок
+/// в конце (незакрытая строка считается открытой скобкой)
+fn scan(code: &str, mut on_statement_end: impl FnMut(usize)) -> usize {
+    let mut depth = 0usize;
+    let mut chars = code.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            '"' => {
+                let mut escaped = false;
+                let mut closed = false;
+                for (_, c) in chars.by_ref() {
+                    match c {
+                        _ if escaped => escaped = false,
+                        '\\' => escaped = true,
+                        '"' => {
+                            closed = true;
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                if !closed {
+                    return depth + 1;
+                }
+            }
+            '\'' => {
+                // 'a', '\n', '\u{263a}'; без закрывающей кавычки - время жизни
+                let mut ahead = chars.clone();
+                match (ahead.next(), ahead.next()) {
+                    (Some((_, '\\')), _) => {
+                        chars.next();
+                        chars.next();
+                        for (_, c) in chars.by_ref() {
+                            if c == '\'' {
+                                break;
+                            }
+                        }
+                    }
+                    (Some(_), Some((_, '\''))) => {
+                        chars.next();
+                        chars.next();
+                    }
+                    _ => {}
+                }
+            }
+            ';' if depth == 0 => on_statement_end(i),
+            _ => {}
+        }
+    }
+    depth
+}
+
+/// Разбивает код на инструкции по ';' верхнего уровня (вне скобок, строк
+/// и символьных литералов). Последняя часть может быть без ';'
+pub fn split_statements(code: &str) -> Vec<&str> {
+    let mut statements = Vec::new();
+    let mut start = 0;
+    scan(code, |end| {
+        statements.push(code[start..=end].trim());
+        start = end + 1;
+    });
+    let rest = code[start..].trim();
+    if !rest.is_empty() {
+        statements.push(rest);
+    }
+    statements
+}
+
+/// Все скобки и строки закрыты - фрагмент можно отдавать интерпретатору
+pub fn is_complete(code: &str) -> bool {
+    scan(code, |_| {}) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_statements() {
+        assert_eq!(split_statements("let x = 1; x + 1"), vec!["let x = 1;", "x + 1"]);
+        assert_eq!(split_statements("let s = \"a;b\"; f(1; 2)"), vec!["let s = \"a;b\";", "f(1; 2)"]);
+        assert_eq!(split_statements("fn f() { 1; 2 }"), vec!["fn f() { 1; 2 }"]);
+        assert_eq!(split_statements("let c = ';'; c"), vec!["let c = ';';", "c"]);
+        assert_eq!(split_statements("fn f<'a>(s: &'a str) -> &'a str { s }"), vec!["fn f<'a>(s: &'a str) -> &'a str { s }"]);
+        assert!(split_statements("  ").is_empty());
+    }
+
+    #[test]
+    fn test_is_complete() {
+        assert!(is_complete("for (int i = 0; i < 3; i++) { f(i); }"));
+        assert!(!is_complete("for (int i = 0; i < 3; i++) {"));
+        assert!(!is_complete("String s = \"abc"));
+        assert!(is_complete("char c = '{';"));
+    }
+}
@@ -0,0 +1,402 @@
+//! !python: один процесс python3 на сессию, поэтому импорты и переменные
+//! сохраняются между вызовами. Запрос - кадр "<длина cwd> <длина кода>\n"
+//! с байтами каталога и кода, ответ - "<статус> <длина stdout> <длина stderr>\n"
+//! с перехваченным выводом. Интерпретатор берется из виртуального окружения
+//! ($VIRTUAL_ENV, .venv или venv в текущем каталоге и выше), иначе python3
+//! из PATH; при смене окружения сессия начинается заново. Ctrl+C прерывает
+//! фрагмент (KeyboardInterrupt), повторный Ctrl+C завершает сессию
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+use crate::runners;
+use crate::terminal::signals;
+use crate::terminal::{CommandResult, ExitStatus};
+
+/// Программа на стороне интерпретатора. Кадры идут через копии stdin и
+/// stdout; сам stdout перенаправлен в stderr, чтобы вывод os.system и
+/// дочерних процессов не попал в протокол. Между запросами SIGINT игнорируется
+const DRIVER: &str = r#"
+import ast, io, os, signal, sys, traceback
+
+requests = os.fdopen(os.dup(0), 'rb')
+responses = os.fdopen(os.dup(1), 'wb')
+os.dup2(os.open(os.devnull, os.O_RDONLY), 0)
+os.dup2(2, 1)
+sys.stdin = open(os.devnull)
+namespace = {'__name__': '__main__', '__builtins__': __builtins__}
+
+def read(size):
+    data = requests.read(size)
+    if len(data) < size:
+        sys.exit(0)
+    return data
+
+def run(code):
+    tree = ast.parse(code, '<!python>')
+    last = None
+    if tree.body and isinstance(tree.body[-1], ast.Expr):
+        last = ast.Expression(tree.body.pop().value)
+    exec(compile(tree, '<!python>', 'exec'), namespace)
+    if last is not None:
+        value = eval(compile(last, '<!python>', 'eval'), namespace)
+        if value is not None:
+            namespace['_'] = value
+            print(repr(value))
+
+while True:
+    signal.signal(signal.SIGINT, signal.SIG_IGN)
+    header = requests.readline()
+    if not header:
+        break
+    cwd_size, code_size = map(int, header.split())
+    cwd = read(cwd_size).decode()
+    code = read(code_size).decode()
+    sys.stdout, sys.stderr = io.StringIO(), io.StringIO()
+    status = 'ok'
+    try:
+        os.chdir(cwd)
+        signal.signal(signal.SIGINT, signal.default_int_handler)
+        run(code)
+    except SystemExit as e:
+        if e.code is None or isinstance(e.code, int):
+            status = 'exit:%d' % (e.code or 0)
+        else:
+            sys.stderr.write('%s\n' % e.code)
+            status = 'exit:1'
+    except BaseException as e:
+        status = 'interrupted' if isinstance(e, KeyboardInterrupt) else 'error'
+        error = traceback.TracebackException.from_exception(e)
+        stack = list(error.stack)
+        while stack and stack[0].filename != '<!python>':
+            stack.pop(0)
+        error.stack = traceback.StackSummary.from_list(stack)
+        sys.stderr.write(''.join(error.format()))
+    finally:
+        signal.signal(signal.SIGINT, signal.SIG_IGN)
+    out = sys.stdout.getvalue().encode()
+    err = sys.stderr.getvalue().encode()
+    sys.stdout, sys.stderr = sys.__stdout__, sys.__stderr__
+    responses.write(('%s %d %d\n' % (status, len(out), len(err))).encode() + out + err)
+    responses.flush()
+    if status.startswith('exit:'):
+        break
+"#;
+
+/// Каталоги виртуальных окружений, которые ищутся от текущего каталога вверх
+const VENV_DIRS: &[&str] = &[".venv", "venv"];
+
+const INSTALL_HINT: &str = "Python не найден: установите python3 (sudo apt install python3)";
+
+/// Как часто проверяется Ctrl+C во время ожидания ответа
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Интерпретатор и виртуальное окружение, к которому он относится
+#[derive(Debug, Clone, PartialEq)]
+struct Interpreter {
+    path: PathBuf,
+    venv: Option<PathBuf>,
+}
+
+/// $VIRTUAL_ENV, затем .venv или venv в cwd и выше, затем python3 или python из PATH
+fn find_interpreter(cwd: &Path, env: &HashMap<String, String>) -> Result<Interpreter, String> {
+    let active = env.get("VIRTUAL_ENV").filter(|dir| !dir.is_empty()).map(PathBuf::from);
+    let nearby = cwd.ancestors()
+        .flat_map(|dir| VENV_DIRS.iter().map(move |name| dir.join(name)))
+        .filter(|venv| venv.join("pyvenv.cfg").is_file());
+    for venv in active.into_iter().chain(nearby) {
+        let path = venv.join("bin").join("python");
+        if path.is_file() {
+            return Ok(Interpreter { path, venv: Some(venv) });
+        }
+    }
+
+    let search_path = env.get("PATH").map_or("", String::as_str);
+    ["python3", "python"].iter()
+        .find_map(|name| runners::find_in_path(name, search_path))
+        .map(|path| Interpreter { path, venv: None })
+        .ok_or_else(|| INSTALL_HINT.to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Error,
+    Interrupted,
+    /// exit() или sys.exit() во фрагменте: сессия завершается
+    Exit(i32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Response {
+    status: Status,
+    stdout: String,
+    stderr: String,
+}
+
+/// Заголовок ответа: статус и длины stdout и stderr в байтах
+fn parse_header(line: &str) -> Option<(Status, usize, usize)> {
+    let mut parts = line.split_whitespace();
+    let status = match parts.next()? {
+        "ok" => Status::Ok,
+        "error" => Status::Error,
+        "interrupted" => Status::Interrupted,
+        other => Status::Exit(other.strip_prefix("exit:")?.parse().ok()?),
+    };
+    let stdout = parts.next()?.parse().ok()?;
+    let stderr = parts.next()?.parse().ok()?;
+    parts.next().is_none().then_some((status, stdout, stderr))
+}
+
+/// Читает кадры ответов, пока интерпретатор не закроет stdout
+fn read_responses(stdout: ChildStdout, sender: Sender<Response>) {
+    let mut reader = BufReader::new(stdout);
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).unwrap_or(0) == 0 {
+            return;
+        }
+        let Some((status, stdout_size, stderr_size)) = parse_header(&header) else {
+            return;
+        };
+        let mut stdout = vec![0; stdout_size];
+        let mut stderr = vec![0; stderr_size];
+        if reader.read_exact(&mut stdout).and_then(|()| reader.read_exact(&mut stderr)).is_err() {
+            return;
+        }
+        let response = Response {
+            status,
+            stdout: String::from_utf8_lossy(&stdout).to_string(),
+            stderr: String::from_utf8_lossy(&stderr).to_string(),
+        };
+        if sender.send(response).is_err() {
+            return;
+        }
+    }
+}
+
+struct Session {
+    interpreter: Interpreter,
+    child: Child,
+    requests: ChildStdin,
+    responses: Receiver<Response>,
+}
+
+impl Session {
+    fn start(interpreter: Interpreter, env: &HashMap<String, String>) -> Result<Self, String> {
+        let mut command = Command::new(&interpreter.path);
+        command.arg("-c")
+            .arg(DRIVER)
+            .env_clear()
+            .envs(env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit());
+        // Как после source venv/bin/activate: pip и скрипты окружения в PATH
+        if let Some(venv) = &interpreter.venv {
+            let path = env.get("PATH").map_or(String::new(), |path| format!(":{}", path));
+            command.env("VIRTUAL_ENV", venv)
+                .env("PATH", format!("{}{}", venv.join("bin").display(), path));
+        }
+        // Своя группа процессов: Ctrl+C пересылается только во время фрагмента
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
+        let mut child = command.spawn()
+            .map_err(|e| format!("Не удалось запустить {}: {}", interpreter.path.display(), e))?;
+        let requests = child.stdin.take().ok_or("python: нет stdin")?;
+        let stdout = child.stdout.take().ok_or("python: нет stdout")?;
+
+        let (sender, responses) = mpsc::channel();
+        thread::spawn(move || read_responses(stdout, sender));
+        Ok(Self { interpreter, child, requests, responses })
+    }
+
+    fn eval(&mut self, code: &str, cwd: &str) -> Result<Response, String> {
+        let frame = format!("{} {}\n{}{}", cwd.len(), code.len(), cwd, code);
+        self.requests.write_all(frame.as_bytes())
+            .and_then(|()| self.requests.flush())
+            .map_err(|_| "Python сессия завершилась, переменные сброшены".to_string())?;
+
+        // Обработчик SIGINT терминала перешлет Ctrl+C группе интерпретатора
+        let pgid = self.child.id() as i32;
+        signals::set_foreground(Some(pgid));
+        let mut interrupted = false;
+        let result = loop {
+            match self.responses.recv_timeout(POLL_INTERVAL) {
+                Ok(response) => break Ok(response),
+                Err(RecvTimeoutError::Timeout) => {
+                    if signals::take_interrupt().is_none() {
+                        continue;
+                    }
+                    // Фрагмент не реагирует на KeyboardInterrupt (например, в C коде)
+                    if interrupted {
+                        unsafe {
+                            libc::kill(-pgid, libc::SIGKILL);
+                        }
+                    }
+                    interrupted = true;
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    break Err(if interrupted {
+                        "Python сессия прервана, переменные сброшены".to_string()
+                    } else {
+                        "Python сессия завершилась, переменные сброшены".to_string()
+                    });
+                }
+            }
+        };
+        signals::set_foreground(None);
+        result
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+pub struct PythonRunner {
+    /// Запускается при первом !python
+    session: Option<Session>,
+}
+
+impl PythonRunner {
+    pub fn new() -> Self {
+        Self { session: None }
+    }
+
+    /// Интерпретатор, который будет использован в каталоге cwd
+    pub fn locate(env: &HashMap<String, String>, cwd: &str) -> Result<PathBuf, String> {
+        find_interpreter(Path::new(cwd), env).map(|interpreter| interpreter.path)
+    }
+
+    /// Выполняет фрагмент в каталоге cwd. `:reset` - начать новую сессию
+    pub fn run(&mut self, code: &str, env: &HashMap<String, String>, cwd: &str) -> Result<CommandResult, String> {
+        if code.trim() == ":reset" {
+            self.session = None;
+            return Ok(CommandResult::success("Сессия Python сброшена".to_string()));
+        }
+
+        let interpreter = find_interpreter(Path::new(cwd), env)?;
+        if self.session.as_ref().is_some_and(|session| session.interpreter != interpreter) {
+            self.session = None;
+        }
+        let session = match &mut self.session {
+            Some(session) => session,
+            None => self.session.insert(Session::start(interpreter, env)?),
+        };
+
+        let response = match session.eval(code, cwd) {
+            Ok(response) => response,
+            Err(e) => {
+                self.session = None;
+                return Err(e);
+            }
+        };
+        if let Status::Exit(_) = response.status {
+            self.session = None;
+        }
+        Ok(Self::to_result(response))
+    }
+
+    fn to_result(response: Response) -> CommandResult {
+        let output = response.stdout.strip_suffix('\n').unwrap_or(&response.stdout).to_string();
+        let status = match response.status {
+            Status::Ok => return CommandResult { stderr: response.stderr, ..CommandResult::success(output) },
+            Status::Error => ExitStatus::Code(1),
+            Status::Interrupted => ExitStatus::Signal(libc::SIGINT),
+            Status::Exit(code) => ExitStatus::Code(code),
+        };
+        let error = response.stderr.trim_end().to_string();
+        CommandResult {
+            success: status.success(),
+            output,
+            stderr: response.stderr,
+            error: (!error.is_empty()).then_some(error),
+            status,
+            ..CommandResult::success(String::new())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_header() {
+        assert_eq!(parse_header("ok 3 0\n"), Some((Status::Ok, 3, 0)));
+        assert_eq!(parse_header("interrupted 0 12\n"), Some((Status::Interrupted, 0, 12)));
+        assert_eq!(parse_header("exit:2 0 0\n"), Some((Status::Exit(2), 0, 0)));
+        assert_eq!(parse_header("ok 3\n"), None);
+        assert_eq!(parse_header("done 1 1\n"), None);
+    }
+
+    #[test]
+    fn test_find_interpreter() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("project");
+        std::fs::create_dir_all(project.join(".venv/bin")).unwrap();
+        std::fs::write(project.join(".venv/pyvenv.cfg"), "").unwrap();
+        std::fs::write(project.join(".venv/bin/python"), "").unwrap();
+        let mut env = HashMap::new();
+        env.insert("PATH".to_string(), String::new());
+
+        let found = find_interpreter(&project.join("src/app"), &env).unwrap();
+        assert_eq!(found.path, project.join(".venv/bin/python"));
+        assert_eq!(found.venv, Some(project.join(".venv")));
+        assert_eq!(find_interpreter(dir.path(), &env).unwrap_err(), INSTALL_HINT);
+
+        env.insert("VIRTUAL_ENV".to_string(), project.join(".venv").display().to_string());
+        assert_eq!(find_interpreter(dir.path(), &env).unwrap(), found);
+    }
+
+    #[test]
+    fn test_session() {
+        let env: HashMap<String, String> = std::env::vars().collect();
+        let Ok(interpreter) = find_interpreter(Path::new("/"), &env) else {
+            return;
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let cwd = dir.path().display().to_string();
+        let mut runner = PythonRunner::new();
+
+        assert!(runner.run("import math\nx = 5", &env, &cwd).unwrap().success);
+        assert_eq!(runner.run("math.factorial(x)", &env, &cwd).unwrap().output, "120");
+        assert_eq!(runner.run("print('a'); None", &env, &cwd).unwrap().output, "a");
+        assert_eq!(runner.run("open('f.txt', 'w').write('hi')", &env, &cwd).unwrap().output, "2");
+        assert!(dir.path().join("f.txt").is_file());
+
+        let failed = runner.run("def f():\n    return 1 / 0\nf()", &env, &cwd).unwrap();
+        let error = failed.error.unwrap();
+        assert!(error.contains("File \"<!python>\", line 2") && error.ends_with("ZeroDivisionError: division by zero"), "{}", error);
+        assert!(!error.contains("<string>"), "{}", error);
+
+        // Прерванный фрагмент не теряет переменные сессии
+        let pid = runner.session.as_ref().unwrap().child.id() as i32;
+        let interrupter = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(300));
+            unsafe {
+                libc::kill(pid, libc::SIGINT);
+            }
+        });
+        let interrupted = runner.run("import time\ntime.sleep(30)", &env, &cwd).unwrap();
+        interrupter.join().unwrap();
+        assert_eq!(interrupted.status, ExitStatus::Signal(libc::SIGINT));
+        assert_eq!(runner.run("x", &env, &cwd).unwrap().output, "5");
+
+        assert_eq!(runner.run("exit(3)", &env, &cwd).unwrap().status, ExitStatus::Code(3));
+        assert!(runner.session.is_none());
+        assert!(!runner.run("x", &env, &cwd).unwrap().success);
+        assert_eq!(runner.session.as_ref().unwrap().interpreter, interpreter);
+    }
+}
@@ -0,0 +1,205 @@
+//! Реестр исполнителей кода: префикс !<имя> -> встроенный исполнитель
+//! (rust, python, java) или внешний интерпретатор из [runners.<имя>].
+//! Исполнитель из конфигурации с именем встроенного заменяет его
+
+use std::path::PathBuf;
+use crate::runners::{ExternalRunner, JavaRunner, PythonRunner, RunnerConfig, RustRunner};
+use crate::terminal::{CommandResult, TerminalState};
+
+/// Встроенные исполнители и их описание
+pub const BUILTIN_RUNNERS: &[(&str, &str)] = &[
+    ("rust", "выполнить Rust код (fn, struct сохраняются; let выполняются заново перед каждым фрагментом; :defs - определения)"),
+    ("python", "выполнить Python код (импорты и переменные сохраняются)"),
+    ("java", "выполнить Java код в jshell (переменные и методы сохраняются)"),
+];
+
+enum Backend {
+    Rust(RustRunner),
+    Python(PythonRunner),
+    Java(JavaRunner),
+    External(Box<ExternalRunner>),
+}
+
+struct Runner {
+    name: String,
+    description: String,
+    backend: Backend,
+}
+
+impl Runner {
+    fn builtin(name: &str, description: &str) -> Self {
+        let backend = match name {
+            "rust" => Backend::Rust(RustRunner::new()),
+            "python" => Backend::Python(PythonRunner::new()),
+            _ => Backend::Java(JavaRunner::new()),
+        };
+        Self { name: name.to_string(), description: description.to_string(), backend }
+    }
+}
+
+pub struct RunnerRegistry {
+    runners: Vec<Runner>,
+}
+
+impl RunnerRegistry {
+    pub fn new() -> Self {
+        Self {
+            runners: BUILTIN_RUNNERS.iter().map(|(name, description)| Runner::builtin(name, description)).collect(),
+        }
+    }
+
+    /// Заменяет исполнители из конфигурации. Сессии встроенных исполнителей,
+    /// которые конфигурация не переопределяет, и исполнителей с неизменным
+    /// определением сохраняются
+    pub fn configure(&mut self, configs: Vec<RunnerConfig>) {
+        let mut previous = std::mem::take(&mut self.runners);
+        for (name, description) in BUILTIN_RUNNERS {
+            if configs.iter().any(|config| config.name == *name) {
+                continue;
+            }
+            let kept = previous.iter()
+                .position(|runner| runner.name == *name && !matches!(runner.backend, Backend::External(_)));
+            self.runners.push(match kept {
+                Some(index) => previous.swap_remove(index),
+                None => Runner::builtin(name, description),
+            });
+        }
+        for config in configs {
+            let kept = previous.iter()
+                .position(|runner| matches!(&runner.backend, Backend::External(external) if *external.config() == config));
+            self.runners.push(match kept {
+                Some(index) => previous.swap_remove(index),
+                None => Runner {
+                    name: config.name.clone(),
+                    description: config.description.clone(),
+                    backend: Backend::External(Box::new(ExternalRunner::new(config))),
+                },
+            });
+        }
+    }
+
+    /// Интерпретатор внешнего исполнителя; None - встроенный или неизвестный
+    pub fn interpreter(&self, name: &str) -> Option<&str> {
+        self.runners.iter()
+            .find(|runner| runner.name == name)
+            .and_then(|runner| match &runner.backend {
+                Backend::External(external) => Some(external.interpreter()),
+                _ => None,
+            })
+    }
+
+    /// Имена (без '!') и описания в порядке регистрации
+    pub fn names(&self) -> Vec<(String, String)> {
+        self.runners.iter().map(|runner| (runner.name.clone(), runner.description.clone())).collect()
+    }
+
+    pub fn run(&mut self, name: &str, code: &str, state: &TerminalState) -> Result<CommandResult, String> {
+        let runner = self.runners.iter_mut()
+            .find(|runner| runner.name == name)
+            .ok_or_else(|| format!("Неизвестный исполнитель !{}", name))?;
+        let env = &state.environment;
+        match &mut runner.backend {
+            Backend::Rust(rust) => rust.run(code, env),
+            Backend::Python(python) => python.run(code, env, &state.current_directory),
+            Backend::Java(java) => java.run(code, env),
+            Backend::External(external) => external.run(code, env, &state.current_directory),
+        }
+    }
+
+    /// Интерпретатор каждого исполнителя или причина, по которой он недоступен
+    pub fn locate(&self, state: &TerminalState) -> Vec<(&str, &str, Result<PathBuf, String>)> {
+        let env = &state.environment;
+        self.runners.iter().map(|runner| {
+            let location = match &runner.backend {
+                Backend::Rust(_) => RustRunner::locate(env),
+                Backend::Python(_) => PythonRunner::locate(env, &state.current_directory),
+                Backend::Java(_) => JavaRunner::locate(env),
+                Backend::External(external) => external.locate(env),
+            };
+            (runner.name.as_str(), runner.description.as_str(), location)
+        }).collect()
+    }
+
+    /// Раздел справки: доступные на этой машине исполнители и недоступные
+    pub fn help(&self, state: &TerminalState) -> String {
+        let mut lines = vec!["  Исполнители кода (!<язык> без кода - многострочный ввод, !<язык> :reset - новая сессия):".to_string()];
+        let mut missing = Vec::new();
+        for (name, description, location) in self.locate(state) {
+            match location {
+                Ok(path) => lines.push(format!("    {:<20}- {} ({})", format!("!{} <code>", name), description, path.display())),
+                Err(reason) => missing.push(format!("!{} ({})", name, reason)),
+            }
+        }
+        if !missing.is_empty() {
+            lines.push(format!("    Недоступны: {}", missing.join(", ")));
+        }
+        lines.push("    Свои исполнители (!node, !ruby, !go, ...) - секция [runners.<имя>] конфигурации".to_string());
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runners::RunnerMode;
+
+    fn external(name: &str, program: &str) -> RunnerConfig {
+        RunnerConfig {
+            name: name.to_string(),
+            description: format!("выполнить {}", name),
+            command: vec![program.to_string()],
+            extension: name.to_string(),
+            mode: RunnerMode::OneShot,
+            timeout: None,
+            marker: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_configure_overrides_builtins() {
+        let mut registry = RunnerRegistry::new();
+        let names = |registry: &RunnerRegistry| registry.names().into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+        assert_eq!(names(&registry), vec!["rust", "python", "java"]);
+
+        registry.configure(vec![external("python", "pypy3"), external("sh", "sh")]);
+        assert_eq!(names(&registry), vec!["rust", "java", "python", "sh"]);
+        assert_eq!(registry.names()[2].1, "выполнить python");
+
+        registry.configure(Vec::new());
+        assert_eq!(names(&registry), vec!["rust", "python", "java"]);
+    }
+
+    #[test]
+    fn test_reload_keeps_unchanged_sessions() {
+        let state = TerminalState::default();
+        let repl = |name: &str| RunnerConfig {
+            mode: RunnerMode::Repl,
+            marker: "echo '{marker}'".to_string(),
+            ..external(name, "sh")
+        };
+        let mut registry = RunnerRegistry::new();
+        registry.configure(vec![repl("sh-keep"), repl("sh-change")]);
+        for name in ["sh-keep", "sh-change"] {
+            assert!(registry.run(name, "x=5", &state).unwrap().success);
+        }
+
+        let changed = RunnerConfig { timeout: Some(std::time::Duration::from_secs(5)), ..repl("sh-change") };
+        registry.configure(vec![repl("sh-keep"), changed]);
+        assert_eq!(registry.run("sh-keep", "echo \"[$x]\"", &state).unwrap().output, "[5]");
+        assert_eq!(registry.run("sh-change", "echo \"[$x]\"", &state).unwrap().output, "[]");
+    }
+
+    #[test]
+    fn test_run_and_help() {
+        let state = TerminalState::default();
+        let mut registry = RunnerRegistry::new();
+        registry.configure(vec![external("shell", "sh"), external("missing", "no-such-interpreter")]);
+
+        assert_eq!(registry.run("shell", "echo ok", &state).unwrap().output, "ok\n");
+        assert!(registry.run("ruby", "puts 1", &state).unwrap_err().contains("!ruby"));
+
+        let help = registry.help(&state);
+        assert!(help.contains("!shell <code>"), "{}", help);
+        assert!(help.contains("Недоступны:") && help.contains("!missing (no-such-interpreter не найден)"), "{}", help);
+    }
+}
@@ -0,0 +1,155 @@
+//! Долгоживущий интерпретатор без собственного протокола (jshell, sh,
+//! исполнители из конфигурации в режиме repl): фрагмент и инструкция,
+//! печатающая метку, пишутся в stdin, а общий вывод stdout и stderr
+//! читается до строки с меткой
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::terminal::signals;
+
+/// Строка, которой отмечается конец вывода фрагмента
+pub const MARKER: &str = "--smart-term-end--";
+
+/// Подстановка метки в шаблоне инструкции: echo '{marker}'
+pub const MARKER_PLACEHOLDER: &str = "{marker}";
+
+/// Как часто проверяется время ожидания
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+pub struct Repl {
+    name: String,
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    /// Инструкция языка, печатающая MARKER
+    marker_statement: String,
+}
+
+impl Repl {
+    /// Запускает интерпретатор и ждет, пока он напечатает первую метку:
+    /// баннер и сообщения при запуске отбрасываются
+    pub fn start(name: &str, mut command: Command, marker_statement: &str) -> Result<Self, String> {
+        let (reader, writer) = std::io::pipe().map_err(|e| format!("{}: {}", name, e))?;
+        let error_writer = writer.try_clone().map_err(|e| format!("{}: {}", name, e))?;
+        command.stdin(Stdio::piped())
+            .stdout(writer)
+            .stderr(error_writer);
+        // Своя группа процессов: Ctrl+C пересылается только во время фрагмента
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
+        let mut child = command.spawn()
+            .map_err(|e| format!("Не удалось запустить {}: {}", name, e))?;
+        // Концы канала для записи остались только у интерпретатора: EOF - его завершение
+        drop(command);
+        let stdin = child.stdin.take().ok_or_else(|| format!("{}: нет stdin", name))?;
+
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(reader).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut repl = Self {
+            name: name.to_string(),
+            child,
+            stdin,
+            lines,
+            marker_statement: marker_statement.replace(MARKER_PLACEHOLDER, MARKER),
+        };
+        repl.eval("", None)?;
+        Ok(repl)
+    }
+
+    /// Выполняет фрагмент и возвращает его вывод. Ctrl+C пересылается
+    /// интерпретатору; после ошибки или истечения timeout процесс завершен
+    /// и сессию нужно начать заново
+    pub fn eval(&mut self, code: &str, timeout: Option<Duration>) -> Result<String, String> {
+        let request = if code.is_empty() {
+            format!("{}\n", self.marker_statement)
+        } else {
+            format!("{}\n{}\n", code, self.marker_statement)
+        };
+        self.stdin.write_all(request.as_bytes())
+            .and_then(|()| self.stdin.flush())
+            .map_err(|_| format!("{} неожиданно завершился", self.name))?;
+
+        let pgid = self.child.id() as i32;
+        signals::set_foreground(Some(pgid));
+        let result = self.read_output(timeout.map(|timeout| Instant::now() + timeout));
+        signals::set_foreground(None);
+        if result.is_err() {
+            self.kill();
+        }
+        result
+    }
+
+    /// Вывод до метки конца фрагмента
+    fn read_output(&mut self, deadline: Option<Instant>) -> Result<String, String> {
+        let mut output = Vec::new();
+        loop {
+            let line = match self.lines.recv_timeout(POLL_INTERVAL) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => {
+                    if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                        return Err(format!("{}: превышено время выполнения, сессия сброшена", self.name));
+                    }
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(format!("{} неожиданно завершился", self.name));
+                }
+            };
+            // Текст без перевода строки оказывается перед меткой
+            if let Some(text) = line.strip_suffix(MARKER) {
+                if !text.is_empty() {
+                    output.push(text.to_string());
+                }
+                return Ok(output.join("\n"));
+            }
+            output.push(line);
+        }
+    }
+
+    fn kill(&mut self) {
+        // Группа уже завершенного процесса могла достаться другим процессам
+        if let Ok(Some(_)) = self.child.try_wait() {
+            return;
+        }
+        unsafe {
+            libc::kill(-(self.child.id() as i32), libc::SIGKILL);
+        }
+        let _ = self.child.wait();
+    }
+}
+
+impl Drop for Repl {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_session() {
+        let mut repl = Repl::start("sh", Command::new("sh"), "echo '{marker}'").unwrap();
+        assert_eq!(repl.eval("x=5", None).unwrap(), "");
+        assert_eq!(repl.eval("echo $((x * 2)); echo err >&2", None).unwrap(), "10\nerr");
+        assert_eq!(repl.eval("printf abc", None).unwrap(), "abc");
+
+        let error = repl.eval("sleep 5", Some(Duration::from_millis(200))).unwrap_err();
+        assert!(error.contains("превышено время"), "{}", error);
+        assert!(repl.eval("echo again", None).is_err());
+    }
+}
@@ -0,0 +1,347 @@
+//! !rust: фрагмент оборачивается в main, компилируется локальным rustc с
+//! инкрементальной компиляцией в каталоге кэша и запускается. Определения
+//! (fn, struct, use, ...) и let-привязки остаются доступны до конца сессии,
+//! ошибки компиляции показываются с номерами строк фрагмента. Каждый фрагмент -
+//! отдельная программа, поэтому let-привязки выполняются в ней заново вместе
+//! с побочными эффектами (вывод, чтение ввода, запись в файлы)
+
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use crate::runners::{self, split_statements};
+use crate::terminal::CommandResult;
+
+/// Слова, с которых начинается определение верхнего уровня
+const ITEM_KEYWORDS: &[&str] = &[
+    "fn", "struct", "enum", "union", "trait", "impl", "type", "const", "static",
+    "mod", "use", "extern", "macro_rules!",
+];
+
+/// Модификаторы перед определением (pub, pub(crate) и т.п. проверяются отдельно)
+const ITEM_MODIFIERS: &[&str] = &["async", "unsafe", "default"];
+
+/// Заголовок let-привязок в выводе :defs
+const BINDINGS_NOTE: &str = "// let-привязки выполняются заново перед каждым фрагментом, вместе с их побочными эффектами";
+
+/// Значение последнего выражения печатается, если это не ()
+const SHOW_FUNCTION: &str = "\
+fn __show<T: std::fmt::Debug>(value: T) {
+    if std::any::type_name::<T>() != \"()\" {
+        println!(\"{:?}\", value);
+    }
+}
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SnippetKind {
+    /// fn, struct, use, ... - добавляются к определениям сессии
+    Items,
+    /// Только let - выполняются заново перед каждым следующим фрагментом
+    Bindings,
+    /// Инструкции; значение последнего выражения печатается через {:?}
+    Expression,
+}
+
+/// Определение сессии; key - чтобы повторное определение заменяло прежнее
+#[derive(Debug, Clone)]
+struct Item {
+    key: Option<String>,
+    code: String,
+}
+
+pub struct RustRunner {
+    items: Vec<Item>,
+    bindings: Vec<String>,
+    /// Исходник и программа этой сессии
+    dir: PathBuf,
+    /// Кэш инкрементальной компиляции, общий для сессий
+    incremental: PathBuf,
+}
+
+impl RustRunner {
+    pub fn new() -> Self {
+        Self::with_cache(&runners::cache_dir("rust"))
+    }
+
+    pub fn with_cache(cache: &Path) -> Self {
+        Self {
+            items: Vec::new(),
+            bindings: Vec::new(),
+            dir: cache.join(format!("session-{}", std::process::id())),
+            incremental: cache.join("incremental"),
+        }
+    }
+
+    /// Компилятор ($RUSTC или rustc) в PATH сессии
+    pub fn locate(env: &HashMap<String, String>) -> Result<PathBuf, String> {
+        let compiler = env.get("RUSTC").map_or("rustc", String::as_str);
+        let found = if compiler.contains('/') {
+            Some(PathBuf::from(compiler)).filter(|path| path.is_file())
+        } else {
+            runners::find_in_path(compiler, env.get("PATH").map_or("", String::as_str))
+        };
+        found.ok_or_else(|| format!("{} не найден: установите Rust (https://rustup.rs)", compiler))
+    }
+
+    /// Выполняет фрагмент. `:defs` - показать определения сессии, `:reset` - забыть их
+    pub fn run(&mut self, code: &str, env: &HashMap<String, String>) -> Result<CommandResult, String> {
+        match code.trim() {
+            ":reset" => {
+                self.items.clear();
+                self.bindings.clear();
+                return Ok(CommandResult::success("Определения Rust сброшены".to_string()));
+            }
+            ":defs" => {
+                let mut definitions: Vec<&str> = self.items.iter().map(|item| item.code.as_str()).collect();
+                if !self.bindings.is_empty() {
+                    definitions.push(BINDINGS_NOTE);
+                    definitions.extend(self.bindings.iter().map(String::as_str));
+                }
+                return Ok(CommandResult::success(definitions.join("\n")));
+            }
+            _ => {}
+        }
+
+        let kind = classify(code);
+        let key = (kind == SnippetKind::Items).then(|| item_key(code)).flatten();
+        let (source, first_line) = self.generate(code, kind, key.as_deref());
+
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("{}: {}", self.dir.display(), e))?;
+        let source_path = self.dir.join("main.rs");
+        let binary = self.dir.join("snippet");
+        std::fs::write(&source_path, source)
+            .map_err(|e| format!("{}: {}", source_path.display(), e))?;
+
+        let compiler = env.get("RUSTC").map_or("rustc", String::as_str);
+        let compiled = Command::new(compiler)
+            .args(["--edition", "2021", "--error-format=short", "-A", "warnings", "-C"])
+            .arg(format!("incremental={}", self.incremental.display()))
+            .arg("-o")
+            .arg(&binary)
+            .arg(&source_path)
+            .env_clear()
+            .envs(env)
+            .output()
+            .map_err(|e| match e.kind() {
+                ErrorKind::NotFound => format!("{} не найден: установите Rust (https://rustup.rs)", compiler),
+                _ => format!("Не удалось запустить {}: {}", compiler, e),
+            })?;
+        if !compiled.status.success() {
+            let errors = map_errors(&String::from_utf8_lossy(&compiled.stderr), &source_path, first_line, code.lines().count());
+            return Ok(CommandResult::error(errors));
+        }
+
+        if kind == SnippetKind::Items {
+            self.items.retain(|item| key.is_none() || item.key != key);
+            self.items.push(Item { key, code: code.trim().to_string() });
+            return Ok(CommandResult::success(String::new()));
+        }
+
+        let output = Command::new(&binary)
+            .env_clear()
+            .envs(env)
+            .stdin(Stdio::inherit())
+            .output()
+            .map_err(|e| format!("Ошибка выполнения Rust: {}", e))?;
+        let result = CommandResult::from_output(output);
+        if result.success && kind == SnippetKind::Bindings {
+            self.bindings.push(code.trim().to_string());
+        }
+        Ok(result)
+    }
+
+    /// Исходный текст программы и номер строки, с которой начинается фрагмент.
+    /// Определение с ключом replaced не попадает в текст: его заменяет фрагмент
+    fn generate(&self, code: &str, kind: SnippetKind, replaced: Option<&str>) -> (String, usize) {
+        let mut source = String::from("#![allow(unused)]\n");
+        for item in &self.items {
+            if replaced.is_none() || item.key.as_deref() != replaced {
+                source.push_str(&item.code);
+                source.push('\n');
+            }
+        }
+        source.push_str(SHOW_FUNCTION);
+
+        if kind == SnippetKind::Items {
+            let first_line = source.lines().count() + 1;
+            source.push_str(code);
+            source.push_str("\nfn main() {}\n");
+            return (source, first_line);
+        }
+
+        source.push_str("fn main() {\n");
+        for binding in &self.bindings {
+            source.push_str(binding);
+            source.push('\n');
+        }
+        if kind == SnippetKind::Expression {
+            source.push_str("__show({\n");
+        }
+        let first_line = source.lines().count() + 1;
+        source.push_str(code);
+        source.push_str(if kind == SnippetKind::Expression { "\n});\n}\n" } else { "\n}\n" });
+        (source, first_line)
+    }
+}
+
+impl Drop for RustRunner {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Текст без атрибутов в начале: #[derive(Debug, Clone)] struct P -> struct P
+fn strip_attributes(code: &str) -> &str {
+    let mut code = code.trim_start();
+    while let Some(rest) = code.strip_prefix("#[") {
+        code = rest.split_once(']').map_or("", |(_, rest)| rest).trim_start();
+    }
+    code
+}
+
+fn classify(code: &str) -> SnippetKind {
+    let trimmed = code.trim_start();
+    let first_word = strip_attributes(trimmed).split_whitespace()
+        .find(|word| !word.starts_with("pub") && !ITEM_MODIFIERS.contains(word))
+        .unwrap_or("");
+    let keyword: String = first_word.chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '!')
+        .collect();
+    // #[derive(...)] и другие атрибуты бывают только у определений
+    if trimmed.starts_with("#[") || ITEM_KEYWORDS.contains(&keyword.as_str()) {
+        return SnippetKind::Items;
+    }
+
+    let statements = split_statements(code);
+    if !statements.is_empty() && statements.iter().all(|s| s.starts_with("let ") && s.ends_with(';')) {
+        SnippetKind::Bindings
+    } else {
+        SnippetKind::Expression
+    }
+}
+
+/// "fn square", "struct Point"; use - весь текст; impl и extern - без ключа
+fn item_key(code: &str) -> Option<String> {
+    let code = code.trim();
+    let mut words = strip_attributes(code).split_whitespace()
+        .skip_while(|word| word.starts_with("pub") || ITEM_MODIFIERS.contains(word));
+    let keyword = words.next()?;
+    if keyword == "use" {
+        return Some(code.to_string());
+    }
+    if let Some(name) = keyword.strip_prefix("macro_rules!") {
+        let name = if name.is_empty() { words.next()? } else { name };
+        return Some(format!("macro_rules! {}", name.trim_end_matches('{')));
+    }
+    if ["impl", "extern"].iter().any(|prefix| keyword.starts_with(prefix)) {
+        return None;
+    }
+    let name: String = words.next()?
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .collect();
+    (!name.is_empty()).then(|| format!("{} {}", keyword, name))
+}
+
+/// Сообщения rustc в формате short: "main.rs:12:5: error..." - номера строк
+/// фрагмента становятся "!rust:2:5: error...", итоговые строки rustc убираются
+fn map_errors(stderr: &str, source_path: &Path, first_line: usize, line_count: usize) -> String {
+    let prefix = format!("{}:", source_path.display());
+    stderr.lines()
+        .filter(|line| !line.starts_with("error: aborting due to") && !line.starts_with("For more information"))
+        .map(|line| {
+            let Some(rest) = line.strip_prefix(&prefix) else {
+                return line.to_string();
+            };
+            let mut parts = rest.splitn(3, ':');
+            let (Some(line_number), Some(column), Some(message)) = (parts.next(), parts.next(), parts.next()) else {
+                return line.to_string();
+            };
+            match line_number.parse::<usize>() {
+                Ok(number) if number >= first_line && number < first_line + line_count.max(1) => {
+                    format!("!rust:{}:{}:{}", number - first_line + 1, column, message)
+                }
+                _ => format!("(определения сессии):{}", message),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify("fn square(x: i32) -> i32 { x * x }"), SnippetKind::Items);
+        assert_eq!(classify("pub(crate) struct Point { x: i32 }"), SnippetKind::Items);
+        assert_eq!(classify("#[derive(Debug)] struct P;"), SnippetKind::Items);
+        assert_eq!(classify("use std::collections::HashMap;"), SnippetKind::Items);
+        assert_eq!(classify("impl<T> Tr for T {}"), SnippetKind::Items);
+        assert_eq!(classify("let x = 5; let y = x * 2;"), SnippetKind::Bindings);
+        assert_eq!(classify("let x = 5; x + 1"), SnippetKind::Expression);
+        assert_eq!(classify("unsafe { 1 }"), SnippetKind::Expression);
+        assert_eq!(classify("println!(\"hi\")"), SnippetKind::Expression);
+    }
+
+    #[test]
+    fn test_item_key() {
+        assert_eq!(item_key("fn square(x: i32) -> i32 { x * x }").as_deref(), Some("fn square"));
+        assert_eq!(item_key("#[derive(Debug, Clone)] pub struct Point<T> { x: T }").as_deref(), Some("struct Point"));
+        assert_eq!(item_key("macro_rules! twice { ($e:expr) => { $e * 2 } }").as_deref(), Some("macro_rules! twice"));
+        assert_eq!(item_key("use std::fmt;").as_deref(), Some("use std::fmt;"));
+        assert_eq!(item_key("impl Point { }"), None);
+    }
+
+    #[test]
+    fn test_generate_and_map_errors() {
+        let mut runner = RustRunner::with_cache(Path::new("/nonexistent"));
+        runner.items.push(Item { key: Some("fn one".to_string()), code: "fn one() -> i32 { 1 }".to_string() });
+        runner.bindings.push("let x = 2;".to_string());
+
+        let (source, first_line) = runner.generate("let y = x;\nz + y", SnippetKind::Expression, None);
+        let lines: Vec<&str> = source.lines().collect();
+        assert_eq!(lines[first_line - 1], "let y = x;");
+        assert!(source.contains("fn one() -> i32 { 1 }"));
+
+        let (source, _) = runner.generate("fn one() -> i32 { 2 }", SnippetKind::Items, Some("fn one"));
+        assert_eq!(source.matches("fn one()").count(), 1);
+
+        let path = Path::new("/tmp/s/main.rs");
+        let stderr = format!(
+            "/tmp/s/main.rs:{}:1: error[E0425]: cannot find value `z` in this scope\nerror: aborting due to 1 previous error\n",
+            first_line + 1
+        );
+        assert_eq!(map_errors(&stderr, path, first_line, 2), "!rust:2:1: error[E0425]: cannot find value `z` in this scope");
+    }
+
+    #[test]
+    fn test_run_snippets() {
+        // Нужен локальный rustc
+        if Command::new("rustc").arg("--version").output().is_err() {
+            return;
+        }
+        let cache = tempfile::tempdir().unwrap();
+        let mut runner = RustRunner::with_cache(cache.path());
+        let env: HashMap<String, String> = std::env::vars().collect();
+
+        assert!(runner.run("let x = 20;", &env).unwrap().success);
+        assert!(runner.run("fn square(v: i32) -> i32 { v * v }", &env).unwrap().success);
+        assert_eq!(runner.run("square(x) + 1", &env).unwrap().output, "401\n");
+        assert_eq!(runner.run("println!(\"hi\");", &env).unwrap().output, "hi\n");
+        assert_eq!(
+            runner.run(":defs", &env).unwrap().output,
+            format!("fn square(v: i32) -> i32 {{ v * v }}\n{}\nlet x = 20;", BINDINGS_NOTE)
+        );
+
+        let failed = runner.run("1 +\nnope", &env).unwrap();
+        assert!(!failed.success);
+        assert!(failed.error.unwrap().starts_with("!rust:2:"));
+
+        runner.run(":reset", &env).unwrap();
+        assert!(!runner.run("x", &env).unwrap().success);
+    }
+}
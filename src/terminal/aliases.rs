@@ -0,0 +1,231 @@
+//! Aliases: подстановка первого слова каждой команды до разбора строки,
+//! встроенные команды alias и unalias
+
+use std::collections::HashMap;
+use crate::terminal::CommandParser;
+
+/// Встроенные команды для работы с aliases
+pub const COMMANDS: &[&str] = &["alias", "unalias"];
+
+/// Символы, завершающие слово и начинающие новую команду
+const OPERATOR_CHARS: &[char] = &['|', '&', ';', '<', '>', '(', ')', '\n'];
+
+/// Раскрывает aliases в начале каждой команды строки (после |, &&, || и ;).
+/// Подстановка рекурсивна: результат снова проверяется, но alias, который
+/// уже раскрывается, повторно не подставляется - `alias ls='ls -F'` и
+/// циклы `a -> b -> a` не зацикливаются. Значение, оканчивающееся пробелом,
+/// делает кандидатом и следующее слово (`alias sudo='sudo '`)
+pub fn expand(line: &str, aliases: &HashMap<String, String>, parser: &CommandParser) -> String {
+    // Код для !python и т.п. передается интерпретатору без изменений
+    if aliases.is_empty() || parser.has_bang_prefix(line) {
+        return line.to_string();
+    }
+    expand_with(line, aliases, &mut Vec::new())
+}
+
+fn expand_with(line: &str, aliases: &HashMap<String, String>, active: &mut Vec<String>) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut result = String::new();
+    let mut command_start = true;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if command_start {
+            if c.is_whitespace() && c != '\n' {
+                result.push(c);
+                i += 1;
+                continue;
+            }
+
+            let end = chars[i..].iter()
+                .position(|&c| c.is_whitespace() || OPERATOR_CHARS.contains(&c) || matches!(c, '\'' | '"' | '\\'))
+                .map_or(chars.len(), |offset| i + offset);
+            let word: String = chars[i..end].iter().collect();
+            // Слово с кавычками или \ не считается alias
+            let quoted = chars.get(end).is_some_and(|c| matches!(c, '\'' | '"' | '\\'));
+
+            if !word.is_empty() && !quoted && !active.contains(&word) {
+                if let Some(value) = aliases.get(&word) {
+                    active.push(word);
+                    let expanded = expand_with(value, aliases, active);
+                    active.pop();
+                    command_start = value.ends_with(char::is_whitespace);
+                    // Пробел в конце значения не удваивает пробел из строки
+                    if command_start && chars.get(end).is_some_and(|c| c.is_whitespace()) {
+                        result.push_str(expanded.trim_end());
+                    } else {
+                        result.push_str(&expanded);
+                    }
+                    i = end;
+                    continue;
+                }
+            }
+            command_start = false;
+            // Пустое слово - оператор или кавычка, они обрабатываются ниже
+            if !word.is_empty() {
+                result.push_str(&word);
+                i = end;
+                continue;
+            }
+        }
+
+        match c {
+            '\'' => {
+                let close = chars[i + 1..].iter().position(|&c| c == '\'').map_or(chars.len(), |p| i + 1 + p + 1);
+                result.extend(&chars[i..close.min(chars.len())]);
+                i = close;
+                continue;
+            }
+            '"' => {
+                let mut j = i + 1;
+                while j < chars.len() && chars[j] != '"' {
+                    if chars[j] == '\\' {
+                        j += 1;
+                    }
+                    j += 1;
+                }
+                let close = (j + 1).min(chars.len());
+                result.extend(&chars[i..close]);
+                i = close;
+                continue;
+            }
+            '\\' => {
+                let close = (i + 2).min(chars.len());
+                result.extend(&chars[i..close]);
+                i = close;
+                continue;
+            }
+            '|' | '&' | ';' | '\n' | '(' => {
+                // &>, >& и 2>&1 - перенаправления, а не разделители команд
+                let is_redirect = c == '&'
+                    && (chars.get(i + 1) == Some(&'>') || i.checked_sub(1).map(|p| chars[p]) == Some('>'));
+                command_start = !is_redirect;
+            }
+            _ => {}
+        }
+        result.push(c);
+        i += 1;
+    }
+    result
+}
+
+/// Допустимое имя alias: без пробелов, кавычек, =, / и операторов
+pub fn is_valid_alias_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.chars().any(|c| c.is_whitespace() || OPERATOR_CHARS.contains(&c)
+            || matches!(c, '=' | '/' | '\'' | '"' | '\\' | '$' | '`'))
+}
+
+fn format_alias(name: &str, value: &str) -> String {
+    format!("alias {}='{}'", name, value.replace('\'', "'\\''"))
+}
+
+/// alias [-p] [name[=value] ...]
+pub fn run_alias(args: &[String], aliases: &mut HashMap<String, String>) -> Result<String, String> {
+    let args: Vec<&String> = args.iter().filter(|arg| arg.as_str() != "-p").collect();
+
+    if args.is_empty() {
+        let mut names: Vec<&String> = aliases.keys().collect();
+        names.sort();
+        return Ok(names.iter()
+            .map(|name| format_alias(name, &aliases[*name]))
+            .collect::<Vec<_>>()
+            .join("\n"));
+    }
+
+    let mut output = Vec::new();
+    let mut errors = Vec::new();
+    for arg in args {
+        match arg.split_once('=') {
+            Some((name, value)) => {
+                if is_valid_alias_name(name) {
+                    aliases.insert(name.to_string(), value.to_string());
+                } else {
+                    errors.push(format!("'{}': неверное имя alias", name));
+                }
+            }
+            None => match aliases.get(arg.as_str()) {
+                Some(value) => output.push(format_alias(arg, value)),
+                None => errors.push(format!("{}: не найден", arg)),
+            },
+        }
+    }
+
+    if errors.is_empty() { Ok(output.join("\n")) } else { Err(errors.join("\n")) }
+}
+
+/// unalias [-a] name ...
+pub fn run_unalias(args: &[String], aliases: &mut HashMap<String, String>) -> Result<String, String> {
+    if args.is_empty() {
+        return Err("использование: unalias [-a] имя [имя ...]".to_string());
+    }
+    if args.iter().any(|arg| arg == "-a") {
+        aliases.clear();
+        return Ok(String::new());
+    }
+
+    let missing: Vec<String> = args.iter()
+        .filter(|name| aliases.remove(name.as_str()).is_none())
+        .map(|name| format!("{}: не найден", name))
+        .collect();
+    if missing.is_empty() { Ok(String::new()) } else { Err(missing.join("\n")) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn expand(line: &str, aliases: &HashMap<String, String>) -> String {
+        super::expand(line, aliases, &CommandParser::new())
+    }
+
+    #[test]
+    fn test_expand_command_positions() {
+        let aliases = table(&[("ll", "ls -al"), ("g", "grep -n")]);
+        assert_eq!(expand("ll /tmp", &aliases), "ls -al /tmp");
+        assert_eq!(expand("ll | g foo && ll; g x", &aliases), "ls -al | grep -n foo && ls -al; grep -n x");
+        assert_eq!(expand("echo ll", &aliases), "echo ll");
+        assert_eq!(expand("echo 'a; ll' \"| ll\"", &aliases), "echo 'a; ll' \"| ll\"");
+        assert_eq!(expand("\\ll; 'll'", &aliases), "\\ll; 'll'");
+        assert_eq!(expand("make 2>&1 | g err", &aliases), "make 2>&1 | grep -n err");
+    }
+
+    #[test]
+    fn test_recursive_and_loop_safe() {
+        let aliases = table(&[("ls", "ls -F"), ("l", "ls -a"), ("a", "b x"), ("b", "a y")]);
+        assert_eq!(expand("l", &aliases), "ls -F -a");
+        assert_eq!(expand("a", &aliases), "a y x");
+        assert_eq!(expand("b", &aliases), "b x y");
+    }
+
+    #[test]
+    fn test_trailing_space_expands_next_word() {
+        let aliases = table(&[("sudo", "sudo "), ("ll", "ls -al")]);
+        assert_eq!(expand("sudo ll", &aliases), "sudo ls -al");
+        assert_eq!(expand("!python print(1); ll", &aliases), "!python print(1); ll");
+    }
+
+    #[test]
+    fn test_alias_builtins() {
+        let mut aliases = HashMap::new();
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        run_alias(&args(&["k=kubectl", "q=it's"]), &mut aliases).unwrap();
+        assert_eq!(run_alias(&args(&["q"]), &mut aliases).unwrap(), "alias q='it'\\''s'");
+        assert_eq!(run_alias(&[], &mut aliases).unwrap(), "alias k='kubectl'\nalias q='it'\\''s'");
+        assert!(run_alias(&args(&["a/b=x"]), &mut aliases).is_err());
+        assert!(run_alias(&args(&["nope"]), &mut aliases).is_err());
+
+        run_unalias(&args(&["k"]), &mut aliases).unwrap();
+        assert!(!aliases.contains_key("k"));
+        assert!(run_unalias(&args(&["k"]), &mut aliases).is_err());
+        run_unalias(&args(&["-a"]), &mut aliases).unwrap();
+        assert!(aliases.is_empty());
+    }
+}
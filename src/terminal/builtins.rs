@@ -0,0 +1,487 @@
+//! Встроенные команды, меняющие состояние самого терминала
+
+use std::path::{Path, PathBuf};
+use crate::terminal::{CommandResult, ExitStatus, ShellOptions, TerminalState};
+use crate::terminal::parser::is_valid_name;
+use crate::utils::helpers;
+
+/// Имена встроенных команд, выполняемых внутри процесса терминала
+pub const BUILTIN_COMMANDS: &[&str] = &[
+    "cd", "pushd", "popd", "dirs",
+    "export", "unset", "env", "set",
+    "preview", "history", "config", "exit", "quit",
+];
+
+/// Является ли команда встроенной
+pub fn is_builtin(name: &str) -> bool {
+    BUILTIN_COMMANDS.contains(&name)
+}
+
+/// Нужно ли выполнять вызов как встроенную команду.
+/// `env` с аргументами запускает внешнюю утилиту env
+pub fn handles(argv: &[String]) -> bool {
+    match argv {
+        [name, ..] if name == "env" => argv.len() == 1,
+        [name, ..] => is_builtin(name),
+        [] => false,
+    }
+}
+
+/// Выполняет встроенную команду
+pub fn run(name: &str, args: &[String], state: &mut TerminalState) -> CommandResult {
+    if name == "exit" || name == "quit" {
+        return exit(args, state);
+    }
+    let result = match name {
+        "cd" => cd(args, state),
+        "pushd" => pushd(args, state),
+        "popd" => popd(args, state),
+        "dirs" => dirs(args, state),
+        "export" => export(args, state),
+        "unset" => unset(args, state),
+        "env" => Ok(format_environment(state)),
+        "set" => set(args, state),
+        "preview" => Ok(format_argv(args)),
+        "history" => state.history.run_builtin(&args.iter().map(String::as_str).collect::<Vec<_>>()),
+        _ => Err(format!("Неизвестная встроенная команда: {}", name)),
+    };
+
+    match result {
+        Ok(output) => CommandResult::success(output),
+        Err(error) => CommandResult::error(format!("{}: {}", name, error)),
+    }
+}
+
+/// Раскрывает ~ в начале пути
+fn expand_home(path: &str) -> Result<PathBuf, String> {
+    if path == "~" {
+        return helpers::get_home_dir().map(PathBuf::from);
+    }
+    if let Some(rest) = path.strip_prefix("~/") {
+        return helpers::get_home_dir().map(|home| Path::new(&home).join(rest));
+    }
+    Ok(PathBuf::from(path))
+}
+
+/// Заменяет домашнюю директорию на ~ для вывода
+fn abbreviate_home(path: &str) -> String {
+    match helpers::get_home_dir() {
+        Ok(home) if path == home => "~".to_string(),
+        Ok(home) if path.starts_with(&format!("{}/", home)) => format!("~{}", &path[home.len()..]),
+        _ => path.to_string(),
+    }
+}
+
+/// Ищет директорию с учетом CDPATH. Возвращает путь и признак того,
+/// что он найден через CDPATH (тогда bash печатает новый путь)
+fn resolve_directory(target: &str, state: &TerminalState) -> Result<(PathBuf, bool), String> {
+    let path = expand_home(target)?;
+
+    let is_explicit = path.is_absolute()
+        || target.starts_with("./")
+        || target.starts_with("../")
+        || target == "."
+        || target == "..";
+
+    if !is_explicit {
+        if let Some(cdpath) = state.get_variable("CDPATH") {
+            for base in cdpath.split(':').filter(|b| !b.is_empty()) {
+                let candidate = expand_home(base)?.join(&path);
+                if candidate.is_dir() {
+                    return Ok((candidate, true));
+                }
+            }
+        }
+    }
+
+    Ok((path, false))
+}
+
+/// Меняет рабочую директорию процесса и обновляет состояние терминала
+fn change_directory(path: &Path, state: &mut TerminalState) -> Result<(), String> {
+    std::env::set_current_dir(path)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+
+    let new_directory = std::env::current_dir()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| path.to_string_lossy().to_string());
+
+    state.set_variable("PWD", &new_directory);
+    let old_directory = std::mem::replace(&mut state.current_directory, new_directory);
+    state.set_variable("OLDPWD", &old_directory);
+    state.previous_directory = Some(old_directory);
+    Ok(())
+}
+
+fn cd(args: &[String], state: &mut TerminalState) -> Result<String, String> {
+    match args {
+        [] => {
+            let home = state.get_variable("HOME").ok_or("HOME не задан")?;
+            change_directory(Path::new(&home), state)?;
+            Ok(String::new())
+        }
+        [target] if target == "-" => {
+            let previous = state.previous_directory.clone()
+                .ok_or("OLDPWD не задан")?;
+            change_directory(Path::new(&previous), state)?;
+            Ok(state.current_directory.clone())
+        }
+        [target] => {
+            let (path, from_cdpath) = resolve_directory(target, state)?;
+            change_directory(&path, state)?;
+            Ok(if from_cdpath { state.current_directory.clone() } else { String::new() })
+        }
+        _ => Err("слишком много аргументов".to_string()),
+    }
+}
+
+/// Разбирает аргумент вида +N
+fn parse_stack_index(arg: &str) -> Option<usize> {
+    arg.strip_prefix('+').and_then(|n| n.parse().ok())
+}
+
+fn pushd(args: &[String], state: &mut TerminalState) -> Result<String, String> {
+    match args {
+        [] => {
+            // Меняем местами текущую директорию и вершину стека
+            let top = state.directory_stack.first().cloned()
+                .ok_or("стек директорий пуст")?;
+            let current = state.current_directory.clone();
+            change_directory(Path::new(&top), state)?;
+            state.directory_stack[0] = current;
+        }
+        [arg] if parse_stack_index(arg).is_some() => {
+            // Циклический сдвиг стека так, чтобы N-я запись стала текущей
+            let index = parse_stack_index(arg).unwrap_or(0);
+            let mut full = vec![state.current_directory.clone()];
+            full.extend(state.directory_stack.iter().cloned());
+            if index >= full.len() {
+                return Err(format!("{}: индекс вне стека директорий", arg));
+            }
+            full.rotate_left(index);
+            change_directory(Path::new(&full[0]), state)?;
+            state.directory_stack = full[1..].to_vec();
+        }
+        [target] => {
+            let (path, _) = resolve_directory(target, state)?;
+            let current = state.current_directory.clone();
+            change_directory(&path, state)?;
+            state.directory_stack.insert(0, current);
+        }
+        _ => return Err("слишком много аргументов".to_string()),
+    }
+    Ok(format_stack(state, false))
+}
+
+fn popd(args: &[String], state: &mut TerminalState) -> Result<String, String> {
+    if state.directory_stack.is_empty() {
+        return Err("стек директорий пуст".to_string());
+    }
+
+    match args {
+        [] => {
+            let top = state.directory_stack.remove(0);
+            if let Err(e) = change_directory(Path::new(&top), state) {
+                state.directory_stack.insert(0, top);
+                return Err(e);
+            }
+        }
+        [arg] => {
+            // popd +N удаляет N-ю запись (0 - текущая директория)
+            let index = parse_stack_index(arg)
+                .ok_or_else(|| format!("{}: неверный аргумент", arg))?;
+            if index == 0 {
+                return popd(&[], state);
+            }
+            if index > state.directory_stack.len() {
+                return Err(format!("{}: индекс вне стека директорий", arg));
+            }
+            state.directory_stack.remove(index - 1);
+        }
+        _ => return Err("слишком много аргументов".to_string()),
+    }
+    Ok(format_stack(state, false))
+}
+
+fn dirs(args: &[String], state: &mut TerminalState) -> Result<String, String> {
+    let mut verbose = false;
+    let mut per_line = false;
+
+    for arg in args {
+        match arg.as_str() {
+            "-c" => {
+                state.directory_stack.clear();
+                return Ok(String::new());
+            }
+            "-v" => verbose = true,
+            "-p" => per_line = true,
+            other => return Err(format!("{}: неверная опция", other)),
+        }
+    }
+
+    if verbose {
+        Ok(format_stack(state, true))
+    } else if per_line {
+        Ok(stack_entries(state).join("\n"))
+    } else {
+        Ok(format_stack(state, false))
+    }
+}
+
+fn stack_entries(state: &TerminalState) -> Vec<String> {
+    std::iter::once(&state.current_directory)
+        .chain(state.directory_stack.iter())
+        .map(|dir| abbreviate_home(dir))
+        .collect()
+}
+
+fn format_stack(state: &TerminalState, numbered: bool) -> String {
+    let entries = stack_entries(state);
+    if numbered {
+        entries.iter()
+            .enumerate()
+            .map(|(i, dir)| format!("{:2}  {}", i, dir))
+            .collect::<Vec<_>>()
+            .join("\n")
+    } else {
+        entries.join(" ")
+    }
+}
+
+/// Форматирует значение для вывода в виде, пригодном для повторного ввода
+fn quote_value(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+fn format_environment(state: &TerminalState) -> String {
+    let mut vars: Vec<_> = state.environment.iter().collect();
+    vars.sort();
+    vars.iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn export(args: &[String], state: &mut TerminalState) -> Result<String, String> {
+    if args.is_empty() || args == ["-p"] {
+        let mut vars: Vec<_> = state.environment.iter().collect();
+        vars.sort();
+        return Ok(vars.iter()
+            .map(|(name, value)| format!("declare -x {}={}", name, quote_value(value)))
+            .collect::<Vec<_>>()
+            .join("\n"));
+    }
+
+    let mut errors = Vec::new();
+    for arg in args {
+        let (name, value) = match arg.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (arg.as_str(), None),
+        };
+        if is_valid_name(name) {
+            state.export_variable(name, value);
+        } else {
+            errors.push(format!("'{}': неверный идентификатор", arg));
+        }
+    }
+
+    if errors.is_empty() { Ok(String::new()) } else { Err(errors.join("\n")) }
+}
+
+fn unset(args: &[String], state: &mut TerminalState) -> Result<String, String> {
+    for name in args.iter().filter(|a| a.as_str() != "-v") {
+        if !is_valid_name(name) {
+            return Err(format!("'{}': неверный идентификатор", name));
+        }
+        state.unset_variable(name);
+    }
+    Ok(String::new())
+}
+
+fn set(args: &[String], state: &mut TerminalState) -> Result<String, String> {
+    match args {
+        [] => {}
+        [flag] if flag == "-o" || flag == "+o" => {
+            return Ok(ShellOptions::NAMES.iter()
+                .map(|name| {
+                    let enabled = state.options.get(name).unwrap_or(false);
+                    if flag == "-o" {
+                        format!("{:<15} {}", name, if enabled { "on" } else { "off" })
+                    } else {
+                        format!("set {}o {}", if enabled { "-" } else { "+" }, name)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n"));
+        }
+        [flag, name] if flag == "-o" || flag == "+o" => {
+            state.options.set(name, flag == "-o")?;
+            return Ok(String::new());
+        }
+        // Короткие флаги можно объединять: set -ex, set +x
+        flags if flags.iter().all(|f| is_short_flags(f)) => {
+            for flag in flags {
+                let enable = flag.starts_with('-');
+                for c in flag[1..].chars() {
+                    let name = ShellOptions::from_flag(c)
+                        .ok_or_else(|| format!("{}{}: неизвестная опция", &flag[..1], c))?;
+                    state.options.set(name, enable)?;
+                }
+            }
+            return Ok(String::new());
+        }
+        [other, ..] => return Err(format!("{}: неизвестная опция", other)),
+    }
+
+    let mut vars: Vec<(&String, &String)> = state.environment.iter()
+        .chain(state.shell_variables.iter())
+        .collect();
+    vars.sort();
+    vars.dedup_by(|a, b| a.0 == b.0);
+    Ok(vars.iter()
+        .map(|(name, value)| format!("{}={}", name, quote_value(value)))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// -e, +x, -ex и т.п.
+fn is_short_flags(arg: &str) -> bool {
+    (arg.starts_with('-') || arg.starts_with('+'))
+        && arg.len() > 1
+        && arg[1..].chars().all(|c| c.is_ascii_alphabetic())
+}
+
+/// exit [N]: завершает терминал или скрипт с кодом N (по умолчанию - код
+/// последней команды)
+fn exit(args: &[String], state: &mut TerminalState) -> CommandResult {
+    let code = match args.first() {
+        None => state.last_exit_code,
+        Some(arg) => match arg.parse::<i32>() {
+            Ok(code) => code & 0xff,
+            Err(_) => {
+                let error = format!("exit: {}: требуется числовой аргумент", arg);
+                return CommandResult { status: ExitStatus::Code(2), ..CommandResult::error(error) };
+            }
+        },
+    };
+    state.is_running = false;
+    CommandResult { success: code == 0, status: ExitStatus::Code(code), ..CommandResult::success(String::new()) }
+}
+
+/// Командная строка из аргументов: аргументы с пробелами и спецсимволами в кавычках
+pub fn quote_argv(argv: &[String]) -> String {
+    argv.iter()
+        .map(|arg| {
+            let plain = !arg.is_empty()
+                && arg.chars().all(|c| c.is_alphanumeric() || "-_./=:,+@%".contains(c));
+            if plain { arg.clone() } else { quote_value(arg) }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Строка трассировки set -x
+pub fn format_trace(argv: &[String]) -> String {
+    format!("+ {}", quote_argv(argv))
+}
+
+/// Показывает аргументы после всех раскрытий, не выполняя команду
+pub fn format_argv(argv: &[String]) -> String {
+    argv.iter()
+        .enumerate()
+        .map(|(i, arg)| format!("[{}] {}", i, arg))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(name: &str, args: &[&str], state: &mut TerminalState) -> CommandResult {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        run(name, &args, state)
+    }
+
+    // cd меняет рабочую директорию всего процесса, поэтому все проверки в одном тесте
+    #[test]
+    fn test_directory_builtins() {
+        let original = std::env::current_dir().unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let base = temp_dir.path().canonicalize().unwrap();
+        std::fs::create_dir_all(base.join("a/inner")).unwrap();
+        std::fs::create_dir(base.join("b")).unwrap();
+        let path = |name: &str| base.join(name).to_string_lossy().to_string();
+        let (root, a, b) = (base.to_string_lossy().to_string(), path("a"), path("b"));
+        let mut state = TerminalState::default();
+
+        // cd - меняет местами текущую и предыдущую директорию
+        assert!(call("cd", &[&a], &mut state).success);
+        assert!(call("cd", &[&b], &mut state).success);
+        let result = call("cd", &["-"], &mut state);
+        assert_eq!(result.output, a);
+        assert_eq!(state.get_variable("OLDPWD"), Some(b.clone()));
+        assert_eq!(call("cd", &["-"], &mut state).output, b);
+        assert_eq!(state.get_variable("PWD"), Some(b.clone()));
+        assert_eq!(std::env::current_dir().unwrap().to_string_lossy(), b.as_str());
+
+        // Найденная через CDPATH директория печатается
+        state.set_variable("CDPATH", &format!("/nonexistent:{}", a));
+        assert_eq!(call("cd", &["inner"], &mut state).output, path("a/inner"));
+        assert!(!call("cd", &["missing"], &mut state).success);
+        assert!(!call("cd", &[&a, &b], &mut state).success);
+
+        // pushd, dirs и popd
+        assert!(call("cd", &[&root], &mut state).success);
+        assert_eq!(call("pushd", &[&a], &mut state).output, format!("{} {}", a, root));
+        assert_eq!(call("pushd", &[&b], &mut state).output, format!("{} {} {}", b, a, root));
+        assert_eq!(call("dirs", &["-v"], &mut state).output, format!(" 0  {}\n 1  {}\n 2  {}", b, a, root));
+        assert_eq!(call("dirs", &["-p"], &mut state).output, format!("{}\n{}\n{}", b, a, root));
+        assert_eq!(call("pushd", &[], &mut state).output, format!("{} {} {}", a, b, root));
+        assert_eq!(call("popd", &[], &mut state).output, format!("{} {}", b, root));
+        assert_eq!(state.current_directory, b);
+        assert_eq!(call("popd", &["+1"], &mut state).output, b);
+        let result = call("popd", &[], &mut state);
+        assert!(!result.success);
+        assert_eq!(result.error.as_deref(), Some("popd: стек директорий пуст"));
+        assert!(!call("dirs", &["-x"], &mut state).success);
+
+        std::env::set_current_dir(original).unwrap();
+    }
+
+    #[test]
+    fn test_export_and_unset() {
+        let mut state = TerminalState::default();
+        state.unset_variable("ST_TEST_A");
+
+        // Экспортированная переменная видна дочерним процессам
+        assert!(call("export", &["ST_TEST_A=1"], &mut state).success);
+        let child = std::process::Command::new("sh")
+            .args(["-c", "echo \"[$ST_TEST_A]\""])
+            .env_clear()
+            .envs(&state.environment)
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&child.stdout), "[1]\n");
+        assert!(call("env", &[], &mut state).output.lines().any(|line| line == "ST_TEST_A=1"));
+        assert!(call("export", &["-p"], &mut state).output.contains("declare -x ST_TEST_A='1'"));
+
+        // Переменная сессии попадает в окружение только после export
+        state.set_variable("ST_TEST_B", "x y");
+        assert!(!state.environment.contains_key("ST_TEST_B"));
+        assert!(call("set", &[], &mut state).output.contains("ST_TEST_B='x y'"));
+        call("export", &["ST_TEST_B"], &mut state);
+        assert_eq!(state.environment.get("ST_TEST_B").map(String::as_str), Some("x y"));
+
+        assert!(call("unset", &["ST_TEST_A", "ST_TEST_B"], &mut state).success);
+        assert_eq!(state.get_variable("ST_TEST_A"), None);
+        assert_eq!(state.get_variable("ST_TEST_B"), None);
+
+        // Неверные имена
+        let result = call("export", &["1A=2", "OK_NAME=3"], &mut state);
+        assert_eq!(result.error.as_deref(), Some("export: '1A=2': неверный идентификатор"));
+        assert_eq!(state.get_variable("OK_NAME").as_deref(), Some("3"));
+        assert!(!call("unset", &["A-B"], &mut state).success);
+        assert!(!call("set", &["-q"], &mut state).success);
+    }
+}
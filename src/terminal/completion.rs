@@ -0,0 +1,473 @@
+//! Автодополнение по Tab: команды, пути, git и префиксы интерпретаторов.
+//! Для отдельных команд можно зарегистрировать свой `Completer`
+
+use std::collections::HashMap;
+use std::path::Path;
+use crate::git::{GitManager, GIT_COMMANDS};
+use crate::terminal::builtins::BUILTIN_COMMANDS;
+use crate::terminal::aliases::COMMANDS as ALIAS_COMMANDS;
+use crate::terminal::jobs::COMMANDS as JOB_COMMANDS;
+use crate::terminal::parser::{CommandParser, INTERNAL_COMMANDS};
+use crate::utils::helpers;
+
+/// Вариант дополнения
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub text: String,
+    pub description: Option<String>,
+    /// Добавлять пробел после единственного варианта (для директорий - нет)
+    pub append_space: bool,
+}
+
+impl Candidate {
+    pub fn new(text: &str) -> Self {
+        Self { text: text.to_string(), description: None, append_space: true }
+    }
+
+    pub fn with_description(text: &str, description: &str) -> Self {
+        Self { text: text.to_string(), description: Some(description.to_string()), append_space: true }
+    }
+}
+
+/// Что известно о дополняемом слове
+#[derive(Debug, Clone, Default)]
+pub struct CompletionContext {
+    /// Полные слова текущей команды перед дополняемым
+    pub words: Vec<String>,
+    /// Начало дополняемого слова без кавычек и экранирования
+    pub prefix: String,
+    /// Дополняемое слово стоит после перенаправления (> file)
+    pub after_redirect: bool,
+}
+
+/// Источник вариантов дополнения
+pub trait Completer {
+    fn complete(&self, context: &CompletionContext) -> Vec<Candidate>;
+}
+
+/// Результат дополнения: варианты заменяют текст начиная с символа `start`
+#[derive(Debug, Clone, Default)]
+pub struct Completion {
+    pub start: usize,
+    pub candidates: Vec<Candidate>,
+}
+
+/// Пути к файлам и директориям
+pub struct PathCompleter {
+    pub directories_only: bool,
+}
+
+impl Completer for PathCompleter {
+    fn complete(&self, context: &CompletionContext) -> Vec<Candidate> {
+        complete_path(&context.prefix, |is_dir, _| is_dir || !self.directories_only)
+    }
+}
+
+/// Имена команд: встроенные, внутренние, aliases и функции, $PATH и префиксы !lang
+pub struct CommandCompleter {
+    pub aliases: Vec<String>,
+    pub search_path: String,
+    /// Префиксы !имя с описанием
+    pub bang_prefixes: Vec<(String, String)>,
+}
+
+impl Completer for CommandCompleter {
+    fn complete(&self, context: &CompletionContext) -> Vec<Candidate> {
+        let prefix = context.prefix.as_str();
+        if prefix.contains('/') {
+            return complete_path(prefix, |is_dir, path| is_dir || is_executable(path));
+        }
+        if prefix.starts_with('!') {
+            return self.bang_prefixes.iter()
+                .filter(|(name, _)| name.starts_with(prefix))
+                .map(|(name, description)| Candidate::with_description(name, description))
+                .collect();
+        }
+
+        let mut names: Vec<String> = BUILTIN_COMMANDS.iter()
+            .chain(ALIAS_COMMANDS.iter())
+            .chain(JOB_COMMANDS.iter())
+            .chain(INTERNAL_COMMANDS.iter())
+            .map(|name| name.to_string())
+            .chain(self.aliases.iter().cloned())
+            .filter(|name| name.starts_with(prefix))
+            .collect();
+
+        for dir in self.search_path.split(':').filter(|dir| !dir.is_empty()) {
+            let entries = match std::fs::read_dir(dir) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name.starts_with(prefix) && is_executable(&entry.path()) {
+                    names.push(name);
+                }
+            }
+        }
+
+        names.into_iter().map(|name| Candidate::new(&name)).collect()
+    }
+}
+
+/// Подкоманды git, ветки для checkout/merge/rebase и файлы для add/diff
+pub struct GitCompleter;
+
+impl GitCompleter {
+    const BRANCH_COMMANDS: &'static [&'static str] = &["checkout", "switch", "merge", "rebase", "branch", "push", "pull"];
+}
+
+impl Completer for GitCompleter {
+    fn complete(&self, context: &CompletionContext) -> Vec<Candidate> {
+        match context.words.get(1) {
+            None => GIT_COMMANDS.iter()
+                .filter(|(name, _)| name.starts_with(&context.prefix))
+                .map(|(name, description)| Candidate::with_description(name, description))
+                .collect(),
+            Some(sub) if Self::BRANCH_COMMANDS.contains(&sub.as_str()) => {
+                let branches = std::env::current_dir().ok()
+                    .and_then(|dir| GitManager::new(&dir).ok())
+                    .and_then(|git| git.get_branches().ok())
+                    .unwrap_or_default();
+                branches.iter()
+                    .filter(|branch| branch.starts_with(&context.prefix))
+                    .map(|branch| Candidate::new(branch))
+                    .collect()
+            }
+            Some(_) => PathCompleter { directories_only: false }.complete(context),
+        }
+    }
+}
+
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata().is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+/// Дополняет путь; `accept(is_dir, path)` отбирает подходящие записи
+fn complete_path(prefix: &str, accept: impl Fn(bool, &Path) -> bool) -> Vec<Candidate> {
+    let (dir_part, file_prefix) = match prefix.rfind('/') {
+        Some(pos) => (&prefix[..=pos], &prefix[pos + 1..]),
+        None => ("", prefix),
+    };
+
+    let search_dir = if dir_part.is_empty() {
+        ".".to_string()
+    } else if let Some(rest) = dir_part.strip_prefix("~/") {
+        match helpers::get_home_dir() {
+            Ok(home) => format!("{}/{}", home, rest),
+            Err(_) => return Vec::new(),
+        }
+    } else {
+        dir_part.to_string()
+    };
+
+    let entries = match std::fs::read_dir(&search_dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    entries.flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            // Скрытые файлы - только если пользователь начал с точки
+            if !name.starts_with(file_prefix) || (name.starts_with('.') && !file_prefix.starts_with('.')) {
+                return None;
+            }
+            let path = entry.path();
+            let is_dir = path.is_dir();
+            if !accept(is_dir, &path) {
+                return None;
+            }
+            Some(if is_dir {
+                Candidate {
+                    text: format!("{}{}/", dir_part, name),
+                    description: None,
+                    append_space: false,
+                }
+            } else {
+                Candidate::new(&format!("{}{}", dir_part, name))
+            })
+        })
+        .collect()
+}
+
+/// Разобранная строка до курсора
+#[derive(Debug, Default)]
+struct ParsedLine {
+    context: CompletionContext,
+    /// Позиция начала дополняемого слова (в символах)
+    word_start: usize,
+    /// Открытая кавычка в дополняемом слове
+    quote: Option<char>,
+}
+
+fn parse_line(before_cursor: &str) -> ParsedLine {
+    let mut parsed = ParsedLine::default();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut redirect_pending = false;
+    let mut chars = before_cursor.chars().enumerate().peekable();
+
+    while let Some((index, ch)) = chars.next() {
+        if let Some(quote) = parsed.quote {
+            if ch == quote {
+                parsed.quote = None;
+            } else {
+                current.push(ch);
+            }
+            continue;
+        }
+
+        match ch {
+            '\'' | '"' => {
+                if !in_word {
+                    parsed.word_start = index;
+                    in_word = true;
+                }
+                parsed.quote = Some(ch);
+            }
+            '\\' => {
+                if !in_word {
+                    parsed.word_start = index;
+                    in_word = true;
+                }
+                if let Some((_, next)) = chars.next() {
+                    current.push(next);
+                }
+            }
+            ' ' | '\t' | '|' | ';' | '&' | '<' | '>' => {
+                if in_word {
+                    let word = std::mem::take(&mut current);
+                    if redirect_pending {
+                        redirect_pending = false;
+                    } else {
+                        parsed.context.words.push(word);
+                    }
+                    in_word = false;
+                }
+                match ch {
+                    '|' | ';' | '&' => {
+                        parsed.context.words.clear();
+                        redirect_pending = false;
+                    }
+                    '<' | '>' => redirect_pending = true,
+                    _ => {}
+                }
+                parsed.word_start = index + 1;
+            }
+            _ => {
+                if !in_word {
+                    parsed.word_start = index;
+                    in_word = true;
+                }
+                current.push(ch);
+            }
+        }
+    }
+
+    if !in_word {
+        parsed.word_start = before_cursor.chars().count();
+    }
+    parsed.context.prefix = current;
+    parsed.context.after_redirect = redirect_pending;
+    parsed
+}
+
+/// Экранирует спецсимволы shell в дополненном слове
+fn escape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if matches!(ch, ' ' | '\t' | '\'' | '"' | '\\' | '|' | '&' | ';' | '<' | '>' | '(' | ')' | '$' | '`' | '*' | '?' | '[' | '#') {
+            result.push('\\');
+        }
+        result.push(ch);
+    }
+    result
+}
+
+/// Движок дополнения с набором completers по именам команд
+pub struct CompletionEngine {
+    completers: HashMap<String, Box<dyn Completer>>,
+    aliases: Vec<String>,
+    search_path: String,
+    bang_prefixes: Vec<(String, String)>,
+}
+
+impl CompletionEngine {
+    pub fn new() -> Self {
+        let mut engine = Self {
+            completers: HashMap::new(),
+            aliases: Vec::new(),
+            search_path: std::env::var("PATH").unwrap_or_default(),
+            bang_prefixes: CommandParser::new().bang_prefixes(),
+        };
+
+        engine.register("git", Box::new(GitCompleter));
+        for command in ["cd", "pushd", "rmdir"] {
+            engine.register(command, Box::new(PathCompleter { directories_only: true }));
+        }
+        engine
+    }
+
+    /// Регистрирует completer для аргументов команды
+    pub fn register(&mut self, command: &str, completer: Box<dyn Completer>) {
+        self.completers.insert(command.to_string(), completer);
+    }
+
+    pub fn set_aliases<I: IntoIterator<Item = String>>(&mut self, aliases: I) {
+        self.aliases = aliases.into_iter().collect();
+    }
+
+    /// Каталоги для поиска команд (значение $PATH текущей сессии)
+    pub fn set_search_path(&mut self, search_path: &str) {
+        self.search_path = search_path.to_string();
+    }
+
+    /// Префиксы !имя из разборщика (исполнители кода из конфигурации)
+    pub fn set_bang_prefixes(&mut self, parser: &CommandParser) {
+        self.bang_prefixes = parser.bang_prefixes();
+    }
+
+    /// Дополняет строку `line` в позиции курсора `cursor` (в символах)
+    pub fn complete(&self, line: &str, cursor: usize) -> Completion {
+        let before_cursor: String = line.chars().take(cursor).collect();
+        let parsed = parse_line(&before_cursor);
+        let context = &parsed.context;
+
+        let mut candidates = if context.after_redirect {
+            PathCompleter { directories_only: false }.complete(context)
+        } else if context.words.is_empty() {
+            CommandCompleter {
+                aliases: self.aliases.clone(),
+                search_path: self.search_path.clone(),
+                bang_prefixes: self.bang_prefixes.clone(),
+            }.complete(context)
+        } else {
+            match self.completers.get(&context.words[0]) {
+                Some(completer) => completer.complete(context),
+                None => PathCompleter { directories_only: false }.complete(context),
+            }
+        };
+
+        candidates.sort_by(|a, b| a.text.cmp(&b.text));
+        candidates.dedup_by(|a, b| a.text == b.text);
+
+        // Возвращаем текст в том виде, в каком он попадет в строку
+        for candidate in candidates.iter_mut() {
+            candidate.text = match parsed.quote {
+                Some(quote) => format!("{}{}", quote, candidate.text),
+                None => escape(&candidate.text),
+            };
+        }
+
+        Completion { start: parsed.word_start, candidates }
+    }
+}
+
+impl Default for CompletionEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Общее начало всех вариантов
+pub fn common_prefix(candidates: &[Candidate]) -> String {
+    let mut iter = candidates.iter();
+    let first = match iter.next() {
+        Some(first) => first.text.clone(),
+        None => return String::new(),
+    };
+    iter.fold(first, |prefix, candidate| {
+        prefix.chars()
+            .zip(candidate.text.chars())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a)
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_parse_line_context() {
+        let parsed = parse_line("ls | git che");
+        assert_eq!(parsed.context.words, vec!["git"]);
+        assert_eq!(parsed.context.prefix, "che");
+        assert_eq!(parsed.word_start, 9);
+
+        let parsed = parse_line("echo hi > out");
+        assert!(parsed.context.after_redirect);
+        assert_eq!(parsed.context.words, vec!["echo", "hi"]);
+
+        let parsed = parse_line("cat 'my fi");
+        assert_eq!(parsed.quote, Some('\''));
+        assert_eq!(parsed.context.prefix, "my fi");
+        assert_eq!(parsed.word_start, 4);
+
+        let parsed = parse_line("cd ");
+        assert_eq!(parsed.context.prefix, "");
+        assert_eq!(parsed.word_start, 3);
+    }
+
+    #[test]
+    fn test_language_prefixes_and_git_subcommands() {
+        let engine = CompletionEngine::new();
+        let completion = engine.complete("!py", 3);
+        assert_eq!(completion.candidates.len(), 1);
+        assert_eq!(completion.candidates[0].text, "!python");
+
+        let completion = engine.complete("git st", 6);
+        assert_eq!(completion.start, 4);
+        assert_eq!(completion.candidates[0].text, "status");
+    }
+
+    #[test]
+    fn test_path_completion() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let base = temp_dir.path().to_string_lossy().to_string();
+        fs::create_dir(temp_dir.path().join("src")).unwrap();
+        fs::write(temp_dir.path().join("sample file.txt"), "").unwrap();
+        fs::write(temp_dir.path().join(".secret"), "").unwrap();
+
+        let engine = CompletionEngine::new();
+        let line = format!("cat {}/s", base);
+        let texts: Vec<String> = engine.complete(&line, line.chars().count())
+            .candidates.into_iter().map(|c| c.text).collect();
+        assert_eq!(texts, vec![format!("{}/sample\\ file.txt", escape(&base)), format!("{}/src/", escape(&base))]);
+
+        let line = format!("cd {}/", base);
+        let completion = engine.complete(&line, line.chars().count());
+        assert_eq!(completion.candidates.len(), 1);
+        assert!(!completion.candidates[0].append_space);
+    }
+
+    #[test]
+    fn test_custom_completer() {
+        struct Fixed;
+        impl Completer for Fixed {
+            fn complete(&self, context: &CompletionContext) -> Vec<Candidate> {
+                ["start", "stop", "status"].iter()
+                    .filter(|s| s.starts_with(&context.prefix))
+                    .map(|s| Candidate::new(s))
+                    .collect()
+            }
+        }
+
+        let mut engine = CompletionEngine::new();
+        engine.register("service", Box::new(Fixed));
+        let completion = engine.complete("service st", 10);
+        assert_eq!(common_prefix(&completion.candidates), "st");
+        assert_eq!(completion.candidates.len(), 3);
+    }
+}
@@ -1,100 +1,242 @@
-use std::io::{self, Write, BufRead};
-use crate::terminal::{CommandHistory, CommandExecutor, CommandParser, TerminalConfig, TerminalState};
-use crate::utils::privileges::{PrivilegeManager, PrivilegeLevel};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use crate::config::Config;
+use crate::help::suggest;
+use crate::terminal::{CommandHistory, CommandExecutor, TerminalConfig, TerminalState};
+use crate::terminal::history::{HistoryEntry, HistoryFilter};
+use crate::terminal::history_expansion;
+use crate::terminal::rc;
+use crate::terminal::signals;
+use crate::terminal::prompt::{PromptContext, PromptTemplate};
+use crate::terminal::line_editor::{LineEditor, ReadResult};
+use crate::terminal::completion::CompletionEngine;
+use crate::utils::privileges::PrivilegeManager;
+use crate::git::GitManager;
 use crate::ui::ncurses_like::NcursesLikeUI;
+use crate::ui::keybindings::KeyBindings;
 use crate::utils::helpers;
 
 pub struct Terminal {
     prompt: String,
-    history: CommandHistory,
+    prompt_template: PromptTemplate,
+    line_editor: LineEditor,
     executor: CommandExecutor,
-    parser: CommandParser,
     config: TerminalConfig,
     state: TerminalState,
+    keybindings: KeyBindings,
+    /// Длительность последней команды для приглашения
+    last_duration: Option<Duration>,
 }
 
 impl Terminal {
+    #[allow(dead_code)]
     pub fn new() -> Self {
-        let config = TerminalConfig::default();
-        let state = TerminalState::default();
-        let executor = CommandExecutor::new();
-        let parser = CommandParser::new();
-        let history = CommandHistory::new(config.history_size);
-        
-        let prompt = Self::build_prompt(&state);
-        
-        Self {
-            prompt,
-            history,
-            executor,
-            parser,
-            config,
-            state,
-        }
+        let config = Config::load(None).unwrap_or_else(|e| {
+            helpers::print_error(&e);
+            helpers::print_info("Используется конфигурация по умолчанию");
+            Config::default()
+        });
+        Self::from_config(config)
     }
     
+    #[allow(dead_code)]
     pub fn with_config(config: TerminalConfig) -> Self {
-        let state = TerminalState::default();
-        let executor = CommandExecutor::new();
-        let parser = CommandParser::new();
-        let history = CommandHistory::new(config.history_size);
-        
-        let prompt = if config.prompt.is_empty() {
-            Self::build_prompt(&state)
-        } else {
-            config.prompt.clone()
+        Self::from_config(Config { terminal: config, ..Config::default() })
+    }
+    
+    /// Терминал с конфигурацией из файла: цвета, aliases, клавиши и настройки редактора
+    pub fn from_config(config: Config) -> Self {
+        let state = TerminalState {
+            history: Self::build_history(&config.terminal),
+            ..TerminalState::default()
         };
         
-        Self {
-            prompt,
-            history,
-            executor,
-            parser,
-            config,
+        let mut terminal = Self {
+            prompt: String::new(),
+            prompt_template: PromptTemplate::default(),
+            line_editor: LineEditor::new(),
+            executor: CommandExecutor::new(),
+            config: config.terminal.clone(),
             state,
-        }
+            keybindings: KeyBindings::new(),
+            last_duration: None,
+        };
+        terminal.apply_config(config);
+        terminal
     }
     
-    pub fn run(&mut self) {
+    /// Применяет конфигурацию к работающему терминалу
+    fn apply_config(&mut self, config: Config) {
+        self.executor.apply_config(&config);
+        self.apply_terminal_config(config);
+    }
+    
+    /// Часть конфигурации, которой владеет сам терминал. Файл и размер истории
+    /// при перезагрузке не меняются - они действуют с начала сессии
+    fn apply_terminal_config(&mut self, config: Config) {
+        self.keybindings = config.keybindings;
+        self.state.history.set_filter(HistoryFilter {
+            ignore_space: config.terminal.history_ignore_space,
+            ignore_patterns: config.terminal.history_ignore.clone(),
+        });
+        // Шаблон из файла проверен при загрузке, with_config может передать любой
+        self.prompt_template = if config.terminal.prompt.is_empty() {
+            PromptTemplate::default()
+        } else {
+            PromptTemplate::parse(&config.terminal.prompt).unwrap_or_else(|e| {
+                helpers::print_error(&format!("prompt: {}", e));
+                PromptTemplate::default()
+            })
+        };
+        self.config = config.terminal;
+    }
+    
+    /// Интерактивный режим. Возвращает код завершения для процесса (exit N)
+    pub fn run(&mut self) -> i32 {
         if self.config.show_welcome {
             self.show_welcome_message();
         }
         
-        let stdin = io::stdin();
+        // Ctrl+C во время команды не должен завершать терминал;
+        // терминал получает свою группу процессов до запуска первых команд
+        signals::install();
+        self.executor.enable_job_control();
+        self.load_rc();
+        
+        if self.config.auto_completion {
+            self.line_editor.set_completion(CompletionEngine::new());
+        }
         
         while self.state.is_running {
-            self.show_prompt();
+            for notice in self.executor.job_notifications() {
+                println!("{}", notice);
+            }
+            
+            // Aliases, функции и PATH могли измениться после предыдущей команды
+            if let Some(completion) = self.line_editor.completion_mut() {
+                completion.set_aliases(self.executor.get_aliases().keys()
+                    .chain(self.executor.get_functions().keys())
+                    .cloned());
+                completion.set_search_path(&self.state.get_variable("PATH").unwrap_or_default());
+                completion.set_bang_prefixes(self.executor.parser());
+            }
             
-            if let Some(input) = stdin.lock().lines().next() {
-                match input {
-                    Ok(line) => self.process_input(&line),
-                    Err(e) => eprintln!("Ошибка чтения: {}", e),
+            // Сигнал, пришедший вне команды, не должен прервать следующую
+            signals::take_interrupt();
+            // Приглашение (и git status для него) - только перед вводом
+            self.update_prompt();
+            match self.line_editor.read_line(&self.prompt, &mut self.state.history) {
+                Ok(ReadResult::Line(line)) => {
+                    // !python без кода - многострочный ввод
+                    let runner = self.executor.parser().split_runner(line.trim()).filter(|(_, code)| code.is_empty());
+                    if let Some((name, _)) = runner {
+                        if let Some(code) = self.read_code_block(&name) {
+                            self.process_input(&format!("!{} {}", name, code));
+                        }
+                    } else {
+                        self.process_input(&line);
+                    }
+                    if !self.state.is_running {
+                        helpers::print_success("До свидания!");
+                    }
+                }
+                Ok(ReadResult::Interrupted) => self.state.last_exit_code = signals::exit_code(libc::SIGINT),
+                Ok(ReadResult::SwitchToUi) => self.activate_ui_mode(),
+                Ok(ReadResult::Eof) => {
+                    helpers::print_success("До свидания!");
+                    self.state.is_running = false;
+                }
+                Err(e) => {
+                    eprintln!("Ошибка чтения: {}", e);
+                    self.state.is_running = false;
                 }
             }
         }
+        self.state.last_exit_code
     }
     
-    fn build_prompt(state: &TerminalState) -> String {
-        let privilege_level = PrivilegeManager::check_privileges();
-        let user_indicator = match privilege_level {
-            PrivilegeLevel::Root => "🔴",
-            PrivilegeLevel::Admin => "🔴", 
-            PrivilegeLevel::User => "🟢",
-            PrivilegeLevel::Unknown => "⚪",
+    /// Многострочный ввод для исполнителя кода: строки до пустой, как в
+    /// REPL Python. Ctrl+C или Ctrl+D отменяют ввод
+    fn read_code_block(&mut self, runner: &str) -> Option<String> {
+        helpers::print_info(&format!("!{}: пустая строка - выполнить, Ctrl+C - отменить", runner));
+        let mut lines = Vec::new();
+        loop {
+            match self.line_editor.read_line("... ", &mut self.state.history) {
+                Ok(ReadResult::Line(line)) if line.trim().is_empty() => break,
+                Ok(ReadResult::Line(line)) => lines.push(line),
+                Ok(ReadResult::Interrupted) => {
+                    self.state.last_exit_code = signals::exit_code(libc::SIGINT);
+                    return None;
+                }
+                _ => return None,
+            }
+        }
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    /// Выполняет команды без интерактивного ввода: -c, файл скрипта или stdin.
+    /// История, стартовый файл и управление заданиями не используются.
+    /// Возвращает код завершения последней команды (или переданный в exit)
+    pub fn run_script(&mut self, source: &str) -> i32 {
+        for command in rc::split_commands(source) {
+            self.execute_input(&command.text);
+            if !self.state.is_running {
+                break;
+            }
+        }
+        self.state.last_exit_code
+    }
+    
+    /// Задает $0 и позиционные параметры скрипта
+    pub fn set_script_args(&mut self, name: String, args: Vec<String>) {
+        self.state.script_name = name;
+        self.state.positional_args = args;
+    }
+    
+    /// Включает опцию оболочки (errexit, xtrace) до запуска
+    pub fn set_option(&mut self, name: &str, value: bool) -> Result<(), String> {
+        self.state.options.set(name, value)
+    }
+    
+    /// Выполняет стартовый файл (~/.smarttermrc). Ошибки не прерывают
+    /// запуск: печатается строка файла и выполнение продолжается
+    fn load_rc(&mut self) {
+        let path = match &self.config.rc_file {
+            Some(path) => path.clone(),
+            None => return,
+        };
+        let commands = match rc::load(&path) {
+            Ok(commands) => commands,
+            Err(e) => {
+                helpers::print_error(&e);
+                return;
+            }
         };
         
-        if cfg!(target_os = "windows") {
-            format!("{} {}$ ", user_indicator, state.current_directory)
-        } else {
-            // Unix-style prompt с цветами
-            format!(
-                "\x1b[1;32m{} {}@{}:\x1b[1;34m{}\x1b[0m$ ",
-                user_indicator,
-                state.username,
-                state.hostname,
-                state.current_directory
-            )
+        for command in commands {
+            self.state.last_exit_code = 0;
+            self.execute_input(&command.text);
+            if self.state.last_exit_code != 0 {
+                let first_line = command.text.lines().next().unwrap_or_default();
+                helpers::print_warning(&format!("{}:{}: ошибка в команде '{}'", path.display(), command.line, first_line));
+            }
+            if !self.state.is_running {
+                break;
+            }
         }
+        self.state.last_exit_code = 0;
+    }
+    
+    fn build_history(config: &TerminalConfig) -> CommandHistory {
+        let mut history = match &config.history_file {
+            Some(path) => CommandHistory::with_file(config.history_size, path.clone()),
+            None => CommandHistory::new(config.history_size),
+        };
+        history.set_filter(HistoryFilter {
+            ignore_space: config.history_ignore_space,
+            ignore_patterns: config.history_ignore.clone(),
+        });
+        history
     }
     
     fn show_welcome_message(&self) {
@@ -128,8 +270,9 @@ impl Terminal {
         println!("║ 🎮 ГОРЯЧИЕ КЛАВИШИ И КОМАНДЫ:                                               ║");
         println!("║                                                                              ║");
         println!("║  Ctrl+U        - Переключение в псевдографический режим                     ║");
-        println!("║  Tab           - Автодополнение (в разработке)                              ║");
+        println!("║  Tab           - Автодополнение команд, путей и git                         ║");
         println!("║  Стрелки ↑↓    - Навигация по истории команд                                ║");
+        println!("║  Ctrl+R        - Нечеткий поиск по истории                                  ║");
         println!("║  help          - Справка по терминалу                                       ║");
         println!("║  bash-help     - Полная справка по Bash                                     ║");
         println!("║  bash-quick    - Быстрая справка (часто используемые команды)               ║");
@@ -138,7 +281,7 @@ impl Terminal {
         println!("║  privileges    - Показать текущий уровень прав                              ║");
         println!("║  history       - Показать историю команд                                    ║");
         println!("║  clear         - Очистить экран                                             ║");
-        println!("║  exit/quit     - Выход из терминала                                         ║");
+        println!("║  exit [N]      - Выход из терминала с кодом N                               ║");
         println!("║                                                                              ║");
         println!("╠══════════════════════════════════════════════════════════════════════════════╣");
         println!("║ 💡 ПРИМЕРЫ ИСПОЛЬЗОВАНИЯ:                                                   ║");
@@ -154,49 +297,94 @@ impl Terminal {
         println!();
     }
     
-    fn show_prompt(&self) {
-        print!("{}", self.prompt);
-        if let Err(e) = io::stdout().flush() {
-            eprintln!("Ошибка вывода: {}", e);
-        }
-    }
-    
-    fn process_input(&mut self, input: &str) {
-        let input = input.trim();
+    fn process_input(&mut self, raw_input: &str) {
+        let input = raw_input.trim();
         
         if input.is_empty() {
             return;
         }
         
-        // Проверяем специальные комбинации клавиш
-        if input == "\x15" { // Ctrl+U
-            self.activate_ui_mode();
-            return;
+        // !!, !$, ^old^new раскрываются до разбора; итоговая команда показывается
+        let expanded;
+        let input = match history_expansion::expand(input, &self.state.history, self.executor.parser()) {
+            Ok(Some(command)) => {
+                println!("{}", command);
+                expanded = command;
+                expanded.as_str()
+            }
+            Ok(None) => input,
+            Err(e) => {
+                helpers::print_error(&e);
+                self.state.last_exit_code = 1;
+                return;
+            }
+        };
+        
+        // Фильтр проверяет исходную строку: команда с пробелом в начале не сохраняется
+        let record = !self.state.history.filter().should_ignore(raw_input) && input != "history -c";
+        let mut entry = HistoryEntry::new(input);
+        entry.cwd = self.state.current_directory.clone();
+        let started = Instant::now();
+        
+        self.execute_input(input);
+        let duration = started.elapsed();
+        
+        if record {
+            entry.exit_code = self.state.last_exit_code;
+            entry.duration_ms = duration.as_millis() as u64;
+            self.state.history.add_entry(entry);
         }
         
-        // Добавляем в историю (кроме специальных комбинаций)
-        // ИСПРАВЛЕНИЕ: убрана некорректная escape-последовательность
-        if !input.starts_with('\x15') && !input.chars().next().map_or(false, |c| c.is_control()) {
-            self.history.add(input.to_string());
+        // Длительность долгой команды показывается в следующем приглашении
+        self.last_duration = Some(duration);
+    }
+    
+    /// suggest <запрос>: подбирает команды по описанию задачи. Выбранный пример
+    /// не запускается, а подставляется в строку ввода: в нем есть заглушки
+    /// вроде <файл>, которые нужно заменить
+    fn run_suggest(&mut self, query: &str) {
+        let query = query.trim_matches(|c| c == '"' || c == '\'');
+        if query.is_empty() {
+            helpers::print_error("suggest: использование: suggest \"как найти файл\"");
+            self.state.last_exit_code = 2;
+            return;
+        }
+        let suggestions = suggest::suggest(query, suggest::SUGGESTION_LIMIT);
+        if suggestions.is_empty() {
+            helpers::print_warning(&format!("Нет подходящих команд для '{}'", query));
+            self.state.last_exit_code = 1;
+            return;
+        }
+        if let Some(suggestion) = suggest::pick(&suggestions) {
+            self.line_editor.set_initial_text(&suggestion.command);
+            helpers::print_info("Команда подставлена в строку ввода: замените заглушки и нажмите Enter");
+        }
+        self.state.last_exit_code = 0;
+    }
+    
+    /// Ошибка разбора или раскрытия команды; при set -e завершает выполнение
+    fn fail(&mut self, error: &str) {
+        helpers::print_error(error);
+        self.state.last_exit_code = 1;
+        if self.state.options.errexit {
+            self.state.is_running = false;
+        }
+    }
+    
+    fn execute_input(&mut self, input: &str) {
+        if input == "suggest" || input.starts_with("suggest ") {
+            self.run_suggest(input["suggest".len()..].trim());
+            return;
         }
         
         // Обрабатываем специальные команды
         match input {
-            "exit" | "quit" => {
-                helpers::print_success("До свидания!");
-                self.state.is_running = false;
-                return;
-            }
             "clear" => {
                 helpers::clear_screen();
                 return;
             }
-            "history" => {
-                self.show_history();
-                return;
-            }
             "help" | "bash-help" | "bash-quick" => {
-                let _ = self.executor.execute_internal_command(input);
+                self.show_help(input);
                 return;
             }
             "elevate" => {
@@ -224,37 +412,53 @@ impl Terminal {
         
         // Если команда начинается с "help " - обрабатываем как запрос справки
         if input.starts_with("help ") {
-            let _ = self.executor.execute_internal_command(input);
+            self.show_help(input);
             return;
         }
         
-        // Парсим и выполняем команду
-        match self.parser.parse(input) {
+        // Подставляем aliases, парсим и выполняем команду
+        match self.executor.parser().parse(&self.executor.expand_aliases(input)) {
             Ok(command) => {
-                match self.executor.execute(&command) {
+                match self.executor.execute(&command, &mut self.state) {
                     Ok(result) => {
-                        if !result.output.is_empty() {
+                        if !result.streamed && !result.output.is_empty() {
                             println!("{}", result.output);
                         }
-                        if let Some(error) = result.error {
-                            helpers::print_error(&error);
+                        if let Some(error) = &result.error {
+                            helpers::print_error(error);
                         }
-                        self.state.last_exit_code = if result.success { 0 } else { 1 };
-                    }
-                    Err(e) => {
-                        helpers::print_error(&e);
-                        self.state.last_exit_code = 1;
+                        // После ^C приглашение начинается с новой строки
+                        if signals::is_interrupt(result.exit_code()) {
+                            println!();
+                        }
+                        self.state.last_exit_code = result.exit_code();
                     }
+                    Err(e) => self.fail(&e),
                 }
             }
-            Err(e) => {
-                helpers::print_error(&e);
-                self.state.last_exit_code = 1;
-            }
+            Err(e) => self.fail(&e),
         }
         
-        // Обновляем промпт после выполнения команды
-        self.update_prompt();
+        // config reload в этой строке: клавиши, приглашение и фильтр истории
+        if let Some(config) = self.executor.take_reloaded_config() {
+            self.apply_terminal_config(config);
+        }
+    }
+    
+    /// help, bash-help, bash-quick и help <команда>
+    fn show_help(&mut self, input: &str) {
+        match self.executor.execute_internal_command(input, &self.state) {
+            Ok(result) => {
+                if !result.output.is_empty() {
+                    println!("{}", result.output);
+                }
+                if let Some(error) = &result.error {
+                    helpers::print_error(error);
+                }
+                self.state.last_exit_code = result.exit_code();
+            }
+            Err(e) => self.fail(&e),
+        }
     }
     
     fn activate_ui_mode(&self) {
@@ -266,8 +470,9 @@ impl Terminal {
         println!("   : - ввод команд (как в Vim)");
         println!();
         
-        match NcursesLikeUI::new() {
+        match NcursesLikeUI::with_history(self.state.history.clone()) {
             Ok(mut ui) => {
+                ui.set_keybindings(self.keybindings.clone());
                 if let Err(e) = ui.run() {
                     helpers::print_error(&format!("Ошибка в графическом режиме: {}", e));
                     helpers::print_info("Возврат в текстовый режим...");
@@ -301,32 +506,68 @@ impl Terminal {
         println!("Повышены ли права: {}", PrivilegeManager::is_elevated());
     }
     
-    fn show_history(&self) {
-        println!("История команд:");
-        for (i, cmd) in self.history.get_all().iter().enumerate() {
-            println!("{:4}: {}", i + 1, cmd);
-        }
-    }
-    
     fn update_prompt(&mut self) {
         // Обновляем текущую директорию
         if let Ok(current_dir) = std::env::current_dir() {
             self.state.current_directory = current_dir.to_string_lossy().to_string();
         }
         
-        // Перестраиваем промпт
-        self.prompt = Self::build_prompt(&self.state);
+        let slow = self.last_duration.filter(|duration| {
+            self.config.slow_command_threshold.is_some_and(|threshold| *duration >= threshold)
+        });
+        // git status только если он есть в шаблоне и не дольше таймаута
+        let git = if self.prompt_template.uses_git() {
+            GitManager::new(Path::new(&self.state.current_directory))
+                .and_then(|git| git.get_status_within(self.config.prompt_git_timeout))
+                .ok()
+        } else {
+            None
+        };
+        let home = self.state.get_variable("HOME");
+        let colors = helpers::color_scheme();
+        let context = PromptContext {
+            user: &self.state.username,
+            host: &self.state.hostname,
+            cwd: &self.state.current_directory,
+            home: home.as_deref(),
+            privilege: PrivilegeManager::check_privileges(),
+            exit_code: self.state.last_exit_code,
+            duration: slow,
+            git: git.as_ref(),
+            colors: &colors,
+        };
+        self.prompt = self.prompt_template.render(&context);
     }
     
-    pub fn set_prompt(&mut self, prompt: String) {
-        self.prompt = prompt;
+    /// Меняет шаблон приглашения (синтаксис - как у prompt в конфигурации)
+    #[allow(dead_code)]
+    pub fn set_prompt(&mut self, template: &str) -> Result<(), String> {
+        self.prompt_template = PromptTemplate::parse(template)?;
+        Ok(())
     }
-    
-    pub fn get_state(&self) -> &TerminalState {
-        &self.state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Конфигурация без файлов: тесты не трогают историю и rc пользователя
+    fn test_config() -> Config {
+        let mut config = Config::default();
+        config.terminal.history_file = None;
+        config.terminal.rc_file = None;
+        config.guard.log_file = None;
+        config
     }
-    
-    pub fn get_config(&self) -> &TerminalConfig {
-        &self.config
+
+    #[test]
+    fn test_script_exit_code() {
+        // smart-term -c 'exit 7'
+        let mut terminal = Terminal::from_config(test_config());
+        assert_eq!(terminal.run_script("exit 7"), 7);
+
+        // exit прерывает скрипт, quit - то же самое
+        let mut terminal = Terminal::from_config(test_config());
+        assert_eq!(terminal.run_script("quit 3\nexit 5"), 3);
     }
 }
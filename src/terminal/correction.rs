@@ -0,0 +1,204 @@
+//! Исправление опечаток в именах команд ("Возможно, вы имели в виду...")
+//! и подсказка пакета для установки известной, но отсутствующей утилиты
+
+use std::io::{self, Write};
+use std::path::Path;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::terminal;
+
+/// Пакеты, в которых лежат известные утилиты (имена для apt; для других
+/// менеджеров пакетов они в основном совпадают)
+const PACKAGES: &[(&str, &str)] = &[
+    ("htop", "htop"), ("tree", "tree"), ("jq", "jq"), ("curl", "curl"), ("wget", "wget"),
+    ("git", "git"), ("vim", "vim"), ("nvim", "neovim"), ("tmux", "tmux"), ("mc", "mc"),
+    ("rg", "ripgrep"), ("fd", "fd-find"), ("fdfind", "fd-find"), ("bat", "bat"), ("ncdu", "ncdu"),
+    ("locate", "plocate"), ("zip", "zip"), ("unzip", "unzip"), ("make", "make"), ("cmake", "cmake"),
+    ("gcc", "gcc"), ("g++", "g++"), ("python3", "python3"), ("pip3", "python3-pip"),
+    ("node", "nodejs"), ("npm", "npm"), ("java", "default-jre"), ("javac", "default-jdk"),
+    ("cargo", "cargo"), ("rustc", "rustc"), ("docker", "docker.io"), ("ssh", "openssh-client"),
+    ("netstat", "net-tools"), ("ifconfig", "net-tools"), ("dig", "dnsutils"), ("nslookup", "dnsutils"),
+    ("traceroute", "traceroute"), ("whois", "whois"), ("nmap", "nmap"), ("ffmpeg", "ffmpeg"),
+];
+
+/// Менеджеры пакетов и команда установки, в порядке проверки
+const PACKAGE_MANAGERS: &[(&str, &str)] = &[
+    ("apt", "sudo apt install"),
+    ("dnf", "sudo dnf install"),
+    ("pacman", "sudo pacman -S"),
+    ("zypper", "sudo zypper install"),
+    ("brew", "brew install"),
+];
+
+/// Расстояние Дамерау-Левенштейна (вставка, удаление, замена и
+/// перестановка соседних символов): stauts -> status = 1
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Допустимое число ошибок: в коротких именах - одна, иначе две
+fn max_distance(word: &str) -> usize {
+    if word.chars().count() <= 4 { 1 } else { 2 }
+}
+
+/// Ближайшие к word варианты: сначала с меньшим расстоянием, при
+/// равенстве - по алфавиту
+pub fn closest<I, S>(word: &str, candidates: I) -> Vec<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let limit = max_distance(word);
+    let mut matches: Vec<(usize, String)> = candidates.into_iter()
+        .filter_map(|candidate| {
+            let candidate = candidate.as_ref();
+            let distance = edit_distance(word, candidate);
+            (distance > 0 && distance <= limit).then(|| (distance, candidate.to_string()))
+        })
+        .collect();
+    matches.sort();
+    matches.dedup();
+    matches.into_iter().map(|(_, candidate)| candidate).collect()
+}
+
+/// Вариант, который можно предложить запустить одной клавишей: лучший,
+/// если он единственный с таким расстоянием
+pub fn best<'a>(word: &str, candidates: &'a [String]) -> Option<&'a str> {
+    let first = candidates.first()?;
+    let distance = edit_distance(word, first);
+    let ambiguous = candidates.get(1).is_some_and(|second| edit_distance(word, second) == distance);
+    (!ambiguous).then_some(first.as_str())
+}
+
+/// Есть ли исполняемый файл program в каталогах search_path.
+/// Путь со слешем не проверяется - ошибку покажет запуск
+pub fn command_exists(program: &str, search_path: &str) -> bool {
+    program.contains('/')
+        || search_path.split(':')
+            .filter(|dir| !dir.is_empty())
+            .any(|dir| is_executable(&Path::new(dir).join(program)))
+}
+
+/// Имена исполняемых файлов в каталогах search_path
+pub fn path_commands(search_path: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    for dir in search_path.split(':').filter(|dir| !dir.is_empty()) {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            if is_executable(&entry.path()) {
+                names.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+    }
+    names
+}
+
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata().is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+/// Как установить известную утилиту: "sudo apt install fd-find"
+pub fn install_hint(program: &str, search_path: &str) -> Option<String> {
+    let (_, package) = PACKAGES.iter().find(|(name, _)| *name == program)?;
+    let install = PACKAGE_MANAGERS.iter()
+        .find(|(manager, _)| command_exists(manager, search_path))
+        .map_or("sudo apt install", |&(_, install)| install);
+    Some(format!("{} {}", install, package))
+}
+
+/// Сообщение о ненайденной команде с вариантами исправления и пакетом
+pub fn not_found_message(program: &str, candidates: &[String], search_path: &str) -> String {
+    let mut message = format!("{}: команда не найдена", program);
+    if !candidates.is_empty() {
+        let shown: Vec<&str> = candidates.iter().take(5).map(String::as_str).collect();
+        message.push_str(&format!("\nВозможно, вы имели в виду: {}", shown.join(", ")));
+    }
+    if let Some(hint) = install_hint(program, search_path) {
+        message.push_str(&format!("\nУстановить: {}", hint));
+    }
+    message
+}
+
+/// Предлагает исправление и ждет одно нажатие: Enter или y - запустить,
+/// любая другая клавиша - отказаться
+pub fn confirm(question: &str) -> bool {
+    print!("{} [Enter - да, другая клавиша - нет] ", question);
+    let _ = io::stdout().flush();
+    if terminal::enable_raw_mode().is_err() {
+        return false;
+    }
+    let accepted = loop {
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind != KeyEventKind::Release => {
+                break matches!(key.code, KeyCode::Enter | KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Char('д'));
+            }
+            Ok(_) => continue,
+            Err(_) => break false,
+        }
+    };
+    let _ = terminal::disable_raw_mode();
+    println!();
+    accepted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("stauts", "status"), 1);
+        assert_eq!(edit_distance("gti", "git"), 1);
+        assert_eq!(edit_distance("sl", "ls"), 1);
+        assert_eq!(edit_distance("pyhton3", "python3"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "ls"), 2);
+    }
+
+    #[test]
+    fn test_closest() {
+        let candidates = ["status", "stash", "show", "switch"];
+        assert_eq!(closest("stauts", candidates), vec!["status"]);
+        assert_eq!(closest("stsh", candidates), vec!["stash"]);
+        assert!(closest("commit", candidates).is_empty());
+
+        let found = closest("cta", ["cat", "cd", "ctags"]);
+        assert_eq!(found, vec!["cat"]);
+        assert_eq!(best("cta", &found), Some("cat"));
+        let ambiguous = closest("ca", ["cat", "cd"]);
+        assert_eq!(best("ca", &ambiguous), None);
+    }
+
+    #[test]
+    fn test_install_hint() {
+        let hint = install_hint("rg", "").unwrap();
+        assert!(hint.ends_with(" ripgrep"), "{}", hint);
+        assert_eq!(install_hint("no-such-tool", ""), None);
+        let message = not_found_message("gti", &["git".to_string()], "");
+        assert_eq!(message, "gti: команда не найдена\nВозможно, вы имели в виду: git");
+    }
+}
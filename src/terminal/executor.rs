@@ -1,15 +1,57 @@
 use std::process::{Command, Stdio};
 use std::collections::HashMap;
-use crate::terminal::{CommandResult, CommandType, ParsedCommand};
-use crate::utils::privileges::PrivilegeManager;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use crate::config::{self, Config};
+use crate::terminal::{CommandResult, CommandType, ExitStatus, ParsedCommand, ResourceUsage, TerminalState};
+use crate::terminal::builtins;
+use crate::terminal::correction;
+use crate::terminal::guard::{self, Action, Decision, Guard, GuardConfig, Invocation};
+use crate::terminal::aliases;
+use crate::terminal::CommandParser;
+use crate::terminal::parser::{self, CommandList, Connector, Pipeline, Redirection, SimpleCommand};
+use crate::terminal::lexer::Word;
+use crate::terminal::pipeline::{self, LaunchMode, Stage, StageRedirect};
+use crate::terminal::jobs::{self, Foreground, JobTable};
+use crate::terminal::expand;
+use crate::terminal::signals;
+use crate::terminal::glob;
+use crate::utils::helpers;
+use crate::editor::EditorSettings;
 use crate::editor::micro_like::MicroEditor;
-use crate::git::GitManager;
+use crate::git::{GitManager, GIT_COMMANDS};
 use crate::help::bash_help::BashHelp;
+use crate::runners::{RunnerConfig, RunnerRegistry};
+
+/// Ограничение глубины вызовов функций (защита от бесконечной рекурсии)
+const MAX_FUNCTION_DEPTH: usize = 100;
+
+/// Команды, перед которыми при set -o globpreview показываются раскрытые шаблоны
+const DESTRUCTIVE_COMMANDS: &[&str] = &["rm", "rmdir", "mv", "cp", "chmod", "chown", "shred", "truncate"];
+
+/// Подкоманды git, которые выполняет встроенная обертка (execute_git_command)
+const WRAPPED_GIT_COMMANDS: &[&str] = &["status", "add", "commit", "push", "pull", "log", "diff", "branch", "checkout"];
 
 pub struct CommandExecutor {
     bash_aliases: HashMap<String, String>,
-    // Нужно передать путь и обработать Result
-    git: GitManager::new(Path::new(".")).expect("Failed to create GitManager"),
+    /// Aliases из секции [aliases]: при перезагрузке конфигурации заменяются
+    config_aliases: HashMap<String, String>,
+    // None, если текущая директория не в git репозитории
+    git: Option<GitManager>,
+    editor_settings: EditorSettings,
+    /// Файл конфигурации для config reload
+    config_path: Option<PathBuf>,
+    /// Конфигурация после config reload; клавиши и приглашение применяет терминал
+    reloaded_config: Option<Config>,
+    /// Функции shell: имя -> тело
+    functions: HashMap<String, String>,
+    function_depth: usize,
+    jobs: JobTable,
+    guard: Guard,
+    /// Исполнители кода !rust, !python, ... и их сессии
+    runners: RunnerRegistry,
+    /// Разборщик, знающий префиксы исполнителей из реестра
+    parser: CommandParser,
 }
 
 impl CommandExecutor {
@@ -31,103 +73,636 @@ impl CommandExecutor {
         
         Self {
             bash_aliases: aliases,
-            // Нужно передать путь и обработать Result
-            git: GitManager::new(Path::new(".")).expect("Failed to create GitManager"),
+            config_aliases: HashMap::new(),
+            git: GitManager::new(Path::new(".")).ok(),
+            editor_settings: EditorSettings::default(),
+            config_path: None,
+            reloaded_config: None,
+            functions: HashMap::new(),
+            function_depth: 0,
+            jobs: JobTable::new(),
+            guard: Guard::default(),
+            runners: RunnerRegistry::new(),
+            parser: CommandParser::new(),
         }
     }
     
-    pub fn execute(&self, command: &ParsedCommand) -> Result<CommandResult, String> {
+    /// Перечитывает git репозиторий для текущей директории (после cd)
+    fn refresh_git(&mut self) {
+        self.git = std::env::current_dir()
+            .ok()
+            .and_then(|dir| GitManager::new(&dir).ok());
+    }
+    
+    /// Выполняет команду и дополняет результат затраченными ресурсами
+    pub fn execute(&mut self, command: &ParsedCommand, state: &mut TerminalState) -> Result<CommandResult, String> {
+        let started = Instant::now();
+        let children_before = ResourceUsage::children();
+        let mut result = self.dispatch(command, state)?;
+        Self::complete_usage(&mut result, started, &children_before);
+        Ok(result)
+    }
+    
+    /// Реальное время - всегда, процессорное - по RUSAGE_CHILDREN, если
+    /// его не посчитал wait4 задания (!bash, !python и встроенные команды)
+    fn complete_usage(result: &mut CommandResult, started: Instant, children_before: &ResourceUsage) {
+        if result.usage.max_rss_kb.is_none() {
+            result.usage = ResourceUsage::children().since(children_before);
+        }
+        result.usage.wall = started.elapsed();
+    }
+    
+    fn dispatch(&mut self, command: &ParsedCommand, state: &mut TerminalState) -> Result<CommandResult, String> {
         match &command.cmd_type {
-            CommandType::System(cmd, args) => {
-                self.execute_system_command(cmd, args)
+            CommandType::Shell(list) => {
+                self.execute_list(list, state)
             }
             CommandType::Bash(code) => {
-                self.execute_bash_command(code)
-            }
-            CommandType::Rust(code) => {
-                self.execute_rust_code(code)
-            }
-            CommandType::Python(code) => {
-                self.execute_python_code(code)
+                if let Some(result) = self.guard_code(code) {
+                    return Ok(result);
+                }
+                self.execute_bash_command(code, state)
             }
-            CommandType::Java(code) => {
-                self.execute_java_code(code)
+            CommandType::Runner(name, code) => {
+                if self.runners.interpreter(name).is_some_and(guard::is_shell) {
+                    if let Some(result) = self.guard_code(code) {
+                        return Ok(result);
+                    }
+                }
+                self.runners.run(name, code, state)
             }
             CommandType::Editor(filename) => {
                 self.execute_editor_command(filename)
             }
             CommandType::Git(sub_cmd, args) => {
-                self.execute_git_command(sub_cmd, args)
+                let argv: Vec<String> = std::iter::once(sub_cmd.clone()).chain(args.iter().cloned()).collect();
+                if self.wraps_git(&argv) {
+                    self.run_git(&argv)
+                } else {
+                    self.run_line(&format!("git {}", builtins::quote_argv(&argv)), state)
+                }
             }
             CommandType::Crypto(sub_cmd, args) => {
                 self.execute_crypto_command(sub_cmd, args)
             }
-            CommandType::Internal(cmd) => {
-                self.execute_internal_command(cmd)
+            CommandType::Function(name, body) => {
+                self.functions.insert(name.clone(), body.clone());
+                Ok(CommandResult::success(String::new()))
             }
         }
     }
     
-    fn execute_system_command(&self, cmd: &str, args: &[String]) -> Result<CommandResult, String> {
-        // Проверяем aliases
-        let final_cmd = if let Some(alias) = self.bash_aliases.get(cmd) {
-            alias.clone()
-        } else {
-            cmd.to_string()
+    /// Раскрывает aliases, разбирает и выполняет строку (тело функции)
+    pub fn run_line(&mut self, line: &str, state: &mut TerminalState) -> Result<CommandResult, String> {
+        let command = self.parser.parse(&self.expand_aliases(line))?;
+        self.execute(&command, state)
+    }
+    
+    /// Подставляет aliases в начале каждой команды строки
+    pub fn expand_aliases(&self, line: &str) -> String {
+        aliases::expand(line, &self.bash_aliases, &self.parser)
+    }
+    
+    fn call_function(&mut self, argv: &[String], body: &str, state: &mut TerminalState) -> Result<CommandResult, String> {
+        if self.function_depth >= MAX_FUNCTION_DEPTH {
+            return Ok(CommandResult::error(format!(
+                "{}: превышена максимальная глубина вложенности функций ({})", argv[0], MAX_FUNCTION_DEPTH
+            )));
+        }
+        
+        let saved_args = std::mem::replace(&mut state.positional_args, argv[1..].to_vec());
+        self.function_depth += 1;
+        let result = self.run_line(body, state);
+        self.function_depth -= 1;
+        state.positional_args = saved_args;
+        result
+    }
+    
+    /// Включает управление заданиями (только для интерактивного режима)
+    pub fn enable_job_control(&mut self) {
+        self.jobs.enable();
+    }
+    
+    /// Уведомления о завершившихся фоновых заданиях для вывода перед приглашением
+    pub fn job_notifications(&mut self) -> Vec<String> {
+        self.jobs.take_notifications()
+    }
+    
+    /// Конфигурация, перечитанная командой config reload
+    pub fn take_reloaded_config(&mut self) -> Option<Config> {
+        self.reloaded_config.take()
+    }
+    
+    /// config [path|reload]. Новые цвета, aliases, правила защиты и исполнители
+    /// действуют сразу, остальное терминал применяет после выполнения строки
+    fn run_config_builtin(&mut self, args: &[String]) -> CommandResult {
+        match args {
+            [] => self.show_config_path(),
+            [arg] if arg == "path" => self.show_config_path(),
+            [arg] if arg == "reload" => {
+                // Файл, указанный через --config, перечитывается даже если появился файл в XDG
+                let loaded = match &self.config_path {
+                    Some(path) => Config::load_file(path),
+                    None => Config::load(None),
+                };
+                match loaded {
+                    Ok(config) => {
+                        let source = config.path.as_ref()
+                            .map(|path| path.display().to_string())
+                            .unwrap_or_else(|| "значения по умолчанию".to_string());
+                        self.apply_config(&config);
+                        self.reloaded_config = Some(config);
+                        CommandResult::success(format!("Конфигурация перезагружена: {}", source))
+                    }
+                    Err(e) => CommandResult::error(format!("config: {}\nТекущая конфигурация не изменена", e)),
+                }
+            }
+            _ => CommandResult {
+                status: ExitStatus::Code(2),
+                ..CommandResult::error("config: использование: config [path|reload]".to_string())
+            },
+        }
+    }
+    
+    fn show_config_path(&self) -> CommandResult {
+        match &self.config_path {
+            Some(path) => CommandResult::success(path.display().to_string()),
+            None => {
+                let mut lines = vec![
+                    "Файл конфигурации не найден, используются значения по умолчанию.".to_string(),
+                    "Поиск выполняется в:".to_string(),
+                ];
+                lines.extend(config::search_paths().iter().map(|path| format!("  {}", path.display())));
+                CommandResult::success(lines.join("\n"))
+            }
+        }
+    }
+    
+    /// jobs, fg, bg, kill и wait - работают с таблицей заданий
+    fn run_job_builtin(&mut self, argv: &[String]) -> Option<CommandResult> {
+        let (name, args) = argv.split_first()?;
+        let result = match name.as_str() {
+            "jobs" => self.jobs.jobs(args).map(CommandResult::success),
+            "bg" => self.jobs.bg(args).map(CommandResult::success),
+            "kill" => self.jobs.kill(args).map(CommandResult::success),
+            "wait" => self.jobs.wait(args).map(|(output, status)| CommandResult { status, ..CommandResult::new(status.success(), output, None) }),
+            "fg" => self.jobs.fg(args).map(Self::foreground_result),
+            _ => return None,
+        };
+        Some(result.unwrap_or_else(|error| CommandResult::error(format!("{}: {}", name, error))))
+    }
+    
+    /// alias, unalias и unset -f - работают с таблицами исполнителя
+    fn run_definition_builtin(&mut self, argv: &[String]) -> Option<CommandResult> {
+        let (name, args) = argv.split_first()?;
+        let result = match name.as_str() {
+            "alias" => aliases::run_alias(args, &mut self.bash_aliases),
+            "unalias" => aliases::run_unalias(args, &mut self.bash_aliases),
+            "unset" if args.first().map(String::as_str) == Some("-f") => {
+                for function in &args[1..] {
+                    self.functions.remove(function);
+                }
+                Ok(String::new())
+            }
+            _ => return None,
         };
+        Some(match result {
+            Ok(output) => CommandResult::success(output),
+            Err(error) => CommandResult::error(format!("{}: {}", name, error)),
+        })
+    }
+    
+    fn execute_list(&mut self, list: &CommandList, state: &mut TerminalState) -> Result<CommandResult, String> {
+        let mut output = String::new();
+        let mut stderr = String::new();
+        let mut errors = String::new();
+        let mut success = true;
+        let mut streamed = false;
+        let mut status = ExitStatus::Code(0);
+        let mut usage = ResourceUsage::default();
         
-        // Разбираем команду с учетом alias
-        let parts: Vec<&str> = final_cmd.split_whitespace().collect();
-        let actual_cmd = parts[0];
-        let mut all_args: Vec<String> = parts[1..].iter().map(|&s| s.to_string()).collect();
-        all_args.extend_from_slice(args);
+        for (index, item) in list.items.iter().enumerate() {
+            let should_run = match item.connector {
+                Connector::Always => true,
+                Connector::And => success,
+                Connector::Or => !success,
+            };
+            if !should_run {
+                continue;
+            }
+            
+            let result = if item.background {
+                self.launch_background(&item.pipeline, state)?
+            } else {
+                self.execute_pipeline(&item.pipeline, state)?
+            };
+            success = result.success;
+            status = result.status;
+            state.last_exit_code = status.code();
+            // После первой команды с живым выводом остальной вывод списка
+            // тоже печатается сразу, чтобы сохранить порядок
+            if result.streamed && !streamed && !output.is_empty() {
+                println!("{}", output.trim_end());
+            } else if streamed && !result.streamed && !result.output.is_empty() {
+                println!("{}", result.output);
+            }
+            streamed |= result.streamed;
+            output.push_str(&result.output);
+            stderr.push_str(&result.stderr);
+            usage.add(&result.usage);
+            if let Some(error) = result.error {
+                errors.push_str(&error);
+            }
+            
+            // Ctrl+C прерывает весь список, как в bash
+            if let Some(signal) = signals::take_interrupt() {
+                status = ExitStatus::Signal(signal);
+                state.last_exit_code = status.code();
+                success = false;
+                break;
+            }
+            if signals::is_interrupt(state.last_exit_code) {
+                break;
+            }
+            // exit внутри списка или функции
+            if !state.is_running {
+                break;
+            }
+            // set -e не срабатывает на левой части && и ||: ее неудача проверяется
+            let tested = matches!(
+                list.items.get(index + 1).map(|next| next.connector),
+                Some(Connector::And) | Some(Connector::Or)
+            );
+            if !success && state.options.errexit && !tested {
+                state.is_running = false;
+                break;
+            }
+        }
         
-        let output = if cfg!(target_os = "windows") {
-            Command::new("cmd")
-                .args(&["/C", &final_cmd])
-                .args(args)
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .spawn()
-        } else {
-            Command::new(actual_cmd)
-                .args(&all_args)
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .spawn()
-        };
+        let error = if errors.is_empty() { None } else { Some(errors) };
+        let result = CommandResult::new(success, output.trim_end().to_string(), error);
+        Ok(CommandResult { stderr, status, usage, streamed, ..result })
+    }
+    
+    fn execute_pipeline(&mut self, pipeline: &Pipeline, state: &mut TerminalState) -> Result<CommandResult, String> {
+        // time конвейер - отчет о времени и памяти после выполнения
+        if let Some(timed) = Self::strip_time(pipeline) {
+            let started = Instant::now();
+            let children_before = ResourceUsage::children();
+            let mut result = self.execute_pipeline(&timed, state)?;
+            Self::complete_usage(&mut result, started, &children_before);
+            eprintln!("\n{}", result.usage.report());
+            return Ok(result);
+        }
         
-        match output {
-            Ok(child) => {
-                let output = child.wait_with_output()
-                    .map_err(|e| format!("Ошибка выполнения команды: {}", e))?;
-                
-                let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let mut stages = Vec::with_capacity(pipeline.commands.len());
+        // Вывод встроенной команды в начале конвейера - ввод следующей стадии
+        let mut piped_input = None;
+        
+        for (index, command) in pipeline.commands.iter().enumerate() {
+            let (mut argv, globbed) = self.expand_argv(command, state)?;
+            if state.options.xtrace && !argv.is_empty() {
+                eprintln!("{}", builtins::format_trace(&argv));
+            }
+            
+            if pipeline.commands.len() == 1 {
+                // Одни присваивания меняют переменные сессии
+                if argv.is_empty() {
+                    for assignment in &command.assignments {
+                        let value = expand::expand_to_string(&assignment.value, state)?;
+                        if state.options.xtrace {
+                            eprintln!("{}", builtins::format_trace(&[format!("{}={}", assignment.name, value)]));
+                        }
+                        state.set_variable(&assignment.name, &value);
+                    }
+                    return Ok(CommandResult::success(String::new()));
+                }
+            }
+            
+            // Встроенные команды выполняются внутри процесса терминала, их вывод
+            // идет в перенаправления, а в конвейере - на ввод следующей стадии
+            if index == 0 {
+                if let Some(result) = self.run_in_process(&argv, state) {
+                    let result = self.redirect_builtin(command, result, state)?;
+                    if pipeline.commands.len() == 1 {
+                        return Ok(result);
+                    }
+                    if let Some(error) = &result.error {
+                        helpers::print_error(error);
+                    }
+                    piped_input = Some(if result.output.is_empty() { result.output } else { result.output + "\n" });
+                    continue;
+                }
+            } else if self.is_in_process(&argv) {
+                return Err(format!("{}: встроенная команда может стоять в конвейере только первой", argv[0]));
+            }
+            
+            if pipeline.commands.len() == 1 {
+                // Функции перекрывают встроенные и внешние команды
+                if let Some(body) = self.functions.get(&argv[0]).cloned() {
+                    return self.call_function(&argv, &body, state);
+                }
                 
-                if output.status.success() {
-                    Ok(CommandResult::success(stdout))
-                } else {
-                    Ok(CommandResult::error(if stderr.is_empty() { stdout } else { stderr }))
+                // git и внутренние команды получают аргументы после всех раскрытий;
+                // с перенаправлениями и NAME=value выполняется настоящий git
+                if command.redirections.is_empty() && command.assignments.is_empty() {
+                    if argv[0] == "git" && self.wraps_git(&argv[1..]) {
+                        return self.run_git(&argv[1..]);
+                    }
+                    if parser::INTERNAL_COMMANDS.contains(&argv[0].as_str()) {
+                        return self.execute_internal_command(&argv.join(" "), state);
+                    }
+                }
+            }
+            
+            if argv.first().is_some_and(|name| self.functions.contains_key(name)) {
+                return Err(format!("{}: функции нельзя использовать в конвейере", argv[0]));
+            }
+            
+            // Опечатка в имени команды
+            let search_path = state.get_variable("PATH").unwrap_or_default();
+            if !argv.is_empty() && !correction::command_exists(&argv[0], &search_path) {
+                // Встроенные команды, функции и aliases можно подставить только в
+                // одиночную команду без перенаправлений - она выполняется заново
+                let rerun = pipeline.commands.len() == 1
+                    && command.redirections.is_empty()
+                    && command.assignments.is_empty();
+                match self.correct_command(&argv[0], &search_path, rerun) {
+                    Ok(corrected) => argv[0] = corrected,
+                    Err(result) => return Ok(*result),
+                }
+                if !correction::command_exists(&argv[0], &search_path) {
+                    return self.run_line(&builtins::quote_argv(&argv), state);
+                }
+            }
+            
+            if globbed && state.options.glob_preview && Self::needs_preview(&argv) {
+                println!("{}", builtins::format_argv(&argv));
+                if !helpers::ask_confirm("Выполнить команду с этими аргументами?") {
+                    return Ok(CommandResult::error("Команда отменена".to_string()));
+                }
+            }
+            
+            let stage = self.prepare_stage(command, argv, state)?;
+            if let Some(result) = self.guard_stage(&stage) {
+                return Ok(result);
+            }
+            stages.push(stage);
+        }
+        
+        let mode = LaunchMode { background: false, job_control: self.jobs.job_control() };
+        let job = match piped_input {
+            Some(input) => pipeline::spawn_with_input(&stages, input, mode)?,
+            None => pipeline::spawn(&stages, mode)?,
+        };
+        Ok(Self::foreground_result(self.jobs.foreground(job)?))
+    }
+    
+    fn guard_stage(&self, stage: &Stage) -> Option<CommandResult> {
+        let argv: Vec<String> = std::iter::once(stage.program.clone()).chain(stage.args.iter().cloned()).collect();
+        self.guard_command(&Invocation::from_stage(stage), &builtins::quote_argv(&argv))
+    }
+    
+    /// Код для оболочки (!bash, !sh) проверяется по каждой простой команде
+    fn guard_code(&self, code: &str) -> Option<CommandResult> {
+        guard::code_commands(code).iter()
+            .find_map(|command| self.guard_command(&command.invocation()?, &builtins::quote_argv(&command.argv)))
+    }
+    
+    /// Проверка правилами защиты от опасных команд. None - команду можно
+    /// запускать; подтверждение спрашивается только в интерактивном режиме,
+    /// в скриптах такая команда не выполняется
+    fn guard_command(&self, invocation: &Invocation, command: &str) -> Option<CommandResult> {
+        let verdict = self.guard.check(invocation)?;
+        let decision = match verdict.action {
+            Action::Allow => return None,
+            Action::Confirm if self.jobs.job_control() => {
+                let question = format!("Опасная команда: {} ({}). Выполнить?", verdict.reason, verdict.rule);
+                if helpers::ask_confirm(&question) { Decision::Confirmed } else { Decision::Declined }
+            }
+            Action::Confirm => Decision::Declined,
+            Action::Block => Decision::Blocked,
+        };
+        self.guard.log(&verdict, command, decision);
+        
+        let error = match (decision, verdict.action) {
+            (Decision::Confirmed, _) => return None,
+            (_, Action::Block) => format!("{}: команда запрещена правилом {} - {}", command, verdict.rule, verdict.reason),
+            _ if self.jobs.job_control() => "Команда отменена".to_string(),
+            _ => format!("{}: требуется подтверждение (правило {} - {})", command, verdict.rule, verdict.reason),
+        };
+        Some(CommandResult { status: ExitStatus::Code(126), ..CommandResult::error(error) })
+    }
+    
+    /// Команда не найдена: в интерактивном режиме предлагает запустить
+    /// ближайшее имя одной клавишей, иначе - ошибка 127 с вариантами и
+    /// пакетом для установки
+    fn correct_command(&self, program: &str, search_path: &str, internal: bool) -> Result<String, Box<CommandResult>> {
+        let candidates = correction::closest(program, self.known_commands(search_path, internal));
+        if self.jobs.job_control() {
+            if let Some(best) = correction::best(program, &candidates) {
+                if correction::confirm(&format!("{}: команда не найдена. Запустить {}?", program, best)) {
+                    return Ok(best.to_string());
+                }
+            }
+        }
+        let error = correction::not_found_message(program, &candidates, search_path);
+        Err(Box::new(CommandResult { status: ExitStatus::Code(127), ..CommandResult::error(error) }))
+    }
+    
+    /// Имена, которые можно предложить вместо опечатки: $PATH, а для
+    /// internal - еще встроенные и внутренние команды, aliases и функции
+    fn known_commands(&self, search_path: &str, internal: bool) -> Vec<String> {
+        let mut names = correction::path_commands(search_path);
+        if internal {
+            names.extend(builtins::BUILTIN_COMMANDS.iter()
+                .chain(aliases::COMMANDS)
+                .chain(jobs::COMMANDS)
+                .chain(["help", "bash-help", "bash-quick"].iter())
+                .map(|name| name.to_string())
+                .chain(self.bash_aliases.keys().cloned())
+                .chain(self.functions.keys().cloned()));
+        }
+        names
+    }
+    
+    fn foreground_result(foreground: Foreground) -> CommandResult {
+        match foreground {
+            Foreground::Finished(result) => {
+                let error = if result.errors.is_empty() { None } else { Some(result.errors) };
+                let usage = result.usage;
+                CommandResult { usage, ..CommandResult::streamed(result.status, result.stdout, result.stderr, error) }
+            }
+            // Ctrl+Z: задание осталось в таблице, продолжить - fg или bg
+            Foreground::Stopped(notice) => CommandResult {
+                status: ExitStatus::Signal(libc::SIGTSTP),
+                ..CommandResult::new(false, notice, None)
+            },
+        }
+    }
+    
+    /// Конвейер без ключевого слова time в начале, если оно есть
+    fn strip_time(pipeline: &Pipeline) -> Option<Pipeline> {
+        let first = pipeline.commands.first()?;
+        let is_time = first.words.len() > 1
+            && !first.words[0].is_quoted()
+            && first.words[0].to_plain() == "time";
+        if !is_time {
+            return None;
+        }
+        let mut timed = pipeline.clone();
+        timed.commands[0].words.remove(0);
+        Some(timed)
+    }
+    
+    /// Выполняет команду внутри процесса терминала, если она встроенная.
+    /// Функции перекрывают встроенные команды, кроме alias, unalias, unset -f и команд заданий
+    fn run_in_process(&mut self, argv: &[String], state: &mut TerminalState) -> Option<CommandResult> {
+        if let Some(result) = self.run_definition_builtin(argv) {
+            return Some(result);
+        }
+        if let Some(result) = self.run_job_builtin(argv) {
+            return Some(result);
+        }
+        if self.functions.contains_key(&argv[0]) || !builtins::handles(argv) {
+            return None;
+        }
+        if argv[0] == "config" {
+            return Some(self.run_config_builtin(&argv[1..]));
+        }
+        let result = builtins::run(&argv[0], &argv[1..], state);
+        if argv[0] != "dirs" {
+            self.refresh_git();
+        }
+        Some(result)
+    }
+    
+    /// Выполнил бы run_in_process эту команду
+    fn is_in_process(&self, argv: &[String]) -> bool {
+        let Some(name) = argv.first() else { return false };
+        aliases::COMMANDS.contains(&name.as_str()) || jobs::COMMANDS.contains(&name.as_str())
+            || (!self.functions.contains_key(name) && builtins::handles(argv))
+    }
+    
+    /// Перенаправления встроенной команды: вывод и сообщение об ошибке
+    /// записываются в файлы вместо показа в терминале
+    fn redirect_builtin(&self, command: &SimpleCommand, result: CommandResult, state: &TerminalState) -> Result<CommandResult, String> {
+        if command.redirections.is_empty() {
+            return Ok(result);
+        }
+        let redirects = Self::stage_redirects(command, state)?;
+        Ok(match pipeline::redirect_text(&redirects, &result.output, result.error.as_deref()) {
+            Ok((output, error)) => CommandResult { output, error, ..result },
+            Err(error) => CommandResult::error(error),
+        })
+    }
+    
+    /// cmd & - запускает конвейер в фоне и добавляет его в таблицу заданий
+    fn launch_background(&mut self, pipeline: &Pipeline, state: &mut TerminalState) -> Result<CommandResult, String> {
+        let mut stages = Vec::with_capacity(pipeline.commands.len());
+        for command in &pipeline.commands {
+            let (argv, _) = self.expand_argv(command, state)?;
+            if let Some(name) = argv.first() {
+                if self.functions.contains_key(name) || builtins::is_builtin(name)
+                    || aliases::COMMANDS.contains(&name.as_str()) || jobs::COMMANDS.contains(&name.as_str())
+                {
+                    return Err(format!("{}: встроенные команды и функции нельзя запускать в фоне", name));
                 }
             }
-            Err(e) => {
-                // Если команда не найдена, пробуем выполнить через shell
-                self.execute_bash_command(&format!("{} {}", cmd, args.join(" ")))
+            let stage = self.prepare_stage(command, argv, state)?;
+            if let Some(result) = self.guard_stage(&stage) {
+                return Ok(result);
+            }
+            stages.push(stage);
+        }
+        
+        let mode = LaunchMode { background: true, job_control: self.jobs.job_control() };
+        let job = pipeline::spawn(&stages, mode)?;
+        // Номер задания печатается сразу, до вывода следующих команд списка
+        println!("{}", self.jobs.add_background(job));
+        Ok(CommandResult::success(String::new()))
+    }
+    
+    fn prepare_stage(&self, command: &SimpleCommand, mut argv: Vec<String>, state: &TerminalState) -> Result<Stage, String> {
+        if argv.is_empty() {
+            return Err("Пустая команда".to_string());
+        }
+        let program = argv.remove(0);
+        
+        // NAME=value перед командой действует только на этот процесс
+        let mut env = state.environment.clone();
+        for assignment in &command.assignments {
+            env.insert(assignment.name.clone(), expand::expand_to_string(&assignment.value, state)?);
+        }
+        
+        let redirects = Self::stage_redirects(command, state)?;
+        Ok(Stage { program, args: argv, env, redirects })
+    }
+    
+    /// Перенаправления команды с раскрытыми именами файлов
+    fn stage_redirects(command: &SimpleCommand, state: &TerminalState) -> Result<Vec<StageRedirect>, String> {
+        command.redirections.iter()
+            .map(|redirection| Ok(match redirection {
+                Redirection::Input { fd, target } => StageRedirect::Input {
+                    fd: *fd,
+                    path: expand::expand_to_string(target, state)?,
+                },
+                Redirection::Output { fd, target, append } => StageRedirect::Output {
+                    fd: *fd,
+                    path: expand::expand_to_string(target, state)?,
+                    append: *append,
+                },
+                Redirection::Duplicate { fd, target_fd } => StageRedirect::Duplicate {
+                    fd: *fd,
+                    target_fd: *target_fd,
+                },
+            }))
+            .collect()
+    }
+    
+    /// Фигурные скобки, переменные и ~, затем шаблоны имен файлов
+    /// (aliases уже подставлены до разбора). Второе значение - был ли
+    /// раскрыт хотя бы один шаблон
+    fn expand_argv(&self, command: &SimpleCommand, state: &TerminalState) -> Result<(Vec<String>, bool), String> {
+        let mut words: Vec<Word> = Vec::new();
+        for word in &command.words {
+            words.extend(glob::expand_braces(word)?);
+        }
+        
+        let mut argv = Vec::new();
+        let mut globbed = false;
+        for word in expand::expand_words(&words, state)? {
+            match glob::expand_glob(&word) {
+                Some(paths) => {
+                    globbed = true;
+                    argv.extend(paths);
+                }
+                None => argv.push(word.to_plain()),
             }
         }
+        Ok((argv, globbed))
     }
     
-    fn execute_bash_command(&self, command: &str) -> Result<CommandResult, String> {
+    fn needs_preview(argv: &[String]) -> bool {
+        argv.first()
+            .map(|program| Path::new(program).file_name().and_then(|n| n.to_str()).unwrap_or(program))
+            .is_some_and(|name| DESTRUCTIVE_COMMANDS.contains(&name))
+    }
+    
+    fn execute_bash_command(&self, command: &str, state: &TerminalState) -> Result<CommandResult, String> {
         let output = if cfg!(target_os = "windows") {
             Command::new("cmd")
-                .args(&["/C", command])
+                .args(["/C", command])
+                .env_clear()
+                .envs(&state.environment)
                 .stdout(Stdio::piped())
                 .stderr(Stdio::piped())
                 .spawn()
         } else {
             Command::new("bash")
-                .args(&["-c", command])
+                .args(["-c", command])
+                .env_clear()
+                .envs(&state.environment)
                 .stdout(Stdio::piped())
                 .stderr(Stdio::piped())
                 .spawn()
@@ -137,63 +712,19 @@ impl CommandExecutor {
             Ok(child) => {
                 let output = child.wait_with_output()
                     .map_err(|e| format!("Ошибка выполнения команды: {}", e))?;
-                
-                let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-                let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-                
-                if output.status.success() {
-                    Ok(CommandResult::success(stdout))
-                } else {
-                    Ok(CommandResult::error(if stderr.is_empty() { stdout } else { stderr }))
-                }
+                Ok(CommandResult::from_output(output))
             }
             Err(e) => Err(format!("Не удалось выполнить команду: {}", e))
         }
     }
     
-    fn execute_rust_code(&self, code: &str) -> Result<CommandResult, String> {
-        // Простая реализация Rust REPL
-        let output = format!("[RUST] Выполнение Rust кода: {}", code);
-        Ok(CommandResult::success(output))
-    }
-    
-    fn execute_python_code(&self, code: &str) -> Result<CommandResult, String> {
-        let output = Command::new("python")
-            .args(&["-c", code])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .map_err(|e| format!("Ошибка выполнения Python: {}", e))?;
-        
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        
-        if output.status.success() {
-            Ok(CommandResult::success(stdout))
-        } else {
-            Ok(CommandResult::error(if stderr.is_empty() { stdout } else { stderr }))
-        }
-    }
-    
-    fn execute_java_code(&self, code: &str) -> Result<CommandResult, String> {
-        // Упрощенная Java поддержка
-        let output = format!("[JAVA] Выполнение Java кода: {}", code);
-        Ok(CommandResult::success(output))
-    }
-    
     fn execute_editor_command(&self, filename: &str) -> Result<CommandResult, String> {
-        let mut editor = MicroEditor::new();
+        let mut editor = MicroEditor::with_settings(self.editor_settings.clone());
         
         match editor.open_file(filename) {
             Ok(()) => {
                 match editor.run() {
-                    Ok(result) => {
-                        if result.saved {
-                            Ok(CommandResult::success(format!("Файл {} сохранен", filename)))
-                        } else {
-                            Ok(CommandResult::success(format!("Редактирование {} завершено", filename)))
-                        }
-                    }
+                    Ok(()) => Ok(CommandResult::success(format!("Редактирование {} завершено", filename))),
                     Err(e) => Ok(CommandResult::error(format!("Ошибка редактора: {}", e)))
                 }
             }
@@ -201,63 +732,142 @@ impl CommandExecutor {
         }
     }
     
+    /// Выполняет ли git <argv> встроенная обертка: ее подкоманды и опечатки
+    /// в них, если обертка понимает все аргументы. Остальное (clone, stash,
+    /// aliases git, push --force, commit -am) - настоящий git
+    fn wraps_git(&self, argv: &[String]) -> bool {
+        let Some((subcommand, args)) = argv.split_first() else {
+            return false;
+        };
+        if WRAPPED_GIT_COMMANDS.contains(&subcommand.as_str()) {
+            return self.understands_git_args(subcommand, args);
+        }
+        !GIT_COMMANDS.iter().any(|(name, _)| name == subcommand)
+            && !self.git_corrections(subcommand, args).is_empty()
+    }
+    
+    /// Подкоманды обертки, близкие к опечатке и понимающие аргументы
+    fn git_corrections(&self, subcommand: &str, args: &[String]) -> Vec<String> {
+        correction::closest(subcommand, WRAPPED_GIT_COMMANDS).into_iter()
+            .filter(|candidate| self.understands_git_args(candidate, args))
+            .collect()
+    }
+    
+    /// Понимает ли обертка все аргументы подкоманды: любой флаг, которого
+    /// она не знает, изменил бы смысл команды
+    fn understands_git_args(&self, subcommand: &str, args: &[String]) -> bool {
+        let is_path = |arg: &String| !arg.starts_with('-') && !arg.starts_with(':');
+        match subcommand {
+            "status" | "push" | "pull" | "branch" => args.is_empty(),
+            "add" => !args.is_empty() && args.iter().all(is_path),
+            "commit" => args.len() == 2 && args[0] == "-m",
+            "log" => args.iter().any(|arg| arg == "--oneline")
+                && args.iter().all(|arg| arg == "--oneline" || Self::log_limit(arg).is_some()),
+            "diff" => args.len() <= 1 && args.iter().all(is_path),
+            // checkout <файл> восстанавливает файл - обертка переключает только ветки
+            "checkout" => match args {
+                [branch] if is_path(branch) => self.git.as_ref()
+                    .and_then(|git| git.get_branches().ok())
+                    .is_some_and(|branches| branches.contains(branch)),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+    
+    /// -N в git log
+    fn log_limit(arg: &str) -> Option<usize> {
+        arg.strip_prefix('-').filter(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))?.parse().ok()
+    }
+    
+    /// git <argv> через встроенную обертку, после проверки правилами защиты
+    fn run_git(&self, argv: &[String]) -> Result<CommandResult, String> {
+        let Some(subcommand) = argv.first() else {
+            return Err("Не указана git команда".to_string());
+        };
+        let line = format!("git {}", builtins::quote_argv(argv));
+        if let Some(result) = self.guard_command(&Invocation::new("git", argv), &line) {
+            return Ok(result);
+        }
+        self.execute_git_command(subcommand, &argv[1..])
+    }
+    
     fn execute_git_command(&self, subcommand: &str, args: &[String]) -> Result<CommandResult, String> {
+        let git = match &self.git {
+            Some(git) => git,
+            None => return Ok(CommandResult::error("Текущая директория не является git репозиторием".to_string())),
+        };
+        
         match subcommand {
             "status" => {
-                let output = self.git.status()?;
+                let output = git.get_visual_status()?;
                 Ok(CommandResult::success(output))
             }
             "add" => {
-                let output = if args.is_empty() {
-                    self.git.add(&[".".to_string()])?
-                } else {
-                    self.git.add(args)?
-                };
-                Ok(CommandResult::success(output))
+                let files: Vec<&Path> = args.iter().map(Path::new).collect();
+                git.add_files(&files)?;
+                Ok(CommandResult::success(String::new()))
             }
             "commit" => {
-                let message = if args.len() >= 2 && args[0] == "-m" {
-                    &args[1]
-                } else {
-                    "update"
-                };
-                let output = self.git.commit(message)?;
-                Ok(CommandResult::success(output))
+                let message = &args[1];
+                git.commit(message)?;
+                Ok(CommandResult::success(format!("Коммит создан: {}", message)))
             }
             "push" => {
-                let output = self.git.push()?;
-                Ok(CommandResult::success(output))
+                git.push()?;
+                Ok(CommandResult::success("Изменения отправлены".to_string()))
             }
             "pull" => {
-                let output = self.git.pull()?;
-                Ok(CommandResult::success(output))
+                git.pull()?;
+                Ok(CommandResult::success("Изменения получены".to_string()))
             }
             "log" => {
-                let limit = args.iter()
-                    .find(|a| a.starts_with('-') && a[1..].chars().all(|c| c.is_digit(10)))
-                    .and_then(|a| a[1..].parse().ok());
-                let output = self.git.log(limit)?;
+                let limit = args.iter().find_map(|arg| Self::log_limit(arg));
+                let output = git.get_log(limit)?
+                    .iter()
+                    .map(|commit| format!("{} {}", &commit.hash[..commit.hash.len().min(7)], commit.summary))
+                    .collect::<Vec<_>>()
+                    .join("\n");
                 Ok(CommandResult::success(output))
             }
             "diff" => {
-                let file = args.first().map(|s| s.as_str());
-                let output = self.git.diff(file)?;
+                let file = args.first().map(|s| s.as_str()).unwrap_or(".");
+                let output = git.get_file_diff(Path::new(file))?;
                 Ok(CommandResult::success(output))
             }
             "branch" => {
-                let output = self.git.branch()?;
+                let current = git.get_current_branch().unwrap_or_default();
+                let output = git.get_branches()?
+                    .iter()
+                    .map(|branch| {
+                        let marker = if *branch == current { "*" } else { " " };
+                        format!("{} {}", marker, branch)
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
                 Ok(CommandResult::success(output))
             }
             "checkout" => {
                 if let Some(branch) = args.first() {
-                    let output = self.git.checkout(branch)?;
-                    Ok(CommandResult::success(output))
+                    git.checkout(branch)?;
+                    Ok(CommandResult::success(format!("Переключено на ветку {}", branch)))
                 } else {
                     Ok(CommandResult::error("Не указана ветка".to_string()))
                 }
             }
             _ => {
-                Ok(CommandResult::error(format!("Неизвестная git команда: {}", subcommand)))
+                // git stauts -> status; предлагаются только подкоманды обертки
+                let candidates = self.git_corrections(subcommand, args);
+                if let Some(best) = correction::best(subcommand, &candidates) {
+                    if self.jobs.job_control() && correction::confirm(&format!("Неизвестная git команда: {}. Запустить git {}?", subcommand, best)) {
+                        return self.execute_git_command(best, args);
+                    }
+                }
+                let mut error = format!("Неизвестная git команда: {}", subcommand);
+                if !candidates.is_empty() {
+                    error.push_str(&format!("\nВозможно, вы имели в виду: {}", candidates.join(", ")));
+                }
+                Ok(CommandResult::error(error))
             }
         }
     }
@@ -268,7 +878,7 @@ impl CommandExecutor {
         Ok(CommandResult::success(output))
     }
     
-    pub fn execute_internal_command(&self, cmd: &str) -> Result<CommandResult, String> {
+    pub fn execute_internal_command(&self, cmd: &str, state: &TerminalState) -> Result<CommandResult, String> {
         match cmd {
             "help" => {
                 let help = r#"
@@ -277,11 +887,16 @@ Smart Term - основные команды:
   Системные команды:
     <command> [args]    - выполнить системную команду
     ls, cd, pwd, etc.   - стандартные Unix команды
+    vim, htop, less, ssh - интерактивные программы работают на псевдотерминале
+    cmd1 | cmd2         - конвейер
+    a && b, a || b, a; b - списки команд
+    cmd > file, >>, <, 2>&1 - перенаправления ввода/вывода
+    cmd &               - запустить в фоне
+    time cmd | cmd2     - время выполнения, процессорное время и пиковая память
+
+{runners}
 
   Специальные команды:
-    !rust <code>        - выполнить Rust код
-    !python <code>      - выполнить Python код
-    !java <code>        - выполнить Java код
     !edit <file>        - редактировать файл
     !bash <command>     - выполнить Bash команду
 
@@ -293,21 +908,48 @@ Smart Term - основные команды:
     gp                  - git push
 
   Встроенные команды:
+    cd [dir|-|~]        - сменить директорию (учитывает CDPATH)
+    pushd/popd/dirs     - стек директорий
+    export/unset        - переменные окружения ($VAR, ${VAR:-def}, $?)
+    env, set            - показать окружение / все переменные
+    alias [name=value]  - показать или задать alias (alias sudo='sudo ' раскрывает и следующее слово)
+    unalias [-a] name   - удалить alias
+    name() { ...; }     - определить функцию ($1, $@, $#); unset -f name - удалить
+    ~/.smarttermrc      - команды, выполняемые при запуске (aliases, функции, export)
+    jobs [-l]           - фоновые и остановленные задания
+    fg/bg [%n]          - продолжить задание на переднем плане / в фоне
+    kill [-SIG] %n|pid  - послать сигнал заданию или процессу (kill -l - список)
+    wait [%n]           - дождаться фоновых заданий
+    preview <cmd>       - показать аргументы после раскрытия *, ?, [...], **, {a,b}
+    set -o globpreview  - подтверждать rm/mv/cp/chmod после раскрытия шаблонов
+    set -e / set -x     - остановка на первой ошибке / печать команд перед выполнением
+    suggest "<запрос>"  - подобрать команду по описанию (suggest "как найти файл")
+    Опечатки в именах команд и git подкоманд исправляются: Enter - запустить исправленную
+    Опасные команды (rm -rf /, dd of=/dev/sda, mkfs, git push --force) требуют подтверждения
+    или запрещены, под root - строже; правила - секция [guard] конфигурации
     help                - эта справка
     bash-help           - полная справка по Bash
     bash-quick          - быстрые команды Bash
-    history             - история команд
+    history [N|-c|-d N] - история команд (~/.local/share/smart-term/history)
+    config [path|reload] - файл конфигурации / перечитать без перезапуска
     clear               - очистить экран
     elevate             - перезапуск с правами root
     privileges          - показать уровень прав
     ui                  - псевдографический режим
-    exit/quit           - выход
+    exit [N]            - выход с кодом N (quit - то же)
 
   Горячие клавиши:
     Ctrl+U              - переключение в UI режим
-    Стрелки ↑↓          - навигация по истории
+    Стрелки ↑↓, Ctrl+P/N - навигация по истории
+    Ctrl+R              - нечеткий поиск по истории (Ctrl+R/↑↓ - следующий результат)
+    Ctrl+A/E, Alt+B/F   - начало/конец строки, слово назад/вперед
+    Ctrl+W/K/Y          - удалить слово / до конца строки, вставить удаленное
+    Ctrl+C, Ctrl+D      - отменить строку / выход на пустой строке
+    Ctrl+Z              - остановить выполняющуюся команду (продолжить - fg/bg)
+    Tab                 - автодополнение команд, путей, git веток
                 "#;
-                Ok(CommandResult::success(help.to_string()))
+                // Исполнители зависят от конфигурации и установленных интерпретаторов
+                Ok(CommandResult::success(help.replace("{runners}", &self.runners.help(state))))
             }
             "bash-help" => {
                 let help = BashHelp::get_full_help();
@@ -329,11 +971,141 @@ Smart Term - основные команды:
         }
     }
     
-    pub fn add_alias(&mut self, alias: String, command: String) {
-        self.bash_aliases.insert(alias, command);
+    /// Aliases из конфигурации. Удаленные из файла aliases убираются, если
+    /// их не переопределили командой alias
+    pub fn set_config_aliases(&mut self, aliases: HashMap<String, String>) {
+        for (alias, command) in self.config_aliases.drain() {
+            if self.bash_aliases.get(&alias) == Some(&command) {
+                self.bash_aliases.remove(&alias);
+            }
+        }
+        self.bash_aliases.extend(aliases.clone());
+        self.config_aliases = aliases;
     }
     
     pub fn get_aliases(&self) -> &HashMap<String, String> {
         &self.bash_aliases
     }
+    
+    pub fn get_functions(&self) -> &HashMap<String, String> {
+        &self.functions
+    }
+    
+    /// Разборщик с префиксами исполнителей из конфигурации
+    pub fn parser(&self) -> &CommandParser {
+        &self.parser
+    }
+    
+    /// Применяет части конфигурации, которые относятся к исполнителю
+    pub fn apply_config(&mut self, config: &Config) {
+        helpers::set_color_scheme(config.colors.clone());
+        self.set_config_aliases(config.aliases.clone());
+        self.set_editor_settings(config.editor.clone());
+        self.set_guard(config.guard.clone());
+        self.set_runners(config.runners.clone());
+        self.config_path = config.path.clone();
+    }
+    
+    /// Настройки для !edit / !micro
+    pub fn set_editor_settings(&mut self, settings: EditorSettings) {
+        self.editor_settings = settings;
+    }
+    
+    pub fn set_guard(&mut self, config: GuardConfig) {
+        self.guard = Guard::new(config);
+    }
+    
+    /// Исполнители кода из [runners.<имя>]; префиксы !<имя> становятся известны разбору
+    pub fn set_runners(&mut self, configs: Vec<RunnerConfig>) {
+        self.runners.configure(configs);
+        self.parser = CommandParser::with_runners(self.runners.names());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(line: &str, executor: &mut CommandExecutor, state: &mut TerminalState) -> Result<CommandResult, String> {
+        executor.run_line(line, state)
+    }
+
+    fn wraps(executor: &CommandExecutor, line: &str) -> bool {
+        let argv: Vec<String> = line.split_whitespace().map(str::to_string).collect();
+        executor.wraps_git(&argv)
+    }
+
+    #[test]
+    fn test_builtin_redirections_and_pipes() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = |name: &str| dir.path().join(name).to_string_lossy().to_string();
+        let read = |name: &str| std::fs::read_to_string(dir.path().join(name)).unwrap();
+        let (mut executor, mut state) = (CommandExecutor::new(), TerminalState::default());
+
+        run("export ST_TEST=1", &mut executor, &mut state).unwrap();
+        let result = run(&format!("env > {}", file("env")), &mut executor, &mut state).unwrap();
+        assert_eq!(result.output, "");
+        run(&format!("export -p >> {}", file("env")), &mut executor, &mut state).unwrap();
+        run(&format!("export | grep ST_TEST > {}", file("grep")), &mut executor, &mut state).unwrap();
+        let result = run(&format!("cd /nonexistent 2> {}", file("err")), &mut executor, &mut state).unwrap();
+        assert!(!result.success);
+        assert!(read("env").contains("ST_TEST=1\n"));
+        assert!(read("env").contains("declare -x ST_TEST='1'\n"));
+        assert_eq!(read("grep"), "declare -x ST_TEST='1'\n");
+        assert!(read("err").starts_with("cd: "));
+
+        // history разбирается как обычная команда: конвейеры, перенаправления, списки
+        state.history.add("make build".to_string());
+        state.history.add("cargo test".to_string());
+        run(&format!("history | grep cargo > {}", file("grep")), &mut executor, &mut state).unwrap();
+        assert!(read("grep").ends_with("cargo test\n"));
+        assert_eq!(read("grep").lines().count(), 1);
+        run(&format!("history 1 > {}; echo after >> {}", file("last"), file("last")), &mut executor, &mut state).unwrap();
+        let last = read("last");
+        assert!(last.starts_with("    2  ") && last.ends_with("cargo test\nafter\n"));
+        assert!(!run("history -d 9", &mut executor, &mut state).unwrap().success);
+
+        // Встроенная команда не первой стадией не выполняется
+        assert!(run("echo x | export A=1", &mut executor, &mut state).is_err());
+        assert!(!state.environment.contains_key("A"));
+    }
+
+    #[test]
+    fn test_config_builtin() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("config.toml");
+        std::fs::write(&config_path, "[aliases]\nst-greet = \"echo hi\"\n").unwrap();
+        let (mut executor, mut state) = (CommandExecutor::new(), TerminalState::default());
+        executor.apply_config(&Config { path: Some(config_path.clone()), ..Config::default() });
+
+        assert!(run("config reload", &mut executor, &mut state).unwrap().success);
+        assert!(executor.take_reloaded_config().is_some());
+        assert_eq!(run("config path", &mut executor, &mut state).unwrap().output, config_path.display().to_string());
+        assert_eq!(run("st-greet", &mut executor, &mut state).unwrap().output, "hi");
+
+        // Ошибка в файле: прежняя конфигурация остается
+        std::fs::write(&config_path, "[unknown]\n").unwrap();
+        let result = run("config reload", &mut executor, &mut state).unwrap();
+        assert!(!result.success && result.error.unwrap().starts_with("config: "));
+        assert!(executor.take_reloaded_config().is_none());
+        assert_eq!(run("st-greet", &mut executor, &mut state).unwrap().output, "hi");
+        assert_eq!(run("config reload now", &mut executor, &mut state).unwrap().status, ExitStatus::Code(2));
+    }
+
+    #[test]
+    fn test_git_arguments_not_understood_go_to_git() {
+        let executor = CommandExecutor { git: None, ..CommandExecutor::new() };
+        for line in ["status", "add src/main.rs", "commit -m msg", "push", "log --oneline -10", "diff src", "stauts"] {
+            assert!(wraps(&executor, line), "{}", line);
+        }
+        for line in [
+            "push --force origin main", "commit -am msg", "commit", "checkout -b topic", "branch -d x",
+            "status --short", "add -p", "add", "log", "log --oneline --all", "diff --staged", "pull --rebase",
+            "stash", "comit -am msg",
+        ] {
+            assert!(!wraps(&executor, line), "{}", line);
+        }
+        // Без репозитория обертка не знает веток: checkout <имя> может быть файлом
+        assert!(!wraps(&executor, "checkout main"));
+    }
 }
@@ -0,0 +1,354 @@
+//! Раскрытие слов после разбора: переменные, ${...}, $? и ~
+
+use crate::terminal::lexer::{QuoteKind, Word};
+use crate::terminal::TerminalState;
+
+/// Фрагмент текста после подстановки переменных
+enum Segment {
+    Literal(String),
+    Expanded(String),
+}
+
+/// Раскрывает слова команды. Результат подстановки вне кавычек
+/// разбивается по пробелам на отдельные слова, как в bash
+pub fn expand_words(words: &[Word], state: &TerminalState) -> Result<Vec<Word>, String> {
+    let mut result = Vec::new();
+    for word in words {
+        result.extend(expand_word(word, state)?);
+    }
+    Ok(result)
+}
+
+/// Раскрывает слово в ноль или несколько полей
+pub fn expand_word(word: &Word, state: &TerminalState) -> Result<Vec<Word>, String> {
+    let mut fields = Vec::new();
+    let mut current = Word::default();
+    // Слово в кавычках сохраняется, даже если оно пустое ("")
+    let mut has_content = false;
+
+    fn finish(fields: &mut Vec<Word>, current: &mut Word, has_content: &mut bool) {
+        if *has_content {
+            fields.push(std::mem::take(current));
+            *has_content = false;
+        }
+    }
+
+    for (index, part) in word.parts.iter().enumerate() {
+        match part.quote {
+            QuoteKind::Single => {
+                current.push_str(&part.text, QuoteKind::Single);
+                has_content = true;
+            }
+            QuoteKind::Double => {
+                let text = join_segments(scan(&part.text, state)?);
+                current.push_str(&text, QuoteKind::Double);
+                has_content = true;
+            }
+            QuoteKind::None => {
+                let mut text = part.text.as_str();
+                if index == 0 {
+                    if let Some((home, rest)) = split_tilde(text, state) {
+                        current.push_str(&home, QuoteKind::Single);
+                        has_content = true;
+                        text = rest;
+                    }
+                }
+
+                for segment in scan(text, state)? {
+                    match segment {
+                        Segment::Literal(literal) => {
+                            current.push_str(&literal, QuoteKind::None);
+                            has_content = true;
+                        }
+                        Segment::Expanded(value) => {
+                            let starts_with_space = value.starts_with(char::is_whitespace);
+                            let ends_with_space = value.ends_with(char::is_whitespace);
+                            let pieces: Vec<&str> = value.split_whitespace().collect();
+
+                            if pieces.is_empty() {
+                                if !value.is_empty() {
+                                    finish(&mut fields, &mut current, &mut has_content);
+                                }
+                                continue;
+                            }
+                            if starts_with_space {
+                                finish(&mut fields, &mut current, &mut has_content);
+                            }
+                            for (i, piece) in pieces.iter().enumerate() {
+                                if i > 0 {
+                                    finish(&mut fields, &mut current, &mut has_content);
+                                }
+                                current.push_str(piece, QuoteKind::None);
+                                has_content = true;
+                            }
+                            if ends_with_space {
+                                finish(&mut fields, &mut current, &mut has_content);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    finish(&mut fields, &mut current, &mut has_content);
+    Ok(fields)
+}
+
+/// Раскрывает слово в одну строку без разбиения на поля
+/// (значения присваиваний, имена файлов перенаправлений)
+pub fn expand_to_string(word: &Word, state: &TerminalState) -> Result<String, String> {
+    let mut result = String::new();
+    for (index, part) in word.parts.iter().enumerate() {
+        match part.quote {
+            QuoteKind::Single => result.push_str(&part.text),
+            QuoteKind::Double => result.push_str(&join_segments(scan(&part.text, state)?)),
+            QuoteKind::None => {
+                let mut text = part.text.as_str();
+                if index == 0 {
+                    if let Some((home, rest)) = split_tilde(text, state) {
+                        result.push_str(&home);
+                        text = rest;
+                    }
+                }
+                result.push_str(&join_segments(scan(text, state)?));
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Раскрывает переменные в произвольной строке (как внутри двойных кавычек)
+pub fn expand_string(text: &str, state: &TerminalState) -> Result<String, String> {
+    Ok(join_segments(scan(text, state)?))
+}
+
+fn join_segments(segments: Vec<Segment>) -> String {
+    segments.into_iter()
+        .map(|segment| match segment {
+            Segment::Literal(text) | Segment::Expanded(text) => text,
+        })
+        .collect()
+}
+
+/// Отделяет ~ или ~/ в начале слова и возвращает домашнюю директорию
+fn split_tilde<'a>(text: &'a str, state: &TerminalState) -> Option<(String, &'a str)> {
+    let rest = text.strip_prefix('~')?;
+    if !rest.is_empty() && !rest.starts_with('/') {
+        return None;
+    }
+    let home = state.get_variable("HOME")?;
+    Some((home, rest))
+}
+
+/// Значение специальной переменной ($?, $$, $0, $1-$9, $#, $@, $*)
+fn special_variable(name: char, state: &TerminalState) -> String {
+    match name {
+        '?' => state.last_exit_code.to_string(),
+        '$' => std::process::id().to_string(),
+        '0' => state.script_name.clone(),
+        '#' => state.positional_args.len().to_string(),
+        '@' | '*' => state.positional_args.join(" "),
+        c => c.to_digit(10)
+            .and_then(|n| state.positional_args.get(n as usize - 1))
+            .cloned()
+            .unwrap_or_default(),
+    }
+}
+
+/// Находит подстановки $NAME, ${...} и специальные переменные
+fn scan(text: &str, state: &TerminalState) -> Result<Vec<Segment>, String> {
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if ch != '$' {
+            literal.push(ch);
+            continue;
+        }
+
+        let value = match chars.peek().copied() {
+            Some('{') => {
+                chars.next();
+                let mut inner = String::new();
+                let mut depth = 1;
+                loop {
+                    match chars.next() {
+                        Some('{') => {
+                            depth += 1;
+                            inner.push('{');
+                        }
+                        Some('}') => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                            inner.push('}');
+                        }
+                        Some(c) => inner.push(c),
+                        None => return Err("Незакрытая подстановка ${".to_string()),
+                    }
+                }
+                expand_braced(&inner, state)?
+            }
+            Some(c) if matches!(c, '?' | '$' | '#' | '@' | '*') || c.is_ascii_digit() => {
+                chars.next();
+                special_variable(c, state)
+            }
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+                let mut name = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    name.push(c);
+                    chars.next();
+                }
+                state.get_variable(&name).unwrap_or_default()
+            }
+            _ => {
+                literal.push('$');
+                continue;
+            }
+        };
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(std::mem::take(&mut literal)));
+        }
+        segments.push(Segment::Expanded(value));
+    }
+
+    if !literal.is_empty() {
+        segments.push(Segment::Literal(literal));
+    }
+    Ok(segments)
+}
+
+/// Раскрывает содержимое ${...}: ${VAR}, ${#VAR}, ${VAR:-w}, ${VAR-w},
+/// ${VAR:+w}, ${VAR+w}, ${VAR:?msg}
+fn expand_braced(inner: &str, state: &TerminalState) -> Result<String, String> {
+    if let Some(name) = inner.strip_prefix('#') {
+        if !name.is_empty() {
+            let value = lookup(name, state).unwrap_or_default();
+            return Ok(value.chars().count().to_string());
+        }
+    }
+
+    let name_len = match inner.chars().next() {
+        Some('?' | '$' | '#' | '@' | '*') => 1,
+        _ => inner
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(inner.len()),
+    };
+    let (name, operator) = inner.split_at(name_len);
+    if name.is_empty() {
+        return Err(format!("${{{}}}: неверная подстановка", inner));
+    }
+
+    let value = lookup(name, state);
+    let is_set = value.is_some();
+    let is_non_empty = value.as_deref().is_some_and(|v| !v.is_empty());
+
+    let (check_empty, op, word) = if let Some(rest) = operator.strip_prefix(':') {
+        (true, rest.chars().next(), rest.get(1..).unwrap_or(""))
+    } else {
+        (false, operator.chars().next(), operator.get(1..).unwrap_or(""))
+    };
+    let present = if check_empty { is_non_empty } else { is_set };
+
+    match op {
+        None => Ok(value.unwrap_or_default()),
+        Some('-') => if present { Ok(value.unwrap_or_default()) } else { expand_string(word, state) },
+        Some('+') => if present { expand_string(word, state) } else { Ok(String::new()) },
+        Some('?') => {
+            if present {
+                Ok(value.unwrap_or_default())
+            } else if word.is_empty() {
+                Err(format!("{}: параметр не задан", name))
+            } else {
+                Err(format!("{}: {}", name, expand_string(word, state)?))
+            }
+        }
+        Some('=') => Err(format!("${{{}}}: присваивание в подстановке не поддерживается", inner)),
+        Some(_) => Err(format!("${{{}}}: неверная подстановка", inner)),
+    }
+}
+
+fn lookup(name: &str, state: &TerminalState) -> Option<String> {
+    match name {
+        "?" | "$" | "0" | "#" | "@" | "*" => name.chars().next().map(|c| special_variable(c, state)),
+        // ${10} и дальше - тоже позиционные параметры
+        _ if name.chars().all(|c| c.is_ascii_digit()) => name.parse::<usize>().ok()
+            .and_then(|n| state.positional_args.get(n.checked_sub(1)?))
+            .cloned(),
+        _ => state.get_variable(name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::lexer::tokenize;
+    use crate::terminal::lexer::Token;
+
+    fn expand(input: &str, state: &TerminalState) -> Vec<String> {
+        let words: Vec<Word> = tokenize(input).unwrap()
+            .into_iter()
+            .filter_map(|t| match t {
+                Token::Word(w) => Some(w),
+                _ => None,
+            })
+            .collect();
+        expand_words(&words, state).unwrap().iter().map(Word::to_plain).collect()
+    }
+
+    fn test_state() -> TerminalState {
+        let mut state = TerminalState::default();
+        state.set_variable("HOME", "/home/user");
+        state.set_variable("NAME", "world");
+        state.set_variable("LIST", "a b  c");
+        state.unset_variable("EMPTY");
+        state.last_exit_code = 3;
+        state
+    }
+
+    #[test]
+    fn test_simple_variables() {
+        let state = test_state();
+        assert_eq!(expand("echo $NAME ${NAME}! $?", &state), vec!["echo", "world", "world!", "3"]);
+    }
+
+    #[test]
+    fn test_quoting() {
+        let state = test_state();
+        assert_eq!(expand("echo '$NAME' \"$NAME\" \\$NAME", &state), vec!["echo", "$NAME", "world", "$NAME"]);
+    }
+
+    #[test]
+    fn test_field_splitting() {
+        let state = test_state();
+        assert_eq!(expand("x $LIST \"$LIST\"", &state), vec!["x", "a", "b", "c", "a b  c"]);
+        assert_eq!(expand("x $EMPTY \"$EMPTY\"", &state), vec!["x", ""]);
+    }
+
+    #[test]
+    fn test_default_values() {
+        let state = test_state();
+        assert_eq!(expand("${EMPTY:-fallback} ${NAME:+set} ${#NAME}", &state), vec!["fallback", "set", "5"]);
+    }
+
+    #[test]
+    fn test_positional_args() {
+        let mut state = test_state();
+        state.positional_args = vec!["one".to_string(), "two words".to_string()];
+        assert_eq!(expand("$1 \"$2\" $# ${3:-none}", &state), vec!["one", "two words", "2", "none"]);
+        assert_eq!(expand("x $@", &state), vec!["x", "one", "two", "words"]);
+    }
+
+    #[test]
+    fn test_tilde() {
+        let state = test_state();
+        assert_eq!(expand("~ ~/src a~b '~'", &state), vec!["/home/user", "/home/user/src", "a~b", "~"]);
+    }
+}
@@ -0,0 +1,491 @@
+//! Раскрытие фигурных скобок ({a,b}, {1..5}) и шаблонов имен файлов (*, ?, [...], **).
+//! Символы в кавычках не считаются специальными - те же правила, что и в
+//! `utils::helpers::split_args`
+
+use std::path::Path;
+use crate::terminal::lexer::{QuoteKind, Word};
+
+/// Символ слова вместе с признаком кавычек
+type QuotedChar = (char, QuoteKind);
+
+fn word_chars(word: &Word) -> Vec<QuotedChar> {
+    word.parts.iter()
+        .flat_map(|part| part.text.chars().map(move |c| (c, part.quote)))
+        .collect()
+}
+
+fn word_from_chars(chars: &[QuotedChar]) -> Word {
+    let mut word = Word::default();
+    for &(c, quote) in chars {
+        word.push_str(&c.to_string(), quote);
+    }
+    word
+}
+
+fn is_active(chars: &[QuotedChar], index: usize, expected: char) -> bool {
+    chars.get(index).is_some_and(|&(c, quote)| c == expected && quote == QuoteKind::None)
+}
+
+/// Предел числа слов, получаемых из фигурных скобок в одном слове:
+/// {1..9999999999} не должен занимать всю память
+const MAX_BRACE_WORDS: usize = 100_000;
+
+fn too_many_words() -> String {
+    format!("слишком много слов в фигурных скобках (больше {})", MAX_BRACE_WORDS)
+}
+
+/// Раскрывает фигурные скобки в слове. Без скобок возвращает само слово
+pub fn expand_braces(word: &Word) -> Result<Vec<Word>, String> {
+    let chars = word_chars(word);
+    Ok(match expand_brace_chars(&chars)? {
+        Some(variants) => variants.iter().map(|v| word_from_chars(v)).collect(),
+        None => vec![word.clone()],
+    })
+}
+
+fn expand_brace_chars(chars: &[QuotedChar]) -> Result<Option<Vec<Vec<QuotedChar>>>, String> {
+    for open in 0..chars.len() {
+        // ${VAR} - подстановка переменной, а не фигурные скобки
+        if !is_active(chars, open, '{') || (open > 0 && is_active(chars, open - 1, '$')) {
+            continue;
+        }
+
+        let mut depth = 0;
+        let mut commas = Vec::new();
+        let mut close = None;
+        for index in open + 1..chars.len() {
+            if is_active(chars, index, '{') {
+                depth += 1;
+            } else if is_active(chars, index, '}') {
+                if depth == 0 {
+                    close = Some(index);
+                    break;
+                }
+                depth -= 1;
+            } else if depth == 0 && is_active(chars, index, ',') {
+                commas.push(index);
+            }
+        }
+        let close = match close {
+            Some(close) => close,
+            None => continue,
+        };
+
+        let alternatives: Vec<Vec<QuotedChar>> = if !commas.is_empty() {
+            let mut bounds = vec![open];
+            bounds.extend(&commas);
+            bounds.push(close);
+            bounds.windows(2).map(|w| chars[w[0] + 1..w[1]].to_vec()).collect()
+        } else {
+            let inner: String = chars[open + 1..close].iter().map(|&(c, _)| c).collect();
+            match sequence(&inner)? {
+                Some(items) => items.iter()
+                    .map(|item| item.chars().map(|c| (c, QuoteKind::None)).collect())
+                    .collect(),
+                None => continue,
+            }
+        };
+
+        let prefix = &chars[..open];
+        let suffix = &chars[close + 1..];
+        let mut result = Vec::new();
+        for alternative in alternatives {
+            let mut combined = prefix.to_vec();
+            combined.extend(alternative);
+            combined.extend_from_slice(suffix);
+            match expand_brace_chars(&combined)? {
+                Some(expanded) => result.extend(expanded),
+                None => result.push(combined),
+            }
+            if result.len() > MAX_BRACE_WORDS {
+                return Err(too_many_words());
+            }
+        }
+        return Ok(Some(result));
+    }
+    Ok(None)
+}
+
+/// Последовательность {1..5}, {01..10..2}, {a..e}
+fn sequence(inner: &str) -> Result<Option<Vec<String>>, String> {
+    let parts: Vec<&str> = inner.split("..").collect();
+    let (start, end, step) = match parts.as_slice() {
+        [start, end] => (*start, *end, 1i64),
+        [start, end, step] => match step.parse::<i64>() {
+            Ok(step) => (*start, *end, step.saturating_abs().max(1)),
+            Err(_) => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+
+    if let (Ok(from), Ok(to)) = (start.parse::<i64>(), end.parse::<i64>()) {
+        // Число элементов считаем до того, как их создавать
+        let count = (from as i128 - to as i128).unsigned_abs() / step as u128 + 1;
+        if count > MAX_BRACE_WORDS as u128 {
+            return Err(too_many_words());
+        }
+        let padded = |s: &str| s.trim_start_matches('-').len() > 1 && s.trim_start_matches('-').starts_with('0');
+        let width = if padded(start) || padded(end) { start.len().max(end.len()) } else { 0 };
+        let values: Vec<i64> = if from <= to {
+            (from..=to).step_by(step as usize).collect()
+        } else {
+            (to..=from).rev().step_by(step as usize).collect()
+        };
+        return Ok(Some(values.iter().map(|v| format!("{:0width$}", v, width = width)).collect()));
+    }
+
+    let mut start_chars = start.chars();
+    let mut end_chars = end.chars();
+    match (start_chars.next(), start_chars.next(), end_chars.next(), end_chars.next()) {
+        (Some(from), None, Some(to), None) if from.is_ascii_alphabetic() && to.is_ascii_alphabetic() => {
+            let (from, to) = (from as u8, to as u8);
+            let values: Vec<u8> = if from <= to {
+                (from..=to).step_by(step as usize).collect()
+            } else {
+                (to..=from).rev().step_by(step as usize).collect()
+            };
+            Ok(Some(values.iter().map(|&b| (b as char).to_string()).collect()))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Элемент шаблона имени файла
+#[derive(Debug, Clone, PartialEq)]
+enum PatternToken {
+    Literal(char),
+    AnyChar,
+    AnyString,
+    Class { negated: bool, ranges: Vec<(char, char)> },
+}
+
+/// Компонент пути в шаблоне
+#[derive(Debug, Clone, PartialEq)]
+enum Component {
+    Literal(String),
+    Pattern(Vec<PatternToken>),
+    Recursive, // **
+}
+
+fn parse_component(chars: &[QuotedChar]) -> Component {
+    if chars.len() == 2 && is_active(chars, 0, '*') && is_active(chars, 1, '*') {
+        return Component::Recursive;
+    }
+
+    let mut tokens = Vec::new();
+    let mut has_meta = false;
+    let mut index = 0;
+    while index < chars.len() {
+        let (c, quote) = chars[index];
+        if quote != QuoteKind::None {
+            tokens.push(PatternToken::Literal(c));
+            index += 1;
+            continue;
+        }
+        match c {
+            '*' => {
+                has_meta = true;
+                if tokens.last() != Some(&PatternToken::AnyString) {
+                    tokens.push(PatternToken::AnyString);
+                }
+            }
+            '?' => {
+                has_meta = true;
+                tokens.push(PatternToken::AnyChar);
+            }
+            '[' => {
+                if let Some((token, next)) = parse_class(chars, index) {
+                    has_meta = true;
+                    tokens.push(token);
+                    index = next;
+                    continue;
+                }
+                tokens.push(PatternToken::Literal('['));
+            }
+            _ => tokens.push(PatternToken::Literal(c)),
+        }
+        index += 1;
+    }
+
+    if has_meta {
+        Component::Pattern(tokens)
+    } else {
+        Component::Literal(chars.iter().map(|&(c, _)| c).collect())
+    }
+}
+
+/// Разбирает класс [...] начиная с позиции `start`; возвращает позицию после ]
+fn parse_class(chars: &[QuotedChar], start: usize) -> Option<(PatternToken, usize)> {
+    let mut index = start + 1;
+    let negated = matches!(chars.get(index), Some(&('!', QuoteKind::None)) | Some(&('^', QuoteKind::None)));
+    if negated {
+        index += 1;
+    }
+
+    let mut ranges = Vec::new();
+    let mut first = true;
+    while index < chars.len() {
+        let (c, quote) = chars[index];
+        // ] сразу после [ или [! считается обычным символом
+        if c == ']' && quote == QuoteKind::None && !first {
+            return Some((PatternToken::Class { negated, ranges }, index + 1));
+        }
+        first = false;
+        if chars.get(index + 1).map(|&(c, _)| c) == Some('-')
+            && chars.get(index + 2).is_some_and(|&(c, _)| c != ']')
+        {
+            ranges.push((c, chars[index + 2].0));
+            index += 3;
+        } else {
+            ranges.push((c, c));
+            index += 1;
+        }
+    }
+    None
+}
+
+fn matches_char(token: &PatternToken, c: char) -> bool {
+    match token {
+        PatternToken::Literal(expected) => c == *expected,
+        PatternToken::AnyChar => true,
+        PatternToken::Class { negated, ranges } => {
+            ranges.iter().any(|&(from, to)| from <= c && c <= to) != *negated
+        }
+        PatternToken::AnyString => false,
+    }
+}
+
+/// Сопоставление двумя указателями: при несовпадении возвращаемся к последней *
+/// и даем ей поглотить еще один символ. Время O(длина шаблона * длина имени)
+fn matches(pattern: &[PatternToken], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // Позиция после последней * и позиция в имени, с которой она совпала
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        match pattern.get(p) {
+            Some(PatternToken::AnyString) => {
+                p += 1;
+                star = Some((p, n));
+                continue;
+            }
+            Some(token) if matches_char(token, name[n]) => {
+                p += 1;
+                n += 1;
+                continue;
+            }
+            _ => {}
+        }
+        match star {
+            Some((after_star, matched)) => {
+                p = after_star;
+                n = matched + 1;
+                star = Some((after_star, n));
+            }
+            None => return false,
+        }
+    }
+    pattern[p..].iter().all(|token| *token == PatternToken::AnyString)
+}
+
+/// Проверяет строку целиком на соответствие шаблону (*, ?, [...]).
+/// В отличие от имен файлов, * здесь совпадает и с /
+pub fn matches_pattern(pattern: &str, text: &str) -> bool {
+    let chars: Vec<QuotedChar> = pattern.chars().map(|c| (c, QuoteKind::None)).collect();
+    let text: Vec<char> = text.chars().collect();
+    match parse_component(&chars) {
+        Component::Pattern(tokens) => matches(&tokens, &text),
+        Component::Literal(literal) => literal.chars().eq(text.iter().copied()),
+        Component::Recursive => true,
+    }
+}
+
+/// Скрытые файлы совпадают только с шаблоном, явно начинающимся с точки
+fn allows_hidden(pattern: &[PatternToken]) -> bool {
+    pattern.first() == Some(&PatternToken::Literal('.'))
+}
+
+fn join_path(base: &str, name: &str) -> String {
+    if base.is_empty() {
+        name.to_string()
+    } else if base.ends_with('/') {
+        format!("{}{}", base, name)
+    } else {
+        format!("{}/{}", base, name)
+    }
+}
+
+fn sorted_entries(dir: &str) -> Vec<(String, bool)> {
+    let dir = if dir.is_empty() { "." } else { dir };
+    let mut entries: Vec<(String, bool)> = std::fs::read_dir(dir)
+        .map(|read_dir| {
+            read_dir.flatten()
+                .map(|entry| {
+                    let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+                    (entry.file_name().to_string_lossy().to_string(), is_dir)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    entries.sort();
+    entries
+}
+
+fn walk(components: &[Component], current: String, results: &mut Vec<String>) {
+    let (component, rest) = match components.split_first() {
+        Some(split) => split,
+        None => {
+            if !current.is_empty() && std::fs::symlink_metadata(&current).is_ok() {
+                results.push(current);
+            }
+            return;
+        }
+    };
+
+    match component {
+        Component::Literal(name) => walk(rest, join_path(&current, name), results),
+        Component::Recursive => {
+            // ** совпадает с нулем и более уровней директорий
+            walk(rest, current.clone(), results);
+            for (name, is_dir) in sorted_entries(&current) {
+                if is_dir && !name.starts_with('.') {
+                    walk(components, join_path(&current, &name), results);
+                }
+            }
+        }
+        Component::Pattern(tokens) => {
+            for (name, is_dir) in sorted_entries(&current) {
+                if name.starts_with('.') && !allows_hidden(tokens) {
+                    continue;
+                }
+                if !rest.is_empty() && !is_dir {
+                    continue;
+                }
+                let name_chars: Vec<char> = name.chars().collect();
+                if matches(tokens, &name_chars) {
+                    walk(rest, join_path(&current, &name), results);
+                }
+            }
+        }
+    }
+}
+
+/// Раскрывает шаблон имени файла. Возвращает None, если в слове нет
+/// активных метасимволов или ничего не найдено (тогда слово остается как есть)
+pub fn expand_glob(word: &Word) -> Option<Vec<String>> {
+    let chars = word_chars(word);
+    let has_meta = chars.iter()
+        .any(|&(c, quote)| quote == QuoteKind::None && matches!(c, '*' | '?' | '['));
+    if !has_meta {
+        return None;
+    }
+
+    let absolute = chars.first().map(|&(c, _)| c) == Some('/');
+    let trailing_slash = chars.last().map(|&(c, _)| c) == Some('/');
+    let components: Vec<Component> = chars
+        .split(|&(c, _)| c == '/')
+        .filter(|segment| !segment.is_empty())
+        .map(parse_component)
+        .collect();
+
+    if !components.iter().any(|c| !matches!(c, Component::Literal(_))) {
+        return None;
+    }
+
+    let mut results = Vec::new();
+    walk(&components, if absolute { "/".to_string() } else { String::new() }, &mut results);
+
+    if trailing_slash {
+        results.retain(|path| Path::new(path).is_dir());
+        for path in results.iter_mut() {
+            path.push('/');
+        }
+    }
+    results.sort();
+    results.dedup();
+
+    if results.is_empty() { None } else { Some(results) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn braces(input: &str) -> Vec<String> {
+        expand_braces(&Word::literal(input)).unwrap().iter().map(Word::to_plain).collect()
+    }
+
+    #[test]
+    fn test_brace_alternatives() {
+        assert_eq!(braces("src/{a,b}.rs"), vec!["src/a.rs", "src/b.rs"]);
+        assert_eq!(braces("{x,y}{1,2}"), vec!["x1", "x2", "y1", "y2"]);
+        assert_eq!(braces("a{b,{c,d}}"), vec!["ab", "ac", "ad"]);
+        assert_eq!(braces("${HOME}"), vec!["${HOME}"]);
+        assert_eq!(braces("{single}"), vec!["{single}"]);
+    }
+
+    #[test]
+    fn test_brace_sequences() {
+        assert_eq!(braces("{1..3}"), vec!["1", "2", "3"]);
+        assert_eq!(braces("{3..1}"), vec!["3", "2", "1"]);
+        assert_eq!(braces("{01..03}"), vec!["01", "02", "03"]);
+        assert_eq!(braces("{a..c}"), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_brace_limit() {
+        assert!(expand_braces(&Word::literal("{1..9999999999}")).is_err());
+        assert!(expand_braces(&Word::literal("{1..1000}{1..1000}")).is_err());
+        assert_eq!(braces("{1..100000}").len(), 100_000);
+    }
+
+    #[test]
+    fn test_pattern_matching() {
+        let pattern = |s: &str| match parse_component(&word_chars(&Word::literal(s))) {
+            Component::Pattern(tokens) => tokens,
+            other => panic!("ожидался шаблон, получено {:?}", other),
+        };
+        let name = |s: &str| s.chars().collect::<Vec<_>>();
+
+        assert!(matches(&pattern("*.log"), &name("build.log")));
+        assert!(!matches(&pattern("*.log"), &name("build.txt")));
+        assert!(matches(&pattern("file?.txt"), &name("file1.txt")));
+        assert!(matches(&pattern("[a-c]*"), &name("beta")));
+        assert!(!matches(&pattern("[!a-c]*"), &name("beta")));
+        assert!(matches(&pattern("*a*b"), &name("xaxxab")));
+        assert!(!matches(&pattern("a*"), &name("ba")));
+
+        // Возврат к * не экспоненциален
+        let long = name(&"a".repeat(200));
+        assert!(!matches(&pattern("*a*a*a*a*a*a*a*a*b"), &long));
+    }
+
+    #[test]
+    fn test_expand_glob_in_directory() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src/nested")).unwrap();
+        fs::write(root.join("a.log"), "").unwrap();
+        fs::write(root.join("b.log"), "").unwrap();
+        fs::write(root.join(".hidden.log"), "").unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+        fs::write(root.join("src/nested/lib.rs"), "").unwrap();
+
+        let base = root.to_string_lossy();
+        let logs = expand_glob(&Word::literal(&format!("{}/*.log", base))).unwrap();
+        assert_eq!(logs, vec![format!("{}/a.log", base), format!("{}/b.log", base)]);
+
+        let sources = expand_glob(&Word::literal(&format!("{}/**/*.rs", base))).unwrap();
+        assert_eq!(sources, vec![format!("{}/src/main.rs", base), format!("{}/src/nested/lib.rs", base)]);
+
+        assert!(expand_glob(&Word::literal(&format!("{}/*.none", base))).is_none());
+    }
+
+    #[test]
+    fn test_quoted_glob_is_literal() {
+        let mut word = Word::default();
+        word.push_str("*.log", QuoteKind::Double);
+        assert!(expand_glob(&word).is_none());
+    }
+}
@@ -0,0 +1,638 @@
+//! Защита от опасных команд: правила оценивают риск команды перед
+//! запуском и требуют подтверждения или запрещают ее. Под root (или
+//! через sudo) действуют более строгие действия правил. Перехваченные
+//! команды записываются в журнал
+
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use crate::terminal::parser::{CommandParser, Redirection};
+use crate::terminal::pipeline::{Stage, StageRedirect};
+use crate::utils::privileges::PrivilegeManager;
+
+/// Что делать с командой, подходящей под правило
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Action {
+    Allow,
+    Confirm,
+    Block,
+}
+
+impl Action {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "allow" => Some(Action::Allow),
+            "confirm" => Some(Action::Confirm),
+            "block" => Some(Action::Block),
+            _ => None,
+        }
+    }
+}
+
+/// Встроенное правило: действие для обычного пользователя и под root
+struct BuiltinRule {
+    name: &'static str,
+    action: Action,
+    elevated: Action,
+    reason: &'static str,
+    matches: fn(&Invocation) -> bool,
+}
+
+const BUILTIN_RULES: &[BuiltinRule] = &[
+    BuiltinRule {
+        name: "rm-root",
+        action: Action::Block,
+        elevated: Action::Block,
+        reason: "рекурсивное удаление корня, домашней или системной директории",
+        matches: rm_root,
+    },
+    BuiltinRule {
+        name: "rm-recursive",
+        action: Action::Allow,
+        elevated: Action::Confirm,
+        reason: "рекурсивное удаление",
+        matches: rm_recursive,
+    },
+    BuiltinRule {
+        name: "disk-write",
+        action: Action::Confirm,
+        elevated: Action::Block,
+        reason: "запись напрямую на диск",
+        matches: disk_write,
+    },
+    BuiltinRule {
+        name: "mkfs",
+        action: Action::Confirm,
+        elevated: Action::Block,
+        reason: "форматирование или очистка диска",
+        matches: format_disk,
+    },
+    BuiltinRule {
+        name: "chmod-root",
+        action: Action::Block,
+        elevated: Action::Block,
+        reason: "рекурсивная смена прав или владельца системной директории",
+        matches: chmod_root,
+    },
+    BuiltinRule {
+        name: "chmod-777",
+        action: Action::Confirm,
+        elevated: Action::Block,
+        reason: "рекурсивно разрешает запись всем пользователям",
+        matches: chmod_world_writable,
+    },
+    BuiltinRule {
+        name: "git-force-push",
+        action: Action::Confirm,
+        elevated: Action::Confirm,
+        reason: "перезапись истории на сервере",
+        matches: git_force_push,
+    },
+    BuiltinRule {
+        name: "shutdown",
+        action: Action::Confirm,
+        elevated: Action::Confirm,
+        reason: "выключение или перезагрузка системы",
+        matches: shutdown,
+    },
+];
+
+/// Директории, рекурсивное удаление или chmod которых ломает систему
+const SYSTEM_DIRS: &[&str] = &[
+    "/", "/bin", "/boot", "/dev", "/etc", "/home", "/lib", "/lib64", "/opt",
+    "/proc", "/root", "/sbin", "/srv", "/sys", "/usr", "/var",
+];
+
+/// Имена блочных устройств в /dev
+const DISK_DEVICES: &[&str] = &["sd", "hd", "vd", "xvd", "nvme", "mmcblk", "md", "dm-", "mapper/", "disk/"];
+
+/// Программы, выполняющие команду от имени root
+const ELEVATE_PROGRAMS: &[&str] = &["sudo", "doas"];
+
+/// Опции sudo со значением: -u USER и т.п.
+const SUDO_VALUE_OPTIONS: &[&str] = &["-u", "-g", "-h", "-p", "-C", "-D", "-r", "-t", "-U"];
+
+/// Оболочки, код для которых проверяется по командам (!bash, !sh)
+const SHELLS: &[&str] = &["sh", "bash", "dash", "zsh", "ksh"];
+
+/// Ключевые слова оболочки, после которых начинается команда
+const SHELL_KEYWORDS: &[&str] = &[
+    "if", "then", "else", "elif", "fi", "while", "until", "do", "done",
+    "for", "case", "esac", "{", "}", "!", "time",
+];
+
+/// Имена встроенных правил (для проверки [guard.rules])
+pub fn builtin_rule_names() -> impl Iterator<Item = &'static str> {
+    BUILTIN_RULES.iter().map(|rule| rule.name)
+}
+
+/// Правило пользователя из [[guard.custom]]: команда и аргументы,
+/// которые все должны присутствовать
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomRule {
+    pub name: String,
+    pub command: String,
+    pub args: Vec<String>,
+    pub action: Action,
+    pub elevated: Action,
+    pub reason: String,
+}
+
+/// Настройки защиты из секции [guard]
+#[derive(Debug, Clone, PartialEq)]
+pub struct GuardConfig {
+    pub enabled: bool,
+    /// None - не вести журнал
+    pub log_file: Option<PathBuf>,
+    /// Действия встроенных правил для обычного пользователя
+    pub rules: HashMap<String, Action>,
+    /// Действия встроенных правил под root
+    pub elevated_rules: HashMap<String, Action>,
+    pub custom: Vec<CustomRule>,
+}
+
+impl Default for GuardConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            log_file: default_log_path(),
+            rules: HashMap::new(),
+            elevated_rules: HashMap::new(),
+            custom: Vec::new(),
+        }
+    }
+}
+
+/// $XDG_STATE_HOME/smart-term/guard.log (~/.local/state/smart-term/guard.log)
+pub fn default_log_path() -> Option<PathBuf> {
+    let state_dir = std::env::var("XDG_STATE_HOME")
+        .ok()
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var("HOME").ok().map(|home| Path::new(&home).join(".local/state")))?;
+    Some(state_dir.join("smart-term").join("guard.log"))
+}
+
+/// Команда в том виде, в котором она будет запущена
+#[derive(Debug)]
+pub struct Invocation<'a> {
+    pub program: &'a str,
+    pub args: &'a [String],
+    /// Файлы перенаправления вывода
+    pub outputs: Vec<&'a str>,
+    /// Запуск через sudo или doas
+    pub elevated: bool,
+}
+
+impl<'a> Invocation<'a> {
+    pub fn new(program: &'a str, args: &'a [String]) -> Self {
+        let mut invocation = Self { program, args, outputs: Vec::new(), elevated: false };
+        invocation.unwrap_sudo();
+        invocation
+    }
+
+    pub fn from_stage(stage: &'a Stage) -> Self {
+        let mut invocation = Self::new(&stage.program, &stage.args);
+        invocation.outputs = stage.redirects.iter()
+            .filter_map(|redirect| match redirect {
+                StageRedirect::Output { path, .. } => Some(path.as_str()),
+                _ => None,
+            })
+            .collect();
+        invocation
+    }
+
+    /// sudo -u root rm ... - проверяется rm, но как команда root
+    fn unwrap_sudo(&mut self) {
+        while ELEVATE_PROGRAMS.contains(&self.program) {
+            let mut rest = self.args;
+            while let Some(arg) = rest.first().filter(|arg| arg.starts_with('-')) {
+                let skip = if SUDO_VALUE_OPTIONS.contains(&arg.as_str()) { 2 } else { 1 };
+                rest = &rest[skip.min(rest.len())..];
+            }
+            match rest.split_first() {
+                Some((program, args)) => {
+                    self.program = program;
+                    self.args = args;
+                    self.elevated = true;
+                }
+                None => return,
+            }
+        }
+    }
+
+    /// Имя программы без пути: /bin/rm -> rm
+    fn name(&self) -> &str {
+        self.program.rsplit('/').next().unwrap_or(self.program)
+    }
+
+    /// Аргументы, не являющиеся опциями
+    fn operands(&self) -> impl Iterator<Item = &str> {
+        let mut options_ended = false;
+        self.args.iter().filter_map(move |arg| {
+            if options_ended {
+                return Some(arg.as_str());
+            }
+            if arg == "--" {
+                options_ended = true;
+                return None;
+            }
+            (!arg.starts_with('-') || arg == "-").then_some(arg.as_str())
+        })
+    }
+
+    /// Короткая опция (в том числе внутри -rf) или длинная
+    fn has_flag(&self, short: char, long: &str) -> bool {
+        self.args.iter()
+            .take_while(|arg| *arg != "--")
+            .any(|arg| arg == long || (arg.starts_with('-') && !arg.starts_with("--") && arg.contains(short)))
+    }
+
+    fn recursive(&self) -> bool {
+        self.has_flag('r', "--recursive") || self.has_flag('R', "--recursive")
+    }
+}
+
+/// Простая команда из кода для оболочки, слова без раскрытий
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeCommand {
+    pub argv: Vec<String>,
+    /// Файлы перенаправления вывода
+    pub outputs: Vec<String>,
+}
+
+impl CodeCommand {
+    pub fn invocation(&self) -> Option<Invocation<'_>> {
+        let (program, args) = self.argv.split_first()?;
+        let mut invocation = Invocation::new(program, args);
+        invocation.outputs = self.outputs.iter().map(String::as_str).collect();
+        Some(invocation)
+    }
+
+    fn new(mut argv: Vec<String>, outputs: Vec<String>) -> Option<Self> {
+        let keywords = argv.iter().take_while(|word| SHELL_KEYWORDS.contains(&word.as_str())).count();
+        argv.drain(..keywords);
+        (!argv.is_empty()).then_some(Self { argv, outputs })
+    }
+}
+
+/// Интерпретатор - оболочка: sh, /bin/bash и т.п.
+pub fn is_shell(program: &str) -> bool {
+    SHELLS.contains(&program.rsplit('/').next().unwrap_or(program))
+}
+
+/// Простые команды кода для оболочки. Код, который терминал не разбирает
+/// (подстановка команд, многострочные конструкции), делится по
+/// разделителям - проверка не должна пропустить команду
+pub fn code_commands(code: &str) -> Vec<CodeCommand> {
+    let parsed = CommandParser::new().parse_list(code).ok()
+        .filter(|_| !code.contains("$(") && !code.contains('`'));
+    let Some(list) = parsed else {
+        return code.split([';', '|', '&', '\n', '(', ')', '`'])
+            .filter_map(|segment| {
+                let argv = segment.split_whitespace()
+                    .map(|word| word.trim_matches(|c| c == '"' || c == '\'').to_string())
+                    .collect();
+                CodeCommand::new(argv, Vec::new())
+            })
+            .collect();
+    };
+    list.items.iter()
+        .flat_map(|item| &item.pipeline.commands)
+        .filter_map(|command| {
+            let outputs = command.redirections.iter()
+                .filter_map(|redirection| match redirection {
+                    Redirection::Output { target, .. } => Some(target.to_plain()),
+                    _ => None,
+                })
+                .collect();
+            CodeCommand::new(command.words.iter().map(|word| word.to_plain()).collect(), outputs)
+        })
+        .collect()
+}
+
+/// Сработавшее правило
+#[derive(Debug, Clone, PartialEq)]
+pub struct Verdict {
+    pub rule: String,
+    pub action: Action,
+    pub reason: String,
+}
+
+/// Чем закончилась перехваченная команда (для журнала)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Confirmed,
+    Declined,
+    Blocked,
+}
+
+impl Decision {
+    fn name(self) -> &'static str {
+        match self {
+            Decision::Confirmed => "confirmed",
+            Decision::Declined => "declined",
+            Decision::Blocked => "blocked",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Guard {
+    config: GuardConfig,
+    /// Терминал запущен под root
+    elevated: bool,
+}
+
+impl Default for Guard {
+    fn default() -> Self {
+        Self::new(GuardConfig::default())
+    }
+}
+
+impl Guard {
+    pub fn new(config: GuardConfig) -> Self {
+        Self { config, elevated: PrivilegeManager::is_elevated() }
+    }
+
+    /// Самое строгое из сработавших правил; None - команду можно запускать
+    pub fn check(&self, invocation: &Invocation) -> Option<Verdict> {
+        if !self.config.enabled {
+            return None;
+        }
+        let elevated = self.elevated || invocation.elevated;
+
+        let builtin = BUILTIN_RULES.iter()
+            .filter(|rule| (rule.matches)(invocation))
+            .map(|rule| {
+                let action = if elevated {
+                    // Без явной настройки под root правило не мягче, чем для пользователя
+                    self.config.elevated_rules.get(rule.name).copied().unwrap_or_else(|| {
+                        self.config.rules.get(rule.name).map_or(rule.elevated, |&action| action.max(rule.elevated))
+                    })
+                } else {
+                    self.config.rules.get(rule.name).copied().unwrap_or(rule.action)
+                };
+                Verdict { rule: rule.name.to_string(), action, reason: rule.reason.to_string() }
+            });
+        let custom = self.config.custom.iter()
+            .filter(|rule| rule.command == invocation.name()
+                && rule.args.iter().all(|arg| invocation.args.contains(arg)))
+            .map(|rule| Verdict {
+                rule: rule.name.clone(),
+                action: if elevated { rule.elevated } else { rule.action },
+                reason: rule.reason.clone(),
+            });
+
+        // При равных действиях - первое правило
+        builtin.chain(custom)
+            .filter(|verdict| verdict.action != Action::Allow)
+            .fold(None, |strictest: Option<Verdict>, verdict| match strictest {
+                Some(current) if current.action >= verdict.action => Some(current),
+                _ => Some(verdict),
+            })
+    }
+
+    /// Дописывает перехваченную команду в журнал. Ошибки записи не мешают работе
+    pub fn log(&self, verdict: &Verdict, command: &str, decision: Decision) {
+        let Some(path) = &self.config.log_file else {
+            return;
+        };
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        let line = format_log_line(verdict, command, decision, &whoami::username());
+        if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+            let _ = writeln!(file, "{}", line);
+        }
+    }
+}
+
+/// Строка журнала: время, пользователь, правило, решение, команда
+fn format_log_line(verdict: &Verdict, command: &str, decision: Decision, user: &str) -> String {
+    format!(
+        "{}\t{}\t{}\t{}\t{}",
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+        user,
+        verdict.rule,
+        decision.name(),
+        command,
+    )
+}
+
+/// /, /usr, /etc/, /*, ~ и т.п.
+fn is_critical_path(path: &str) -> bool {
+    let path = path.strip_suffix("/*").unwrap_or(path);
+    let trimmed = path.trim_end_matches('/');
+    let path = if trimmed.is_empty() { "/" } else { trimmed };
+    if path.is_empty() || SYSTEM_DIRS.contains(&path) || path == "~" || path == "$HOME" {
+        return true;
+    }
+    std::env::var("HOME").is_ok_and(|home| !home.is_empty() && home.trim_end_matches('/') == path)
+}
+
+/// /dev/sda, /dev/nvme0n1p1, /dev/mapper/root
+fn is_disk_device(path: &str) -> bool {
+    path.strip_prefix("/dev/")
+        .is_some_and(|device| DISK_DEVICES.iter().any(|prefix| device.starts_with(prefix)))
+}
+
+fn rm_root(invocation: &Invocation) -> bool {
+    invocation.name() == "rm"
+        && (invocation.recursive() || invocation.args.iter().any(|arg| arg == "--no-preserve-root"))
+        && invocation.operands().any(is_critical_path)
+}
+
+fn rm_recursive(invocation: &Invocation) -> bool {
+    invocation.name() == "rm" && invocation.recursive()
+}
+
+fn disk_write(invocation: &Invocation) -> bool {
+    let dd_output = invocation.name() == "dd"
+        && invocation.args.iter().any(|arg| arg.strip_prefix("of=").is_some_and(is_disk_device));
+    dd_output || invocation.outputs.iter().any(|path| is_disk_device(path))
+}
+
+fn format_disk(invocation: &Invocation) -> bool {
+    let name = invocation.name();
+    name == "mkfs" || name.starts_with("mkfs.") || ["mke2fs", "mkswap", "wipefs"].contains(&name)
+}
+
+fn chmod_root(invocation: &Invocation) -> bool {
+    ["chmod", "chown", "chgrp"].contains(&invocation.name())
+        && invocation.recursive()
+        // Первый операнд - режим или владелец
+        && invocation.operands().skip(1).any(is_critical_path)
+}
+
+/// 777, 0666, o+w, a+rwx
+fn is_world_writable(mode: &str) -> bool {
+    if mode.chars().all(|c| c.is_ascii_digit()) {
+        return mode.chars().last()
+            .and_then(|others| others.to_digit(8))
+            .is_some_and(|others| others & 0o2 != 0);
+    }
+    mode.split(',').any(|clause| match clause.split_once(['+', '=']) {
+        Some((who, permissions)) => (who.is_empty() || who.contains(['o', 'a'])) && permissions.contains('w'),
+        None => false,
+    })
+}
+
+fn chmod_world_writable(invocation: &Invocation) -> bool {
+    invocation.name() == "chmod"
+        && invocation.recursive()
+        && invocation.operands().next().is_some_and(is_world_writable)
+}
+
+fn git_force_push(invocation: &Invocation) -> bool {
+    if invocation.name() != "git" {
+        return false;
+    }
+    let Some(push) = invocation.args.iter().position(|arg| arg == "push") else {
+        return false;
+    };
+    invocation.args[push + 1..].iter().any(|arg| {
+        arg == "--force"
+            || arg.starts_with("--force-with-lease")
+            || (arg.starts_with('-') && !arg.starts_with("--") && arg.contains('f'))
+            || (arg.starts_with('+') && arg.len() > 1)
+    })
+}
+
+fn shutdown(invocation: &Invocation) -> bool {
+    let first = invocation.operands().next();
+    match invocation.name() {
+        "shutdown" | "reboot" | "poweroff" | "halt" => true,
+        "systemctl" => matches!(first, Some("poweroff" | "reboot" | "halt" | "kexec")),
+        "init" | "telinit" => matches!(first, Some("0" | "6")),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    fn check(guard: &Guard, line: &str) -> Option<(String, Action)> {
+        let argv = args(line);
+        let invocation = Invocation::new(&argv[0], &argv[1..]);
+        guard.check(&invocation).map(|verdict| (verdict.rule, verdict.action))
+    }
+
+    fn guard(config: GuardConfig, elevated: bool) -> Guard {
+        Guard { config: GuardConfig { log_file: None, ..config }, elevated }
+    }
+
+    #[test]
+    fn test_builtin_rules() {
+        let user = guard(GuardConfig::default(), false);
+        assert_eq!(check(&user, "rm -rf /"), Some(("rm-root".to_string(), Action::Block)));
+        assert_eq!(check(&user, "rm -r -f /usr/"), Some(("rm-root".to_string(), Action::Block)));
+        assert_eq!(check(&user, "/bin/rm --recursive /*"), Some(("rm-root".to_string(), Action::Block)));
+        assert_eq!(check(&user, "rm -rf build"), None);
+        assert_eq!(check(&user, "rm -f /etc/motd"), None);
+        assert_eq!(check(&user, "dd if=disk.img of=/dev/sda bs=4M"), Some(("disk-write".to_string(), Action::Confirm)));
+        assert_eq!(check(&user, "dd if=/dev/zero of=disk.img"), None);
+        assert_eq!(check(&user, "mkfs.ext4 /dev/sdb1"), Some(("mkfs".to_string(), Action::Confirm)));
+        assert_eq!(check(&user, "chmod -R 777 /"), Some(("chmod-root".to_string(), Action::Block)));
+        assert_eq!(check(&user, "chmod -R a+rwx www"), Some(("chmod-777".to_string(), Action::Confirm)));
+        assert_eq!(check(&user, "chmod -R 755 www"), None);
+        assert_eq!(check(&user, "git push --force origin main"), Some(("git-force-push".to_string(), Action::Confirm)));
+        assert_eq!(check(&user, "git push origin +main"), Some(("git-force-push".to_string(), Action::Confirm)));
+        assert_eq!(check(&user, "git push origin main"), None);
+        assert_eq!(check(&user, "systemctl reboot"), Some(("shutdown".to_string(), Action::Confirm)));
+        assert_eq!(check(&user, "systemctl status"), None);
+    }
+
+    #[test]
+    fn test_elevated_is_stricter() {
+        let root = guard(GuardConfig::default(), true);
+        assert_eq!(check(&root, "rm -rf build"), Some(("rm-recursive".to_string(), Action::Confirm)));
+        assert_eq!(check(&root, "mkfs.ext4 /dev/sdb1"), Some(("mkfs".to_string(), Action::Block)));
+
+        // sudo делает команду командой root
+        let user = guard(GuardConfig::default(), false);
+        assert_eq!(check(&user, "sudo -u root dd of=/dev/nvme0n1"), Some(("disk-write".to_string(), Action::Block)));
+        assert_eq!(check(&user, "sudo rm -rf build"), Some(("rm-recursive".to_string(), Action::Confirm)));
+    }
+
+    #[test]
+    fn test_redirection_to_disk() {
+        let user = guard(GuardConfig::default(), false);
+        let stage = Stage {
+            program: "cat".to_string(),
+            args: args("image.iso"),
+            env: HashMap::new(),
+            redirects: vec![StageRedirect::Output { fd: 1, path: "/dev/sdb".to_string(), append: false }],
+        };
+        let verdict = user.check(&Invocation::from_stage(&stage)).unwrap();
+        assert_eq!((verdict.rule.as_str(), verdict.action), ("disk-write", Action::Confirm));
+    }
+
+    #[test]
+    fn test_shell_code() {
+        let user = guard(GuardConfig::default(), false);
+        let rules = |code: &str| -> Vec<String> {
+            code_commands(code).iter()
+                .filter_map(|command| user.check(&command.invocation()?))
+                .map(|verdict| verdict.rule)
+                .collect()
+        };
+        assert_eq!(rules("cd /tmp && sudo rm -rf '/'"), ["rm-root"]);
+        assert_eq!(rules("echo start; cat image.iso > /dev/sda"), ["disk-write"]);
+        assert_eq!(rules("if true; then chmod -R 777 /; fi"), ["chmod-root"]);
+        assert_eq!(rules("echo $(rm -rf /usr)"), ["rm-root"]);
+        assert_eq!(rules("for f in *.log; do rm -f \"$f\"; done"), Vec::<String>::new());
+        assert!(is_shell("/bin/bash") && is_shell("sh") && !is_shell("python3"));
+    }
+
+    #[test]
+    fn test_config_overrides_and_custom_rules() {
+        let mut config = GuardConfig::default();
+        config.rules.insert("git-force-push".to_string(), Action::Allow);
+        config.rules.insert("rm-recursive".to_string(), Action::Confirm);
+        config.elevated_rules.insert("mkfs".to_string(), Action::Confirm);
+        config.custom.push(CustomRule {
+            name: "terraform-destroy".to_string(),
+            command: "terraform".to_string(),
+            args: vec!["destroy".to_string()],
+            action: Action::Confirm,
+            elevated: Action::Block,
+            reason: "удаление инфраструктуры".to_string(),
+        });
+
+        let user = guard(config.clone(), false);
+        assert_eq!(check(&user, "git push -f"), None);
+        assert_eq!(check(&user, "rm -r build"), Some(("rm-recursive".to_string(), Action::Confirm)));
+        assert_eq!(check(&user, "terraform destroy -auto-approve"), Some(("terraform-destroy".to_string(), Action::Confirm)));
+        assert_eq!(check(&user, "terraform plan"), None);
+
+        let root = guard(config, true);
+        // Смягчение для пользователя не действует под root
+        assert_eq!(check(&root, "git push -f"), Some(("git-force-push".to_string(), Action::Confirm)));
+        assert_eq!(check(&root, "mkfs /dev/sdc"), Some(("mkfs".to_string(), Action::Confirm)));
+        assert_eq!(check(&root, "terraform destroy"), Some(("terraform-destroy".to_string(), Action::Block)));
+
+        let disabled = guard(GuardConfig { enabled: false, ..GuardConfig::default() }, true);
+        assert_eq!(check(&disabled, "rm -rf /"), None);
+    }
+
+    #[test]
+    fn test_mode_and_log_line() {
+        assert!(is_world_writable("777"));
+        assert!(is_world_writable("0666"));
+        assert!(is_world_writable("u+x,o+w"));
+        assert!(is_world_writable("+w"));
+        assert!(!is_world_writable("775"));
+        assert!(!is_world_writable("g+w"));
+
+        let verdict = Verdict { rule: "rm-root".to_string(), action: Action::Block, reason: String::new() };
+        let line = format_log_line(&verdict, "rm -rf /", Decision::Blocked, "alice");
+        assert!(line.ends_with("\talice\trm-root\tblocked\trm -rf /"), "{}", line);
+    }
+}
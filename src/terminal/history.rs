@@ -1,88 +1,554 @@
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Local, TimeZone};
+use crate::terminal::glob;
+
+/// Запись истории команд
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub command: String,
+    pub timestamp: DateTime<Local>,
+    pub cwd: String,
+    pub exit_code: i32,
+    pub duration_ms: u64,
+}
+
+impl HistoryEntry {
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            timestamp: Local::now(),
+            cwd: std::env::current_dir()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            exit_code: 0,
+            duration_ms: 0,
+        }
+    }
+
+    /// Строка файла истории: время, длительность, код, директория и команда через табуляцию
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{}",
+            self.timestamp.timestamp(),
+            self.duration_ms,
+            self.exit_code,
+            escape_field(&self.cwd),
+            escape_field(&self.command)
+        )
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let mut fields = line.splitn(5, '\t');
+        let timestamp = fields.next()?.parse::<i64>().ok()?;
+        let duration_ms = fields.next()?.parse().ok()?;
+        let exit_code = fields.next()?.parse().ok()?;
+        let cwd = unescape_field(fields.next()?);
+        let command = unescape_field(fields.next()?);
+        Some(Self {
+            command,
+            timestamp: Local.timestamp_opt(timestamp, 0).single()?,
+            cwd,
+            exit_code,
+            duration_ms,
+        })
+    }
+}
+
+fn escape_field(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n")
+}
+
+fn unescape_field(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            result.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => result.push('\t'),
+            Some('n') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+/// Путь к файлу истории по умолчанию: $XDG_DATA_HOME/smart-term/history
+/// или ~/.local/share/smart-term/history
+pub fn default_history_path() -> Option<PathBuf> {
+    let data_dir = std::env::var("XDG_DATA_HOME")
+        .ok()
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var("HOME").ok().map(|home| Path::new(&home).join(".local/share")))?;
+    Some(data_dir.join("smart-term").join("history"))
+}
+
+/// Файл истории, общий для нескольких сессий. Все операции
+/// выполняются под эксклюзивной блокировкой flock
+#[derive(Debug, Clone)]
+pub struct HistoryFile {
+    path: PathBuf,
+}
+
+/// Блокировка файла, снимается при закрытии
+struct LockedFile {
+    file: File,
+}
+
+impl LockedFile {
+    fn open(path: &Path) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut options = OpenOptions::new();
+        options.read(true).append(true).create(true);
+        // Как и bash, история доступна только владельцу
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let file = options.open(path)?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::io::AsRawFd;
+            if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(Self { file })
+    }
+
+    fn read_entries(&mut self) -> io::Result<Vec<HistoryEntry>> {
+        self.file.seek(SeekFrom::Start(0))?;
+        Ok(BufReader::new(&self.file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| HistoryEntry::from_line(&line))
+            .collect())
+    }
+
+    /// Перезаписывает файл целиком. Файл открыт в режиме append,
+    /// поэтому после set_len(0) запись идет с начала
+    fn rewrite(&mut self, entries: &[HistoryEntry]) -> io::Result<()> {
+        self.file.set_len(0)?;
+        let mut content = String::new();
+        for entry in entries {
+            content.push_str(&entry.to_line());
+            content.push('\n');
+        }
+        self.file.write_all(content.as_bytes())?;
+        self.file.flush()
+    }
+}
+
+impl Drop for LockedFile {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        {
+            use std::os::unix::io::AsRawFd;
+            unsafe {
+                libc::flock(self.file.as_raw_fd(), libc::LOCK_UN);
+            }
+        }
+    }
+}
+
+impl HistoryFile {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Загружает последние `max_size` уникальных записей и число запусков
+    /// каждой команды. Если файл сильно разросся из-за дубликатов, он сжимается
+    pub fn load(&self, max_size: usize) -> io::Result<(Vec<HistoryEntry>, HashMap<String, usize>)> {
+        let mut locked = LockedFile::open(&self.path)?;
+        let entries = locked.read_entries()?;
+        let total = entries.len();
+
+        let mut frequencies = HashMap::new();
+        for entry in &entries {
+            *frequencies.entry(entry.command.clone()).or_insert(0) += 1;
+        }
+        let unique = deduplicate(entries, max_size);
+
+        if total > unique.len() * 2 && total > max_size {
+            locked.rewrite(&unique)?;
+        }
+        Ok((unique, frequencies))
+    }
+
+    /// Дописывает запись в конец файла
+    pub fn append(&self, entry: &HistoryEntry) -> io::Result<()> {
+        let mut locked = LockedFile::open(&self.path)?;
+        writeln!(locked.file, "{}", entry.to_line())?;
+        locked.file.flush()
+    }
+
+    /// Удаляет из файла все строки с этой командой (history -d): старые
+    /// повторы иначе вернули бы ее при следующем запуске
+    pub fn remove(&self, command: &str) -> io::Result<()> {
+        let mut locked = LockedFile::open(&self.path)?;
+        let mut entries = locked.read_entries()?;
+        entries.retain(|e| e.command != command);
+        locked.rewrite(&entries)
+    }
+
+    /// Очищает файл (history -c)
+    pub fn clear(&self) -> io::Result<()> {
+        let mut locked = LockedFile::open(&self.path)?;
+        locked.rewrite(&[])
+    }
+}
+
+/// Оставляет последнее вхождение каждой команды и не более `max_size` записей
+fn deduplicate(entries: Vec<HistoryEntry>, max_size: usize) -> Vec<HistoryEntry> {
+    let mut seen = std::collections::HashSet::new();
+    let mut unique: Vec<HistoryEntry> = entries.into_iter()
+        .rev()
+        .filter(|entry| seen.insert(entry.command.clone()))
+        .take(max_size)
+        .collect();
+    unique.reverse();
+    unique
+}
+
+/// Какие команды не сохраняются в историю
+#[derive(Debug, Clone)]
+pub struct HistoryFilter {
+    /// Не сохранять команды, начинающиеся с пробела
+    pub ignore_space: bool,
+    /// Шаблоны (*, ?, [...]) команд, которые не сохраняются
+    pub ignore_patterns: Vec<String>,
+}
+
+impl Default for HistoryFilter {
+    fn default() -> Self {
+        Self {
+            ignore_space: true,
+            ignore_patterns: default_ignore_patterns(),
+        }
+    }
+}
+
+/// Шаблоны по умолчанию: присваивания секретов
+pub fn default_ignore_patterns() -> Vec<String> {
+    ["*TOKEN=*", "*PASSWORD=*", "*PASSWD=*", "*SECRET*=*", "*API_KEY=*"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+impl HistoryFilter {
+    /// Проверяет исходную строку ввода (до удаления пробелов)
+    pub fn should_ignore(&self, raw_input: &str) -> bool {
+        if self.ignore_space && raw_input.starts_with(' ') {
+            return true;
+        }
+        let command = raw_input.trim();
+        self.ignore_patterns.iter().any(|pattern| glob::matches_pattern(pattern, command))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CommandHistory {
-    commands: Vec<String>,
+    entries: Vec<HistoryEntry>,
     max_size: usize,
     current_index: usize,
+    file: Option<HistoryFile>,
+    filter: HistoryFilter,
+    /// Сколько раз запускалась каждая команда (с учетом дубликатов в файле)
+    frequencies: HashMap<String, usize>,
 }
 
+// Поиск и удаление записей пока не используются терминалом
+#[allow(dead_code)]
 impl CommandHistory {
     pub fn new(max_size: usize) -> Self {
         Self {
-            commands: Vec::with_capacity(max_size),
+            entries: Vec::new(),
             max_size,
             current_index: 0,
+            file: None,
+            filter: HistoryFilter::default(),
+            frequencies: HashMap::new(),
+        }
+    }
+
+    /// История, сохраняемая в файл. Ошибка чтения файла не мешает работе
+    pub fn with_file(max_size: usize, path: PathBuf) -> Self {
+        let file = HistoryFile::new(path);
+        let (entries, frequencies) = file.load(max_size).unwrap_or_else(|e| {
+            eprintln!("Не удалось прочитать историю {}: {}", file.path().display(), e);
+            (Vec::new(), HashMap::new())
+        });
+
+        Self {
+            current_index: entries.len(),
+            entries,
+            max_size,
+            file: Some(file),
+            filter: HistoryFilter::default(),
+            frequencies,
         }
     }
-    
+
+    pub fn set_filter(&mut self, filter: HistoryFilter) {
+        self.filter = filter;
+    }
+
+    pub fn filter(&self) -> &HistoryFilter {
+        &self.filter
+    }
+
     pub fn add(&mut self, command: String) {
-        let command = command.trim().to_string();
-        
-        // Не добавляем пустые команды и дубликаты подряд
-        if !command.is_empty() && self.commands.last() != Some(&command) {
-            if self.commands.len() >= self.max_size {
-                self.commands.remove(0);
+        self.add_entry(HistoryEntry::new(&command));
+    }
+
+    /// Добавляет запись. Более старая такая же команда удаляется из списка
+    pub fn add_entry(&mut self, mut entry: HistoryEntry) {
+        entry.command = entry.command.trim().to_string();
+
+        if !entry.command.is_empty() {
+            self.entries.retain(|e| e.command != entry.command);
+            *self.frequencies.entry(entry.command.clone()).or_insert(0) += 1;
+            if !self.entries.is_empty() && self.entries.len() >= self.max_size {
+                self.entries.remove(0);
             }
-            self.commands.push(command);
+            if let Some(file) = &self.file {
+                if let Err(e) = file.append(&entry) {
+                    eprintln!("Не удалось сохранить историю: {}", e);
+                }
+            }
+            self.entries.push(entry);
         }
-        self.current_index = self.commands.len();
+        self.current_index = self.entries.len();
     }
-    
+
     pub fn get_previous(&mut self) -> Option<&String> {
         if self.current_index > 0 {
             self.current_index -= 1;
-            self.commands.get(self.current_index)
+            self.entries.get(self.current_index).map(|e| &e.command)
         } else {
             None
         }
     }
-    
+
     pub fn get_next(&mut self) -> Option<&String> {
-        if self.current_index < self.commands.len() - 1 {
+        if self.current_index + 1 < self.entries.len() {
             self.current_index += 1;
-            self.commands.get(self.current_index)
+            self.entries.get(self.current_index).map(|e| &e.command)
         } else {
-            self.current_index = self.commands.len();
+            self.current_index = self.entries.len();
             None
         }
     }
-    
-    pub fn get_all(&self) -> &Vec<String> {
-        &self.commands
+
+    /// Сбрасывает позицию навигации в конец истории (перед чтением новой строки)
+    pub fn reset_navigation(&mut self) {
+        self.current_index = self.entries.len();
+    }
+
+    pub fn get_all(&self) -> Vec<&String> {
+        self.entries.iter().map(|e| &e.command).collect()
     }
-    
+
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    /// Сколько раз запускалась команда
+    pub fn frequency(&self, command: &str) -> usize {
+        self.frequencies.get(command).copied().unwrap_or(0)
+    }
+
+    /// Очищает историю в памяти и в файле
     pub fn clear(&mut self) {
-        self.commands.clear();
+        self.entries.clear();
+        self.frequencies.clear();
         self.current_index = 0;
+        if let Some(file) = &self.file {
+            if let Err(e) = file.clear() {
+                eprintln!("Не удалось очистить файл истории: {}", e);
+            }
+        }
     }
-    
+
     pub fn search(&self, query: &str) -> Vec<&String> {
-        self.commands
+        self.entries
             .iter()
+            .map(|e| &e.command)
             .filter(|cmd| cmd.contains(query))
             .collect()
     }
-    
+
     pub fn len(&self) -> usize {
-        self.commands.len()
+        self.entries.len()
     }
-    
+
     pub fn is_empty(&self) -> bool {
-        self.commands.is_empty()
+        self.entries.is_empty()
     }
-    
+
     pub fn get_by_index(&self, index: usize) -> Option<&String> {
-        self.commands.get(index)
+        self.entries.get(index).map(|e| &e.command)
     }
-    
+
     pub fn remove(&mut self, index: usize) -> Option<String> {
-        if index < self.commands.len() {
-            let removed = self.commands.remove(index);
+        if index < self.entries.len() {
+            let removed = self.entries.remove(index);
+            self.frequencies.remove(&removed.command);
             if self.current_index >= index {
                 self.current_index = self.current_index.saturating_sub(1);
             }
-            Some(removed)
+            if let Some(file) = &self.file {
+                if let Err(e) = file.remove(&removed.command) {
+                    eprintln!("Не удалось обновить файл истории: {}", e);
+                }
+            }
+            Some(removed.command)
         } else {
             None
         }
     }
+
+    /// Встроенная команда history: без аргументов, N, -c, -d N.
+    /// Номера записей начинаются с 1, как в bash
+    pub fn run_builtin(&mut self, args: &[&str]) -> Result<String, String> {
+        match args {
+            [] => Ok(self.format_entries(0)),
+            ["-c"] => {
+                self.clear();
+                Ok(String::new())
+            }
+            ["-d", number] => {
+                let index = number.parse::<usize>()
+                    .ok()
+                    .filter(|n| *n >= 1 && *n <= self.entries.len())
+                    .ok_or_else(|| format!("{}: позиция вне диапазона", number))?;
+                self.remove(index - 1);
+                Ok(String::new())
+            }
+            [count] => {
+                let count = count.parse::<usize>()
+                    .map_err(|_| format!("{}: требуется числовой аргумент", count))?;
+                Ok(self.format_entries(self.entries.len().saturating_sub(count)))
+            }
+            _ => Err("использование: history [-c] [-d N] [N]".to_string()),
+        }
+    }
+
+    fn format_entries(&self, skip: usize) -> String {
+        self.entries.iter()
+            .enumerate()
+            .skip(skip)
+            .map(|(i, entry)| format!(
+                "{:5}  {}  {}",
+                i + 1,
+                entry.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                entry.command
+            ))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_line_roundtrip() {
+        let mut entry = HistoryEntry::new("echo 'a\tb'\nls \\");
+        entry.cwd = "/tmp/dir with\ttab".to_string();
+        entry.exit_code = 2;
+        entry.duration_ms = 150;
+
+        let parsed = HistoryEntry::from_line(&entry.to_line()).unwrap();
+        assert_eq!(parsed.command, entry.command);
+        assert_eq!(parsed.cwd, entry.cwd);
+        assert_eq!(parsed.exit_code, 2);
+        assert_eq!(parsed.duration_ms, 150);
+        assert_eq!(parsed.timestamp.timestamp(), entry.timestamp.timestamp());
+    }
+
+    #[test]
+    fn test_persistence_and_dedup() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("nested/history");
+
+        let mut first = CommandHistory::with_file(100, path.clone());
+        first.add("ls".to_string());
+        first.add("pwd".to_string());
+        let mut second = CommandHistory::with_file(100, path.clone());
+        second.add("ls".to_string());
+
+        let reloaded = CommandHistory::with_file(100, path);
+        assert_eq!(reloaded.get_all(), vec!["pwd", "ls"]);
+        assert_eq!(reloaded.frequency("ls"), 2);
+    }
+
+    #[test]
+    fn test_builtin_delete_and_clear() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("history");
+
+        let mut history = CommandHistory::with_file(100, path.clone());
+        for command in ["a", "b", "c"] {
+            history.add(command.to_string());
+        }
+        history.run_builtin(&["-d", "2"]).unwrap();
+        assert_eq!(history.get_all(), vec!["a", "c"]);
+        assert!(history.run_builtin(&["-d", "5"]).is_err());
+        assert_eq!(history.run_builtin(&["1"]).unwrap().lines().count(), 1);
+        assert_eq!(CommandHistory::with_file(100, path.clone()).len(), 2);
+
+        history.run_builtin(&["-c"]).unwrap();
+        assert!(CommandHistory::with_file(100, path).is_empty());
+    }
+
+    #[test]
+    fn test_delete_removes_older_duplicates() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("history");
+
+        // Та же команда из двух сессий: в файле две строки
+        CommandHistory::with_file(100, path.clone()).add("export KEY=secret".to_string());
+        let mut history = CommandHistory::with_file(100, path.clone());
+        history.add("export KEY=secret".to_string());
+        history.add("ls".to_string());
+
+        history.run_builtin(&["-d", "1"]).unwrap();
+        let reloaded = CommandHistory::with_file(100, path.clone());
+        assert_eq!(reloaded.get_all(), vec!["ls"]);
+        assert_eq!(reloaded.frequency("export KEY=secret"), 0);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_ignore_rules() {
+        let filter = HistoryFilter::default();
+        assert!(filter.should_ignore(" ls"));
+        assert!(filter.should_ignore("export GITHUB_TOKEN=abc"));
+        assert!(filter.should_ignore("DB_PASSWORD=x ./run.sh"));
+        assert!(!filter.should_ignore("ls -la"));
+    }
 }
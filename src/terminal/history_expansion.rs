@@ -0,0 +1,301 @@
+//! Раскрытие истории в стиле bash: !!, !n, !-n, !str, !?str?, слова
+//! (!$, !^, !*, !!:2, !3:1-2) и быстрая подстановка ^old^new
+
+use crate::terminal::CommandHistory;
+use crate::terminal::CommandParser;
+
+/// Раскрывает ссылки на историю. Возвращает None, если в строке нечего раскрывать
+pub fn expand(input: &str, history: &CommandHistory, parser: &CommandParser) -> Result<Option<String>, String> {
+    // !rust, !python и т.д. - префиксы интерпретаторов, а не ссылки на историю
+    if parser.has_bang_prefix(input) {
+        return Ok(None);
+    }
+
+    if let Some(rest) = input.strip_prefix('^') {
+        return quick_substitution(rest, history).map(Some);
+    }
+
+    let chars: Vec<char> = input.chars().collect();
+    let mut result = String::new();
+    let mut changed = false;
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '\'' if !in_double => in_single = !in_single,
+            '"' if !in_single => in_double = !in_double,
+            '\\' if !in_single && i + 1 < chars.len() => {
+                // \! остается экранированным для лексера
+                result.push(c);
+                result.push(chars[i + 1]);
+                i += 2;
+                continue;
+            }
+            '!' if !in_single => {
+                if let Some((replacement, consumed)) = parse_reference(&chars[i + 1..], history)? {
+                    result.push_str(&replacement);
+                    changed = true;
+                    i += 1 + consumed;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        result.push(c);
+        i += 1;
+    }
+
+    Ok(if changed { Some(result) } else { None })
+}
+
+/// ^old^new^ - заменить первое вхождение в предыдущей команде
+fn quick_substitution(rest: &str, history: &CommandHistory) -> Result<String, String> {
+    let mut parts = rest.splitn(3, '^');
+    let old = parts.next().unwrap_or("");
+    let new = parts.next().unwrap_or("");
+    let tail = parts.next().unwrap_or("");
+
+    let previous = last_command(history)?;
+    if old.is_empty() || !previous.contains(old) {
+        return Err(format!("^{}^{}: подстановка не удалась", old, new));
+    }
+    Ok(format!("{}{}", previous.replacen(old, new, 1), tail))
+}
+
+fn last_command(history: &CommandHistory) -> Result<String, String> {
+    history.len()
+        .checked_sub(1)
+        .and_then(|index| history.get_by_index(index))
+        .cloned()
+        .ok_or_else(|| "!!: событие не найдено".to_string())
+}
+
+/// Разбирает ссылку после !. Возвращает текст подстановки и число
+/// прочитанных символов, или None, если ! надо оставить как есть
+fn parse_reference(rest: &[char], history: &CommandHistory) -> Result<Option<(String, usize)>, String> {
+    let first = match rest.first() {
+        None => return Ok(None),
+        Some(c) if c.is_whitespace() || matches!(c, '=' | '(' | '"') => return Ok(None),
+        Some(&c) => c,
+    };
+
+    let mut pos;
+    let command = match first {
+        '!' => {
+            pos = 1;
+            last_command(history)?
+        }
+        // !$, !^, !*, !:n - слова предыдущей команды
+        '$' | '^' | '*' | ':' => {
+            pos = 0;
+            last_command(history)?
+        }
+        c if c.is_ascii_digit() || (c == '-' && rest.get(1).is_some_and(|d| d.is_ascii_digit())) => {
+            pos = if c == '-' { 1 } else { 0 };
+            while pos < rest.len() && rest[pos].is_ascii_digit() {
+                pos += 1;
+            }
+            let text: String = rest[..pos].iter().collect();
+            let number: i64 = text.parse().map_err(|_| format!("!{}: событие не найдено", text))?;
+            // !n - номер из вывода history (с 1), !-n - n-я команда с конца
+            let index = if number < 0 {
+                history.len() as i64 + number
+            } else {
+                number - 1
+            };
+            usize::try_from(index).ok()
+                .and_then(|index| history.get_by_index(index))
+                .cloned()
+                .ok_or_else(|| format!("!{}: событие не найдено", text))?
+        }
+        '?' => {
+            let end = rest[1..].iter().position(|&c| c == '?').map(|p| p + 1);
+            let needle: String = rest[1..end.unwrap_or(rest.len())].iter().collect();
+            pos = end.map_or(rest.len(), |end| end + 1);
+            find_recent(history, |command| command.contains(&needle))
+                .ok_or_else(|| format!("!?{}: событие не найдено", needle))?
+        }
+        _ => {
+            pos = 0;
+            while pos < rest.len() && !rest[pos].is_whitespace() && !matches!(rest[pos], ':' | '"' | '\'' | ';' | '|' | '&') {
+                pos += 1;
+            }
+            let prefix: String = rest[..pos].iter().collect();
+            find_recent(history, |command| command.starts_with(&prefix))
+                .ok_or_else(|| format!("!{}: событие не найдено", prefix))?
+        }
+    };
+
+    // Указатель слов: !$, !^, !* без двоеточия или :n, :n-m, :$ и т.д.
+    let designator_start = match rest.get(pos) {
+        Some('$') | Some('^') | Some('*') if pos == 0 => Some(pos),
+        Some(':') if rest.get(pos + 1).is_some_and(|c| c.is_ascii_digit() || matches!(c, '$' | '^' | '*' | '-')) => Some(pos + 1),
+        _ => None,
+    };
+
+    match designator_start {
+        Some(start) => {
+            let (words, consumed) = select_words(&rest[start..], &split_words(&command))
+                .ok_or_else(|| format!("!{}: неверный указатель слова", rest.iter().collect::<String>()))?;
+            Ok(Some((words, start + consumed)))
+        }
+        None => Ok(Some((command, pos))),
+    }
+}
+
+fn find_recent(history: &CommandHistory, predicate: impl Fn(&str) -> bool) -> Option<String> {
+    (0..history.len())
+        .rev()
+        .filter_map(|index| history.get_by_index(index))
+        .find(|command| predicate(command))
+        .cloned()
+}
+
+/// Разбивает команду на слова с учетом кавычек, сохраняя исходный текст
+fn split_words(command: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+
+    for c in command.chars() {
+        match quote {
+            Some(q) => {
+                current.push(c);
+                if c == q {
+                    quote = None;
+                }
+            }
+            None if c.is_whitespace() => {
+                if !current.is_empty() {
+                    words.push(std::mem::take(&mut current));
+                }
+            }
+            None => {
+                if c == '\'' || c == '"' {
+                    quote = Some(c);
+                }
+                current.push(c);
+            }
+        }
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Выбирает слова по указателю (n, ^, $, *, n-m, n-, n*, -m).
+/// Возвращает выбранные слова и число прочитанных символов
+fn select_words(spec: &[char], words: &[String]) -> Option<(String, usize)> {
+    let last = words.len().checked_sub(1)?;
+
+    let read_number = |from: usize| -> (Option<usize>, usize) {
+        let mut end = from;
+        while end < spec.len() && spec[end].is_ascii_digit() {
+            end += 1;
+        }
+        let number = spec[from..end].iter().collect::<String>().parse().ok();
+        (number, end)
+    };
+    let read_bound = |from: usize| -> (Option<usize>, usize) {
+        match spec.get(from) {
+            Some('^') => (Some(1), from + 1),
+            Some('$') => (Some(last), from + 1),
+            _ => read_number(from),
+        }
+    };
+
+    let (range, consumed) = match spec.first()? {
+        '*' => ((1, last), 1),
+        '-' => {
+            let (end, consumed) = read_bound(1);
+            ((0, end?), consumed)
+        }
+        _ => {
+            let (start, after) = read_bound(0);
+            let start = start?;
+            match spec.get(after) {
+                Some('*') => ((start, last), after + 1),
+                Some('-') => match read_bound(after + 1) {
+                    (Some(end), consumed) => ((start, end), consumed),
+                    // n- как в bash: до предпоследнего слова
+                    (None, consumed) => ((start, last.saturating_sub(1)), consumed),
+                },
+                _ => ((start, start), after),
+            }
+        }
+    };
+
+    let (start, end) = range;
+    if start > end {
+        // !* для команды без аргументов дает пустую строку
+        return if spec.first() == Some(&'*') { Some((String::new(), consumed)) } else { None };
+    }
+    if end > last {
+        return None;
+    }
+    Some((words[start..=end].join(" "), consumed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history(commands: &[&str]) -> CommandHistory {
+        let mut history = CommandHistory::new(100);
+        for command in commands {
+            history.add(command.to_string());
+        }
+        history
+    }
+
+    fn expand(input: &str, history: &CommandHistory) -> Result<Option<String>, String> {
+        super::expand(input, history, &CommandParser::new())
+    }
+
+    fn expanded(input: &str, history: &CommandHistory) -> String {
+        expand(input, history).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_event_designators() {
+        let h = history(&["ls -la", "git status", "make build"]);
+        assert_eq!(expanded("sudo !!", &h), "sudo make build");
+        assert_eq!(expanded("!1", &h), "ls -la");
+        assert_eq!(expanded("!-2", &h), "git status");
+        assert_eq!(expanded("!gi", &h), "git status");
+        assert_eq!(expanded("!?stat?", &h), "git status");
+        assert!(expand("!nope", &h).is_err());
+    }
+
+    #[test]
+    fn test_word_designators() {
+        let h = history(&["cp 'my file.txt' /tmp/dest extra"]);
+        assert_eq!(expanded("vim !$", &h), "vim extra");
+        assert_eq!(expanded("cat !^", &h), "cat 'my file.txt'");
+        assert_eq!(expanded("echo !*", &h), "echo 'my file.txt' /tmp/dest extra");
+        assert_eq!(expanded("echo !!:2", &h), "echo /tmp/dest");
+        assert_eq!(expanded("echo !!:0-1", &h), "echo cp 'my file.txt'");
+        assert_eq!(expanded("echo !1:2*", &h), "echo /tmp/dest extra");
+        assert!(expand("echo !!:9", &h).is_err());
+    }
+
+    #[test]
+    fn test_quick_substitution() {
+        let h = history(&["git chekout main"]);
+        assert_eq!(expanded("^chekout^checkout", &h), "git checkout main");
+        assert!(expand("^absent^x", &h).is_err());
+    }
+
+    #[test]
+    fn test_literal_bang() {
+        let h = history(&["ls"]);
+        assert_eq!(expand("echo 'hi!!' ! x", &h).unwrap(), None);
+        assert_eq!(expand("echo \\!!", &h).unwrap(), None);
+        assert_eq!(expand("!python print('!!')", &h).unwrap(), None);
+        assert_eq!(expand("!rust", &h).unwrap(), None);
+    }
+}
@@ -0,0 +1,183 @@
+//! Нечеткий поиск по истории команд: общий для Ctrl+R в текстовом режиме
+//! и панели истории в псевдографическом интерфейсе
+
+use crate::terminal::CommandHistory;
+
+/// Найденная команда истории
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchMatch {
+    /// Индекс записи в истории
+    pub index: usize,
+    pub command: String,
+    /// Позиции (в символах) совпавших символов для подсветки
+    pub positions: Vec<usize>,
+    pub score: f64,
+}
+
+const MATCH_SCORE: i64 = 16;
+const CONSECUTIVE_BONUS: i64 = 15;
+const BOUNDARY_BONUS: i64 = 10;
+const GAP_PENALTY: i64 = 1;
+const MAX_GAP_PENALTY: i64 = 10;
+
+fn is_boundary(prev: Option<char>) -> bool {
+    match prev {
+        None => true,
+        Some(c) => matches!(c, ' ' | '/' | '-' | '_' | '.' | '|' | ';' | '=' | '\''),
+    }
+}
+
+fn chars_equal(a: char, b: char, case_sensitive: bool) -> bool {
+    if case_sensitive {
+        a == b
+    } else {
+        a.to_lowercase().eq(b.to_lowercase())
+    }
+}
+
+/// Символы запроса должны встречаться в тексте по порядку. Возвращает
+/// оценку и позиции совпадений. Регистр учитывается, только если
+/// в запросе есть заглавные буквы
+pub fn fuzzy_match(query: &str, text: &str) -> Option<(i64, Vec<usize>)> {
+    let query: Vec<char> = query.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    if query.is_empty() {
+        return Some((0, Vec::new()));
+    }
+    let case_sensitive = query.iter().any(|c| c.is_uppercase());
+
+    // Жадный проход от каждого возможного начала, выбирается лучший
+    let mut best: Option<(i64, Vec<usize>)> = None;
+    for start in 0..text.len() {
+        if !chars_equal(text[start], query[0], case_sensitive) {
+            continue;
+        }
+
+        let mut positions = vec![start];
+        let mut pos = start + 1;
+        for &q in &query[1..] {
+            while pos < text.len() && !chars_equal(text[pos], q, case_sensitive) {
+                pos += 1;
+            }
+            if pos == text.len() {
+                break;
+            }
+            positions.push(pos);
+            pos += 1;
+        }
+        if positions.len() < query.len() {
+            // Дальше совпадений от более поздних начал тоже не будет
+            break;
+        }
+
+        let mut score = 0;
+        for (i, &position) in positions.iter().enumerate() {
+            score += MATCH_SCORE;
+            if is_boundary(position.checked_sub(1).map(|p| text[p])) {
+                score += BOUNDARY_BONUS;
+            }
+            if i > 0 {
+                let gap = (position - positions[i - 1] - 1) as i64;
+                if gap == 0 {
+                    score += CONSECUTIVE_BONUS;
+                } else {
+                    score -= (gap * GAP_PENALTY).min(MAX_GAP_PENALTY);
+                }
+            }
+        }
+
+        if best.as_ref().is_none_or(|(best_score, _)| score > *best_score) {
+            best = Some((score, positions));
+        }
+    }
+    best
+}
+
+/// Ищет команды в истории и сортирует по совпадению, частоте,
+/// давности и директории запуска
+pub fn search(history: &CommandHistory, query: &str, cwd: &str) -> Vec<SearchMatch> {
+    let entries = history.entries();
+    let total = entries.len().max(1) as f64;
+
+    let mut matches: Vec<SearchMatch> = entries.iter()
+        .enumerate()
+        .filter_map(|(index, entry)| {
+            let (match_score, positions) = fuzzy_match(query, &entry.command)?;
+            let frequency = (history.frequency(&entry.command) as f64 + 1.0).ln() * 10.0;
+            let recency = (index + 1) as f64 / total * 20.0;
+            let directory = if entry.cwd == cwd { 15.0 } else { 0.0 };
+            Some(SearchMatch {
+                index,
+                command: entry.command.clone(),
+                positions,
+                score: match_score as f64 + frequency + recency + directory,
+            })
+        })
+        .collect();
+
+    matches.sort_by(|a, b| {
+        b.score.partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.index.cmp(&a.index))
+    });
+    matches
+}
+
+/// Текст с подсвеченными совпадениями (ANSI)
+pub fn highlight(command: &str, positions: &[usize]) -> String {
+    let mut result = String::new();
+    for (i, ch) in command.chars().enumerate() {
+        if positions.contains(&i) {
+            result.push_str(&format!("\x1b[1;33m{}\x1b[0m", ch));
+        } else {
+            result.push(ch);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::history::HistoryEntry;
+
+    #[test]
+    fn test_fuzzy_match_positions() {
+        let (_, positions) = fuzzy_match("gco", "git checkout main").unwrap();
+        assert_eq!(positions, vec![0, 4, 9]);
+        assert!(fuzzy_match("xyz", "git status").is_none());
+        assert!(fuzzy_match("GS", "git status").is_none());
+        assert!(fuzzy_match("gs", "Git Status").is_some());
+    }
+
+    #[test]
+    fn test_consecutive_match_scores_higher() {
+        let (tight, _) = fuzzy_match("make", "make build").unwrap();
+        let (loose, _) = fuzzy_match("make", "mv a ke").unwrap();
+        assert!(tight > loose);
+    }
+
+    #[test]
+    fn test_ranking_prefers_frequent_and_local() {
+        let mut history = CommandHistory::new(100);
+        for _ in 0..5 {
+            history.add("cargo build".to_string());
+        }
+        let mut entry = HistoryEntry::new("cargo bench");
+        entry.cwd = "/elsewhere".to_string();
+        history.add_entry(entry);
+        let mut entry = HistoryEntry::new("cargo bloat");
+        entry.cwd = "/project".to_string();
+        history.add_entry(entry);
+
+        let results = search(&history, "cb", "/project");
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].command, "cargo bloat");
+        assert_eq!(results[2].command, "cargo bench");
+    }
+
+    #[test]
+    fn test_highlight() {
+        assert_eq!(highlight("ab", &[1]), "a\x1b[1;33mb\x1b[0m");
+    }
+}
@@ -0,0 +1,630 @@
+//! Управление заданиями: фоновые конвейеры (cmd &), таблица заданий,
+//! jobs/fg/bg/kill/wait и остановка по Ctrl+Z (SIGTSTP)
+//!
+//! Каждый конвейер запускается в своей группе процессов. Активное задание
+//! получает терминал через tcsetpgrp, поэтому Ctrl+C и Ctrl+Z приходят ему,
+//! а не терминалу. Сам терминал игнорирует SIGTSTP, SIGTTIN и SIGTTOU
+
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Instant;
+use crate::terminal::{ExitStatus, ResourceUsage};
+use crate::terminal::pipeline::PipelineOutput;
+use crate::terminal::pty::{self, JobTerminal};
+use crate::terminal::signals;
+
+/// Встроенные команды управления заданиями
+pub const COMMANDS: &[&str] = &["jobs", "fg", "bg", "kill", "wait"];
+
+/// Состояние одного процесса конвейера
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessStatus {
+    Running,
+    Stopped,
+    Exited(i32),
+    /// Завершен сигналом
+    Killed(i32),
+}
+
+impl ProcessStatus {
+    fn is_finished(self) -> bool {
+        matches!(self, ProcessStatus::Exited(_) | ProcessStatus::Killed(_))
+    }
+}
+
+/// Процесс конвейера. pid отсутствует, если процесс не удалось запустить
+#[derive(Debug, Clone, PartialEq)]
+pub struct Process {
+    pub pid: Option<i32>,
+    pub status: ProcessStatus,
+}
+
+/// Состояние задания целиком
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobStatus {
+    Running,
+    Stopped,
+    /// Значение $? последней стадии
+    Done(i32),
+}
+
+/// Вывод задания переднего плана: показывается по мере поступления
+/// и копируется для истории и UI, stdout и stderr - раздельно
+pub struct Capture {
+    /// Потоки, копирующие вывод из каналов в терминал (вывод псевдотерминалов
+    /// копирует JobTable)
+    pub readers: Vec<JoinHandle<()>>,
+    pub stdout: Arc<Mutex<Vec<u8>>>,
+    pub stderr: Arc<Mutex<Vec<u8>>>,
+    /// Ошибки запуска стадий (команда не найдена и т.п.) - еще не показаны
+    pub spawn_errors: String,
+}
+
+impl Capture {
+    /// Дожидается конца вывода и возвращает stdout, stderr и ошибки запуска.
+    /// Вызывать только после завершения процессов
+    pub fn finish(self) -> (String, String, String) {
+        for reader in self.readers {
+            let _ = reader.join();
+        }
+        let text = |buffer: &Mutex<Vec<u8>>| pty::clean_transcript(&buffer.lock().unwrap_or_else(|e| e.into_inner()));
+        (text(&self.stdout), text(&self.stderr), self.spawn_errors)
+    }
+}
+
+/// Задание: конвейер, запущенный в отдельной группе процессов
+pub struct Job {
+    pub id: usize,
+    pub pgid: Option<i32>,
+    pub command: String,
+    pub processes: Vec<Process>,
+    /// Вывод задания, запущенного на переднем плане (для фоновых - None,
+    /// они пишут прямо в терминал)
+    capture: Option<Capture>,
+    /// Псевдотерминалы задания в интерактивном режиме
+    terminal: Option<JobTerminal>,
+    started: Instant,
+    /// Время и память завершившихся процессов (из wait4)
+    usage: ResourceUsage,
+}
+
+impl Job {
+    pub fn new(command: String, pgid: Option<i32>, processes: Vec<Process>, capture: Option<Capture>) -> Self {
+        Self { id: 0, pgid, command, processes, capture, terminal: None, started: Instant::now(), usage: ResourceUsage::default() }
+    }
+    
+    /// Вывод задания идет через псевдотерминалы
+    pub fn with_terminal(mut self, terminal: JobTerminal) -> Self {
+        self.terminal = Some(terminal);
+        self
+    }
+
+    pub fn status(&self) -> JobStatus {
+        if self.processes.iter().any(|p| p.status == ProcessStatus::Stopped) {
+            return JobStatus::Stopped;
+        }
+        if self.processes.iter().any(|p| p.status == ProcessStatus::Running) {
+            return JobStatus::Running;
+        }
+        JobStatus::Done(self.exit_status().code())
+    }
+
+    /// Статус конвейера - статус последней стадии
+    fn exit_status(&self) -> ExitStatus {
+        match self.processes.last().map(|p| p.status) {
+            Some(ProcessStatus::Exited(code)) => ExitStatus::Code(code),
+            Some(ProcessStatus::Killed(signal)) => ExitStatus::Signal(signal),
+            _ => ExitStatus::Code(0),
+        }
+    }
+
+    /// Результат завершенного задания с собранным выводом и ресурсами
+    fn into_output(mut self) -> PipelineOutput {
+        let status = match self.status() {
+            JobStatus::Done(_) => self.exit_status(),
+            _ => ExitStatus::Code(1),
+        };
+        let (stdout, stderr, errors) = self.capture.take().map(Capture::finish).unwrap_or_default();
+        let usage = ResourceUsage { wall: self.started.elapsed(), ..self.usage };
+        PipelineOutput { status, stdout, stderr, errors, usage }
+    }
+
+    fn set_status(&mut self, pid: i32, status: ProcessStatus) {
+        if let Some(process) = self.processes.iter_mut().find(|p| p.pid == Some(pid)) {
+            process.status = status;
+        }
+    }
+
+    /// waitpid с учетом ресурсов: время и память завершившегося процесса
+    /// добавляются к заданию. false - состояние не изменилось (WNOHANG)
+    fn wait_process(&mut self, pid: i32, flags: i32) -> std::io::Result<bool> {
+        let mut status = 0;
+        let mut rusage: libc::rusage = unsafe { std::mem::zeroed() };
+        match unsafe { libc::wait4(pid, &mut status, flags, &mut rusage) } {
+            -1 => Err(std::io::Error::last_os_error()),
+            0 => Ok(false),
+            _ => {
+                let status = decode_status(status);
+                // У остановленного процесса rusage неполный - учитываем только завершение
+                if status.is_finished() {
+                    self.usage.add(&ResourceUsage::from_rusage(&rusage));
+                }
+                self.set_status(pid, status);
+                Ok(true)
+            }
+        }
+    }
+
+    /// pid процессов, которые еще выполняются или остановлены
+    fn live_pids(&self) -> Vec<i32> {
+        self.processes.iter()
+            .filter(|p| !p.status.is_finished())
+            .filter_map(|p| p.pid)
+            .collect()
+    }
+
+    fn continue_processes(&mut self) -> Result<(), String> {
+        if let Some(pgid) = self.pgid {
+            if unsafe { libc::kill(-pgid, libc::SIGCONT) } != 0 {
+                return Err(format!("не удалось продолжить задание: {}", std::io::Error::last_os_error()));
+            }
+        }
+        for process in &mut self.processes {
+            if process.status == ProcessStatus::Stopped {
+                process.status = ProcessStatus::Running;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Чем закончилось ожидание задания переднего плана
+pub enum Foreground {
+    Finished(PipelineOutput),
+    /// Задание остановлено (Ctrl+Z) и помещено в таблицу; строка - уведомление
+    Stopped(String),
+}
+
+/// Таблица заданий
+pub struct JobTable {
+    /// Задания в порядке последней активности: последнее - текущее (%+)
+    jobs: Vec<Job>,
+    /// Группа процессов терминала; None - управление заданиями недоступно
+    /// (ввод не с терминала)
+    shell_pgid: Option<i32>,
+}
+
+impl JobTable {
+    pub fn new() -> Self {
+        Self { jobs: Vec::new(), shell_pgid: None }
+    }
+
+    /// Включает управление заданиями, если ввод идет с терминала: терминал
+    /// становится лидером своей группы и перестает реагировать на Ctrl+Z
+    pub fn enable(&mut self) {
+        unsafe {
+            if libc::isatty(libc::STDIN_FILENO) == 0 {
+                return;
+            }
+            libc::signal(libc::SIGTSTP, libc::SIG_IGN);
+            libc::signal(libc::SIGTTIN, libc::SIG_IGN);
+            libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+
+            // Лидер сессии не может сменить группу - тогда он уже лидер своей
+            let pid = libc::getpid();
+            libc::setpgid(pid, pid);
+            let pgid = libc::getpgrp();
+            libc::tcsetpgrp(libc::STDIN_FILENO, pgid);
+            self.shell_pgid = Some(pgid);
+        }
+    }
+
+    /// Может ли задание переднего плана получить терминал
+    pub fn job_control(&self) -> bool {
+        self.shell_pgid.is_some()
+    }
+
+    /// Добавляет фоновое задание и возвращает уведомление "[1] pid"
+    pub fn add_background(&mut self, job: Job) -> String {
+        let id = self.insert(job);
+        let pgid = self.jobs.last().and_then(|job| job.pgid).unwrap_or(0);
+        format!("[{}] {}", id, pgid)
+    }
+
+    fn insert(&mut self, mut job: Job) -> usize {
+        job.id = self.jobs.iter().map(|job| job.id).max().unwrap_or(0) + 1;
+        let id = job.id;
+        self.jobs.push(job);
+        id
+    }
+
+    /// Ждет задание переднего плана. Остановленное задание попадает в таблицу
+    pub fn foreground(&mut self, mut job: Job) -> Result<Foreground, String> {
+        signals::set_foreground(job.pgid);
+        let result = match job.terminal.take() {
+            // Вывод идет через псевдотерминалы. Если задание открыло на нем свою
+            // сессию, терминал оболочки не передается - ввод пересылается ему
+            Some(mut terminal) => {
+                let (stdout, stderr) = job.capture.as_ref()
+                    .map(|capture| (capture.stdout.clone(), capture.stderr.clone()))
+                    .unwrap_or_default();
+                if !terminal.owns_input {
+                    self.give_terminal(job.pgid);
+                }
+                let result = terminal.pump(&stdout, &stderr, || {
+                    reap_job(&mut job, libc::WNOHANG | libc::WUNTRACED);
+                    job.status() != JobStatus::Running
+                });
+                if !terminal.owns_input {
+                    self.give_terminal(self.shell_pgid);
+                }
+                job.terminal = Some(terminal);
+                result
+            }
+            None => {
+                self.give_terminal(job.pgid);
+                let result = wait_job(&mut job, true);
+                self.give_terminal(self.shell_pgid);
+                result
+            }
+        };
+        signals::set_foreground(None);
+        result?;
+
+        if job.status() == JobStatus::Stopped {
+            if job.id == 0 {
+                self.insert(job);
+            } else {
+                self.jobs.push(job);
+            }
+            let job = self.jobs.last().expect("задание только что добавлено");
+            return Ok(Foreground::Stopped(self.format_job(job, false)));
+        }
+        Ok(Foreground::Finished(job.into_output()))
+    }
+
+    fn give_terminal(&self, pgid: Option<i32>) {
+        if let (Some(_), Some(pgid)) = (self.shell_pgid, pgid) {
+            unsafe {
+                libc::tcsetpgrp(libc::STDIN_FILENO, pgid);
+            }
+        }
+    }
+
+    /// Находит задание по спецификации: %n, %%, %+, %-, %строка, %?строка
+    /// или номер без %. Возвращает индекс в таблице
+    fn find(&self, spec: Option<&str>) -> Result<usize, String> {
+        let no_job = |spec: &str| format!("{}: нет такого задания", spec);
+        let spec = match spec {
+            None | Some("%%") | Some("%+") | Some("%") => {
+                return self.jobs.len().checked_sub(1).ok_or_else(|| "нет текущего задания".to_string());
+            }
+            Some(spec) => spec,
+        };
+        if spec == "%-" {
+            return self.jobs.len().checked_sub(2).ok_or_else(|| no_job(spec));
+        }
+
+        let body = spec.strip_prefix('%').unwrap_or(spec);
+        if let Ok(id) = body.parse::<usize>() {
+            return self.jobs.iter().position(|job| job.id == id).ok_or_else(|| no_job(spec));
+        }
+        let matches: Vec<usize> = self.jobs.iter()
+            .enumerate()
+            .filter(|(_, job)| match body.strip_prefix('?') {
+                Some(text) => job.command.contains(text),
+                None => job.command.starts_with(body),
+            })
+            .map(|(index, _)| index)
+            .collect();
+        match matches.as_slice() {
+            [index] => Ok(*index),
+            [] => Err(no_job(spec)),
+            _ => Err(format!("{}: неоднозначное задание", spec)),
+        }
+    }
+
+    /// Проверяет фоновые задания без ожидания
+    pub fn reap(&mut self) {
+        for job in &mut self.jobs {
+            reap_job(job, libc::WNOHANG | libc::WUNTRACED | libc::WCONTINUED);
+        }
+    }
+
+    /// Уведомления о завершенных заданиях; такие задания удаляются из таблицы
+    pub fn take_notifications(&mut self) -> Vec<String> {
+        self.reap();
+        let mut notices = Vec::new();
+        let mut index = 0;
+        while index < self.jobs.len() {
+            if matches!(self.jobs[index].status(), JobStatus::Done(_)) {
+                notices.push(self.format_job(&self.jobs[index], false));
+                // Вывод задания уже показан, остаются только ошибки запуска
+                let output = self.jobs.remove(index).into_output();
+                if !output.errors.is_empty() {
+                    notices.push(output.errors.trim_end().to_string());
+                }
+            } else {
+                index += 1;
+            }
+        }
+        notices
+    }
+
+    /// Строка для jobs: [1]+  Выполняется    sleep 100 &
+    fn format_job(&self, job: &Job, with_pid: bool) -> String {
+        let position = self.jobs.iter().position(|j| j.id == job.id);
+        let marker = match position {
+            Some(p) if p + 1 == self.jobs.len() => '+',
+            Some(p) if p + 2 == self.jobs.len() => '-',
+            _ => ' ',
+        };
+        let (state, suffix) = match job.status() {
+            JobStatus::Running => ("Выполняется".to_string(), " &"),
+            JobStatus::Stopped => ("Остановлено".to_string(), ""),
+            JobStatus::Done(0) => ("Завершено".to_string(), ""),
+            JobStatus::Done(code) => (format!("Завершено ({})", code), ""),
+        };
+        let pid = if with_pid { format!("{} ", job.pgid.unwrap_or(0)) } else { String::new() };
+        format!("[{}]{}  {}{:<16}{}{}", job.id, marker, pid, state, job.command, suffix)
+    }
+
+    /// jobs [-l]
+    pub fn jobs(&mut self, args: &[String]) -> Result<String, String> {
+        self.reap();
+        let with_pid = args.iter().any(|arg| arg == "-l" || arg == "-p");
+        let mut sorted: Vec<&Job> = self.jobs.iter().collect();
+        sorted.sort_by_key(|job| job.id);
+        Ok(sorted.iter()
+            .map(|job| self.format_job(job, with_pid))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    /// fg [%n] - продолжает задание на переднем плане
+    pub fn fg(&mut self, args: &[String]) -> Result<Foreground, String> {
+        self.reap();
+        let index = self.find(args.first().map(String::as_str))?;
+        let mut job = self.jobs.remove(index);
+        println!("{}", job.command);
+
+        // Терминал отдается до SIGCONT, иначе задание сразу получит SIGTTIN.
+        // Задание на псевдотерминале сначала узнает новый размер окна
+        match &mut job.terminal {
+            Some(terminal) if terminal.owns_input => terminal.sync_size(),
+            Some(terminal) => {
+                terminal.sync_size();
+                self.give_terminal(job.pgid);
+            }
+            None => self.give_terminal(job.pgid),
+        }
+        if let Err(e) = job.continue_processes() {
+            self.give_terminal(self.shell_pgid);
+            self.jobs.insert(index, job);
+            return Err(e);
+        }
+        self.foreground(job)
+    }
+
+    /// bg [%n] - продолжает остановленное задание в фоне
+    pub fn bg(&mut self, args: &[String]) -> Result<String, String> {
+        self.reap();
+        let index = self.find(args.first().map(String::as_str))?;
+        if self.jobs[index].status() != JobStatus::Stopped {
+            return Err(format!("задание {} уже выполняется в фоне", self.jobs[index].id));
+        }
+        self.jobs[index].continue_processes()?;
+        // Продолженное задание становится текущим
+        let job = self.jobs.remove(index);
+        self.jobs.push(job);
+        let job = self.jobs.last().expect("задание только что добавлено");
+        Ok(format!("[{}]+ {} &", job.id, job.command))
+    }
+
+    /// kill [-s SIG | -SIG] %n|pid ... ; kill -l
+    pub fn kill(&mut self, args: &[String]) -> Result<String, String> {
+        let mut signal = libc::SIGTERM;
+        let mut targets = Vec::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if arg == "-l" {
+                return Ok(SIGNALS.iter().map(|(name, number)| format!("{:>2}) SIG{}", number, name)).collect::<Vec<_>>().join("\n"));
+            } else if arg == "-s" || arg == "-n" {
+                let name = iter.next().ok_or("ожидалось имя сигнала после -s")?;
+                signal = parse_signal(name).ok_or_else(|| format!("{}: неизвестный сигнал", name))?;
+            } else if let Some(name) = arg.strip_prefix('-').filter(|_| targets.is_empty()) {
+                signal = parse_signal(name).ok_or_else(|| format!("{}: неизвестный сигнал", name))?;
+            } else {
+                targets.push(arg.clone());
+            }
+        }
+        if targets.is_empty() {
+            return Err("использование: kill [-s сигнал | -сигнал] %задание | pid ...".to_string());
+        }
+
+        let mut errors = Vec::new();
+        for target in targets {
+            let pid = if target.starts_with('%') {
+                match self.find(Some(&target)) {
+                    Ok(index) => match self.jobs[index].pgid {
+                        // Сигнал всей группе процессов задания
+                        Some(pgid) => -pgid,
+                        None => continue,
+                    },
+                    Err(e) => {
+                        errors.push(e);
+                        continue;
+                    }
+                }
+            } else {
+                match target.parse::<i32>() {
+                    Ok(pid) => pid,
+                    Err(_) => {
+                        errors.push(format!("{}: аргумент должен быть pid или %задание", target));
+                        continue;
+                    }
+                }
+            };
+            if unsafe { libc::kill(pid, signal) } != 0 {
+                errors.push(format!("({}) - {}", target, std::io::Error::last_os_error()));
+            } else if signal == libc::SIGKILL || signal == libc::SIGTERM || signal == libc::SIGHUP {
+                // Остановленный процесс обработает сигнал только после SIGCONT
+                unsafe { libc::kill(pid, libc::SIGCONT) };
+            }
+        }
+
+        if errors.is_empty() { Ok(String::new()) } else { Err(errors.join("\n")) }
+    }
+
+    /// wait [%n ...] - ждет завершения фоновых заданий
+    pub fn wait(&mut self, args: &[String]) -> Result<(String, ExitStatus), String> {
+        self.reap();
+        let indices: Vec<usize> = if args.is_empty() {
+            (0..self.jobs.len())
+                .filter(|&index| self.jobs[index].status() == JobStatus::Running)
+                .collect()
+        } else {
+            args.iter().map(|arg| self.find(Some(arg))).collect::<Result<_, _>>()?
+        };
+
+        let mut ids: Vec<usize> = indices.iter().map(|&index| self.jobs[index].id).collect();
+        ids.dedup();
+        let mut status = ExitStatus::Code(0);
+        let mut output = Vec::new();
+        for id in ids {
+            let index = match self.jobs.iter().position(|job| job.id == id) {
+                Some(index) => index,
+                None => continue,
+            };
+            if self.jobs[index].status() == JobStatus::Stopped {
+                return Err(format!("задание {} остановлено", id));
+            }
+            let mut job = self.jobs.remove(index);
+            wait_job(&mut job, false)?;
+            let result = job.into_output();
+            status = result.status;
+            if !result.errors.is_empty() {
+                output.push(result.errors.trim_end().to_string());
+            }
+        }
+        Ok((output.join("\n"), status))
+    }
+}
+
+/// Проверяет процессы задания без ожидания
+fn reap_job(job: &mut Job, flags: i32) {
+    for pid in job.live_pids() {
+        let _ = job.wait_process(pid, flags);
+    }
+}
+
+/// Ждет все процессы задания. С until_stopped ожидание прерывается
+/// остановкой (Ctrl+Z), иначе - только завершением
+fn wait_job(job: &mut Job, until_stopped: bool) -> Result<(), String> {
+    let flags = if until_stopped { libc::WUNTRACED } else { 0 };
+    for pid in job.live_pids() {
+        loop {
+            match job.wait_process(pid, flags) {
+                Ok(_) => break,
+                Err(error) if error.kind() == std::io::ErrorKind::Interrupted => continue,
+                // Процесс уже собран (например, reap) - считаем завершенным
+                Err(error) if error.raw_os_error() == Some(libc::ECHILD) => {
+                    job.set_status(pid, ProcessStatus::Exited(0));
+                    break;
+                }
+                Err(error) => return Err(format!("Ошибка ожидания процесса: {}", error)),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn decode_status(status: i32) -> ProcessStatus {
+    if libc::WIFSTOPPED(status) {
+        ProcessStatus::Stopped
+    } else if libc::WIFCONTINUED(status) {
+        ProcessStatus::Running
+    } else if libc::WIFSIGNALED(status) {
+        ProcessStatus::Killed(libc::WTERMSIG(status))
+    } else {
+        ProcessStatus::Exited(libc::WEXITSTATUS(status))
+    }
+}
+
+const SIGNALS: &[(&str, i32)] = &[
+    ("HUP", libc::SIGHUP),
+    ("INT", libc::SIGINT),
+    ("QUIT", libc::SIGQUIT),
+    ("KILL", libc::SIGKILL),
+    ("USR1", libc::SIGUSR1),
+    ("USR2", libc::SIGUSR2),
+    ("TERM", libc::SIGTERM),
+    ("CONT", libc::SIGCONT),
+    ("STOP", libc::SIGSTOP),
+    ("TSTP", libc::SIGTSTP),
+];
+
+/// Сигнал по имени (TERM, SIGTERM, term) или номеру
+pub fn parse_signal(name: &str) -> Option<i32> {
+    if let Ok(number) = name.parse::<i32>() {
+        return (0..65).contains(&number).then_some(number);
+    }
+    let upper = name.to_uppercase();
+    let upper = upper.strip_prefix("SIG").unwrap_or(&upper);
+    SIGNALS.iter().find(|(signal, _)| *signal == upper).map(|(_, number)| *number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(command: &str, statuses: &[ProcessStatus]) -> Job {
+        let processes = statuses.iter()
+            .map(|&status| Process { pid: None, status })
+            .collect();
+        Job::new(command.to_string(), None, processes, None)
+    }
+
+    #[test]
+    fn test_job_status() {
+        use ProcessStatus::*;
+        assert_eq!(job("a", &[Exited(0), Running]).status(), JobStatus::Running);
+        assert_eq!(job("a", &[Stopped, Running]).status(), JobStatus::Stopped);
+        assert_eq!(job("a", &[Exited(1), Exited(3)]).status(), JobStatus::Done(3));
+        assert_eq!(job("a", &[Exited(0), Killed(libc::SIGKILL)]).status(), JobStatus::Done(137));
+        assert_eq!(job("a", &[Killed(libc::SIGTERM)]).exit_status(), ExitStatus::Signal(libc::SIGTERM));
+    }
+
+    #[test]
+    fn test_find_job_spec() {
+        let mut table = JobTable::new();
+        table.insert(job("sleep 100", &[ProcessStatus::Running]));
+        table.insert(job("vim notes.txt", &[ProcessStatus::Stopped]));
+        table.insert(job("make all", &[ProcessStatus::Running]));
+
+        assert_eq!(table.find(None), Ok(2));
+        assert_eq!(table.find(Some("%-")), Ok(1));
+        assert_eq!(table.find(Some("%1")), Ok(0));
+        assert_eq!(table.find(Some("2")), Ok(1));
+        assert_eq!(table.find(Some("%vim")), Ok(1));
+        assert_eq!(table.find(Some("%?all")), Ok(2));
+        assert!(table.find(Some("%9")).is_err());
+    }
+
+    #[test]
+    fn test_jobs_listing() {
+        let mut table = JobTable::new();
+        table.insert(job("sleep 100", &[ProcessStatus::Running]));
+        table.insert(job("vim", &[ProcessStatus::Stopped]));
+        let listing = table.jobs(&[]).unwrap();
+        assert_eq!(listing, "[1]-  Выполняется     sleep 100 &\n[2]+  Остановлено     vim");
+    }
+
+    #[test]
+    fn test_parse_signal() {
+        assert_eq!(parse_signal("9"), Some(9));
+        assert_eq!(parse_signal("KILL"), Some(libc::SIGKILL));
+        assert_eq!(parse_signal("sigterm"), Some(libc::SIGTERM));
+        assert_eq!(parse_signal("BOGUS"), None);
+    }
+}
@@ -0,0 +1,280 @@
+//! Лексер командной строки: слова с учетом кавычек и операторы shell
+
+/// Вид кавычек, в которых находился фрагмент слова
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuoteKind {
+    None,
+    Single,
+    Double,
+}
+
+/// Фрагмент слова с одинаковым типом кавычек
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordPart {
+    pub text: String,
+    pub quote: QuoteKind,
+}
+
+/// Слово командной строки, собранное из фрагментов
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Word {
+    pub parts: Vec<WordPart>,
+}
+
+impl Word {
+    /// Создает слово из текста без кавычек
+    #[cfg(test)]
+    pub fn literal(text: &str) -> Self {
+        Self {
+            parts: vec![WordPart { text: text.to_string(), quote: QuoteKind::None }],
+        }
+    }
+
+    /// Открывает новый фрагмент, если последний другого типа
+    fn ensure_part(&mut self, quote: QuoteKind) {
+        if self.parts.last().is_none_or(|p| p.quote != quote) {
+            self.parts.push(WordPart { text: String::new(), quote });
+        }
+    }
+
+    fn push(&mut self, ch: char, quote: QuoteKind) {
+        self.ensure_part(quote);
+        if let Some(part) = self.parts.last_mut() {
+            part.text.push(ch);
+        }
+    }
+
+    /// Добавляет текст с указанным типом кавычек
+    pub fn push_str(&mut self, text: &str, quote: QuoteKind) {
+        self.ensure_part(quote);
+        if let Some(part) = self.parts.last_mut() {
+            part.text.push_str(text);
+        }
+    }
+
+    /// Текст слова без кавычек
+    pub fn to_plain(&self) -> String {
+        self.parts.iter().map(|p| p.text.as_str()).collect()
+    }
+
+    /// Есть ли в слове фрагменты в кавычках
+    pub fn is_quoted(&self) -> bool {
+        self.parts.iter().any(|p| p.quote != QuoteKind::None)
+    }
+}
+
+/// Вид перенаправления
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RedirectOp {
+    Input,          // <
+    Output,         // >
+    Append,         // >>
+    Duplicate(u32), // >&N
+    OutputAll,      // &>
+}
+
+/// Лексема командной строки
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    Word(Word),
+    Pipe,      // |
+    And,       // &&
+    Or,        // ||
+    Semicolon, // ; или перевод строки
+    Background, // &
+    Redirect { fd: Option<u32>, op: RedirectOp },
+}
+
+/// Разбивает строку на лексемы
+pub fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    let mut word = Word::default();
+    let mut in_word = false;
+
+    fn flush(tokens: &mut Vec<Token>, word: &mut Word, in_word: &mut bool) {
+        if *in_word {
+            tokens.push(Token::Word(std::mem::take(word)));
+            *in_word = false;
+        }
+    }
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            ' ' | '\t' => flush(&mut tokens, &mut word, &mut in_word),
+            '\n' | ';' => {
+                flush(&mut tokens, &mut word, &mut in_word);
+                tokens.push(Token::Semicolon);
+            }
+            '#' if !in_word => {
+                // Комментарий до конца строки
+                while let Some(&next) = chars.peek() {
+                    if next == '\n' {
+                        break;
+                    }
+                    chars.next();
+                }
+            }
+            '\'' => {
+                in_word = true;
+                word.ensure_part(QuoteKind::Single);
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c, QuoteKind::Single),
+                        None => return Err("Незакрытая одинарная кавычка".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                word.ensure_part(QuoteKind::Double);
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.peek() {
+                            Some(&c) if matches!(c, '"' | '\\' | '`') => {
+                                chars.next();
+                                word.push(c, QuoteKind::Double);
+                            }
+                            // Экранированный $ не должен раскрываться как переменная
+                            Some('$') => {
+                                chars.next();
+                                word.push('$', QuoteKind::Single);
+                            }
+                            _ => word.push('\\', QuoteKind::Double),
+                        },
+                        Some(c) => word.push(c, QuoteKind::Double),
+                        None => return Err("Незакрытая двойная кавычка".to_string()),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                match chars.next() {
+                    Some(c) => word.push(c, QuoteKind::Single),
+                    None => word.push('\\', QuoteKind::None),
+                }
+            }
+            '|' => {
+                flush(&mut tokens, &mut word, &mut in_word);
+                if chars.peek() == Some(&'|') {
+                    chars.next();
+                    tokens.push(Token::Or);
+                } else {
+                    tokens.push(Token::Pipe);
+                }
+            }
+            '&' => {
+                flush(&mut tokens, &mut word, &mut in_word);
+                match chars.peek() {
+                    Some('&') => {
+                        chars.next();
+                        tokens.push(Token::And);
+                    }
+                    Some('>') => {
+                        chars.next();
+                        tokens.push(Token::Redirect { fd: None, op: RedirectOp::OutputAll });
+                    }
+                    _ => tokens.push(Token::Background),
+                }
+            }
+            '<' | '>' => {
+                // Число непосредственно перед оператором - номер дескриптора (2>file)
+                let plain = word.to_plain();
+                let fd = if in_word && !word.is_quoted() && !plain.is_empty()
+                    && plain.chars().all(|c| c.is_ascii_digit())
+                {
+                    word = Word::default();
+                    in_word = false;
+                    plain.parse().ok()
+                } else {
+                    flush(&mut tokens, &mut word, &mut in_word);
+                    None
+                };
+
+                let op = if ch == '<' {
+                    RedirectOp::Input
+                } else {
+                    match chars.peek() {
+                        Some('>') => {
+                            chars.next();
+                            RedirectOp::Append
+                        }
+                        Some('&') => {
+                            chars.next();
+                            let mut digits = String::new();
+                            while let Some(&c) = chars.peek() {
+                                if !c.is_ascii_digit() {
+                                    break;
+                                }
+                                digits.push(c);
+                                chars.next();
+                            }
+                            let target = digits.parse()
+                                .map_err(|_| "Ожидался номер дескриптора после >&".to_string())?;
+                            RedirectOp::Duplicate(target)
+                        }
+                        _ => RedirectOp::Output,
+                    }
+                };
+                tokens.push(Token::Redirect { fd, op });
+            }
+            _ => {
+                in_word = true;
+                word.push(ch, QuoteKind::None);
+            }
+        }
+    }
+
+    flush(&mut tokens, &mut word, &mut in_word);
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(tokens: &[Token]) -> Vec<String> {
+        tokens.iter()
+            .filter_map(|t| match t {
+                Token::Word(w) => Some(w.to_plain()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_quotes_and_escapes() {
+        let tokens = tokenize(r#"echo "hello world" 'a b' c\ d"#).unwrap();
+        assert_eq!(words(&tokens), vec!["echo", "hello world", "a b", "c d"]);
+    }
+
+    #[test]
+    fn test_operators() {
+        let tokens = tokenize("a | b && c || d; e").unwrap();
+        assert_eq!(tokens.iter().filter(|t| **t == Token::Pipe).count(), 1);
+        assert!(tokens.contains(&Token::And));
+        assert!(tokens.contains(&Token::Or));
+        assert!(tokens.contains(&Token::Semicolon));
+    }
+
+    #[test]
+    fn test_background() {
+        let tokens = tokenize("sleep 10 & make &> log").unwrap();
+        assert_eq!(tokens.iter().filter(|t| **t == Token::Background).count(), 1);
+        assert!(tokens.contains(&Token::Redirect { fd: None, op: RedirectOp::OutputAll }));
+    }
+
+    #[test]
+    fn test_redirect_fd() {
+        let tokens = tokenize("make > build.log 2>&1").unwrap();
+        assert!(tokens.contains(&Token::Redirect { fd: None, op: RedirectOp::Output }));
+        assert!(tokens.contains(&Token::Redirect { fd: Some(2), op: RedirectOp::Duplicate(1) }));
+    }
+
+    #[test]
+    fn test_unterminated_quote() {
+        assert!(tokenize("echo 'oops").is_err());
+    }
+}
@@ -0,0 +1,788 @@
+//! Редактор строки ввода в raw-режиме: перемещение курсора, kill/yank
+//! в стиле emacs и навигация по истории
+
+use std::io::{self, IsTerminal, Write};
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    queue,
+    style::Print,
+    terminal::{self, ClearType},
+};
+use crate::terminal::CommandHistory;
+use crate::terminal::completion::{self, Candidate, CompletionEngine};
+use crate::terminal::history_search::{self, SearchMatch};
+
+/// Результат чтения строки
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadResult {
+    Line(String),
+    /// Ctrl+C - строка отменена
+    Interrupted,
+    /// Ctrl+D на пустой строке или конец ввода
+    Eof,
+    /// Ctrl+U - переход в псевдографический режим
+    SwitchToUi,
+}
+
+/// Содержимое строки и позиция курсора (в символах)
+#[derive(Debug, Clone, Default)]
+pub struct LineBuffer {
+    chars: Vec<char>,
+    cursor: usize,
+}
+
+impl LineBuffer {
+    pub fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
+    /// Заменяет содержимое строки, курсор ставится в конец
+    pub fn set_text(&mut self, text: &str) {
+        self.chars = text.chars().collect();
+        self.cursor = self.chars.len();
+    }
+
+    pub fn insert(&mut self, ch: char) {
+        self.chars.insert(self.cursor, ch);
+        self.cursor += 1;
+    }
+
+    pub fn insert_str(&mut self, text: &str) {
+        for ch in text.chars() {
+            self.insert(ch);
+        }
+    }
+
+    pub fn delete_backward(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.chars.remove(self.cursor);
+        }
+    }
+
+    pub fn delete_forward(&mut self) {
+        if self.cursor < self.chars.len() {
+            self.chars.remove(self.cursor);
+        }
+    }
+
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.chars.len());
+    }
+
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_end(&mut self) {
+        self.cursor = self.chars.len();
+    }
+
+    /// Начало текущего или предыдущего слова
+    fn word_start(&self) -> usize {
+        let mut pos = self.cursor;
+        while pos > 0 && !self.chars[pos - 1].is_alphanumeric() {
+            pos -= 1;
+        }
+        while pos > 0 && self.chars[pos - 1].is_alphanumeric() {
+            pos -= 1;
+        }
+        pos
+    }
+
+    /// Конец текущего или следующего слова
+    fn word_end(&self) -> usize {
+        let mut pos = self.cursor;
+        while pos < self.chars.len() && !self.chars[pos].is_alphanumeric() {
+            pos += 1;
+        }
+        while pos < self.chars.len() && self.chars[pos].is_alphanumeric() {
+            pos += 1;
+        }
+        pos
+    }
+
+    pub fn move_word_left(&mut self) {
+        self.cursor = self.word_start();
+    }
+
+    pub fn move_word_right(&mut self) {
+        self.cursor = self.word_end();
+    }
+
+    /// Удаляет текст до конца строки и возвращает его (Ctrl+K)
+    pub fn kill_to_end(&mut self) -> String {
+        self.chars.drain(self.cursor..).collect()
+    }
+
+    /// Удаляет слово перед курсором (Ctrl+W)
+    pub fn kill_word_backward(&mut self) -> String {
+        let start = self.word_start();
+        let killed = self.chars.drain(start..self.cursor).collect();
+        self.cursor = start;
+        killed
+    }
+
+    /// Удаляет слово после курсора (Alt+D)
+    pub fn kill_word_forward(&mut self) -> String {
+        let end = self.word_end();
+        self.chars.drain(self.cursor..end).collect()
+    }
+
+    /// Заменяет текст от позиции `start` до курсора
+    pub fn replace_before_cursor(&mut self, start: usize, text: &str) {
+        let start = start.min(self.cursor);
+        self.chars.splice(start..self.cursor, text.chars());
+        self.cursor = start + text.chars().count();
+    }
+
+    /// Меняет местами символы вокруг курсора (Ctrl+T)
+    pub fn transpose(&mut self) {
+        if self.chars.len() < 2 || self.cursor == 0 {
+            return;
+        }
+        if self.cursor == self.chars.len() {
+            self.cursor -= 1;
+        }
+        self.chars.swap(self.cursor - 1, self.cursor);
+        self.cursor += 1;
+    }
+}
+
+/// Включает raw-режим и выключает его при выходе из области видимости
+struct RawModeGuard;
+
+impl RawModeGuard {
+    fn enable() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Состояние поиска по истории (Ctrl+R)
+#[derive(Debug, Default)]
+struct SearchState {
+    query: String,
+    matches: Vec<SearchMatch>,
+    selected: usize,
+    /// Строка до начала поиска, восстанавливается по Esc
+    original: String,
+}
+
+impl SearchState {
+    fn refresh(&mut self, history: &CommandHistory) {
+        let cwd = std::env::current_dir()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.matches = history_search::search(history, &self.query, &cwd);
+        self.selected = 0;
+    }
+
+    fn current(&self) -> Option<&SearchMatch> {
+        self.matches.get(self.selected)
+    }
+
+    fn cycle(&mut self, forward: bool) {
+        let len = self.matches.len();
+        if len > 0 {
+            self.selected = if forward { (self.selected + 1) % len } else { (self.selected + len - 1) % len };
+        }
+    }
+}
+
+/// Редактор строки с буфером для yank, автодополнением и поиском по истории
+#[derive(Default)]
+pub struct LineEditor {
+    kill_buffer: String,
+    completion: Option<CompletionEngine>,
+    /// Меню вариантов дополнения, которое нужно показать под строкой
+    pending_menu: Option<String>,
+    search: Option<SearchState>,
+    /// Текст, с которого начнется следующая строка
+    initial_text: Option<String>,
+}
+
+impl LineEditor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Включает дополнение по Tab
+    pub fn set_completion(&mut self, engine: CompletionEngine) {
+        self.completion = Some(engine);
+    }
+
+    pub fn completion_mut(&mut self) -> Option<&mut CompletionEngine> {
+        self.completion.as_mut()
+    }
+
+    /// Подставляет текст в следующую строку ввода для редактирования
+    pub fn set_initial_text(&mut self, text: &str) {
+        self.initial_text = Some(text.to_string());
+    }
+
+    /// Читает строку. Если stdin не терминал - читает построчно без редактирования
+    pub fn read_line(&mut self, prompt: &str, history: &mut CommandHistory) -> io::Result<ReadResult> {
+        let initial_text = self.initial_text.take();
+        if !io::stdin().is_terminal() {
+            return Self::read_plain_line(prompt);
+        }
+
+        let _guard = RawModeGuard::enable()?;
+        let mut stdout = io::stdout();
+        let mut buffer = LineBuffer::default();
+        if let Some(text) = initial_text {
+            buffer.set_text(&text);
+        }
+        // Строка, которую пользователь набирал до перехода по истории
+        let mut draft: Option<String> = None;
+
+        history.reset_navigation();
+        let mut screen = Screen::default();
+        screen.draw(&mut stdout, prompt, &buffer)?;
+
+        loop {
+            let key = match event::read()? {
+                Event::Key(key) if key.kind != KeyEventKind::Release => key,
+                Event::Paste(text) => {
+                    buffer.insert_str(&text.replace(['\r', '\n'], " "));
+                    screen.draw(&mut stdout, prompt, &buffer)?;
+                    continue;
+                }
+                _ => continue,
+            };
+
+            match self.handle_key(key, &mut buffer, history, &mut draft) {
+                Some(result) => {
+                    // Вывод команды начинается под последней строкой ввода
+                    screen.leave(&mut stdout)?;
+                    stdout.flush()?;
+                    return Ok(result);
+                }
+                None => {
+                    if let Some(menu) = self.pending_menu.take() {
+                        screen.leave(&mut stdout)?;
+                        queue!(stdout, Print(menu.replace('\n', "\r\n")), Print("\r\n"))?;
+                    }
+                    if key.code == KeyCode::Char('l') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                        queue!(stdout, terminal::Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+                        screen = Screen::default();
+                    }
+                    match &self.search {
+                        Some(search) => Self::draw_search(&mut stdout, &mut screen, search)?,
+                        None => screen.draw(&mut stdout, prompt, &buffer)?,
+                    }
+                }
+            }
+        }
+    }
+
+    fn read_plain_line(prompt: &str) -> io::Result<ReadResult> {
+        print!("{}", prompt);
+        io::stdout().flush()?;
+
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line)? == 0 {
+            return Ok(ReadResult::Eof);
+        }
+        Ok(ReadResult::Line(line.trim_end_matches(['\r', '\n']).to_string()))
+    }
+
+    /// Обрабатывает клавишу. Возвращает Some, когда чтение строки закончено
+    fn handle_key(
+        &mut self,
+        key: KeyEvent,
+        buffer: &mut LineBuffer,
+        history: &mut CommandHistory,
+        draft: &mut Option<String>,
+    ) -> Option<ReadResult> {
+        if self.search.is_some() {
+            return self.handle_search_key(key, buffer, history);
+        }
+
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt = key.modifiers.contains(KeyModifiers::ALT);
+
+        match key.code {
+            KeyCode::Enter => return Some(ReadResult::Line(buffer.text())),
+            KeyCode::Char('r') if ctrl => {
+                let mut search = SearchState { original: buffer.text(), ..SearchState::default() };
+                search.refresh(history);
+                self.search = Some(search);
+            }
+            KeyCode::Char('c') if ctrl => return Some(ReadResult::Interrupted),
+            KeyCode::Char('d') if ctrl => {
+                if buffer.is_empty() {
+                    return Some(ReadResult::Eof);
+                }
+                buffer.delete_forward();
+            }
+            KeyCode::Char('u') if ctrl => return Some(ReadResult::SwitchToUi),
+
+            KeyCode::Char('a') if ctrl => buffer.move_home(),
+            KeyCode::Char('e') if ctrl => buffer.move_end(),
+            KeyCode::Char('b') if ctrl => buffer.move_left(),
+            KeyCode::Char('f') if ctrl => buffer.move_right(),
+            KeyCode::Char('b') if alt => buffer.move_word_left(),
+            KeyCode::Char('f') if alt => buffer.move_word_right(),
+            KeyCode::Left if ctrl || alt => buffer.move_word_left(),
+            KeyCode::Right if ctrl || alt => buffer.move_word_right(),
+            KeyCode::Left => buffer.move_left(),
+            KeyCode::Right => buffer.move_right(),
+            KeyCode::Home => buffer.move_home(),
+            KeyCode::End => buffer.move_end(),
+
+            KeyCode::Char('k') if ctrl => self.kill_buffer = buffer.kill_to_end(),
+            KeyCode::Char('w') if ctrl => self.kill_buffer = buffer.kill_word_backward(),
+            KeyCode::Backspace if alt => self.kill_buffer = buffer.kill_word_backward(),
+            KeyCode::Char('d') if alt => self.kill_buffer = buffer.kill_word_forward(),
+            KeyCode::Char('y') if ctrl => buffer.insert_str(&self.kill_buffer),
+            KeyCode::Char('t') if ctrl => buffer.transpose(),
+            KeyCode::Char('h') if ctrl => buffer.delete_backward(),
+            KeyCode::Backspace => buffer.delete_backward(),
+            KeyCode::Delete => buffer.delete_forward(),
+
+            KeyCode::Up => {
+                let current = buffer.text();
+                if let Some(command) = history.get_previous() {
+                    draft.get_or_insert(current);
+                    buffer.set_text(command);
+                }
+            }
+            KeyCode::Char('p') if ctrl => return self.handle_key(KeyEvent::from(KeyCode::Up), buffer, history, draft),
+            KeyCode::Down => match history.get_next() {
+                Some(command) => buffer.set_text(command),
+                None => {
+                    if let Some(text) = draft.take() {
+                        buffer.set_text(&text);
+                    }
+                }
+            },
+            KeyCode::Char('n') if ctrl => return self.handle_key(KeyEvent::from(KeyCode::Down), buffer, history, draft),
+
+            KeyCode::Tab => self.complete(buffer),
+
+            KeyCode::Char(ch) if !ctrl && !alt => buffer.insert(ch),
+            _ => {}
+        }
+        None
+    }
+
+    /// Клавиши в режиме поиска: ввод запроса, Ctrl+R/↑ и Ctrl+S/↓ - перебор
+    /// результатов, Enter - выполнить, Esc - отмена, остальное - редактировать
+    fn handle_search_key(&mut self, key: KeyEvent, buffer: &mut LineBuffer, history: &CommandHistory) -> Option<ReadResult> {
+        let search = self.search.as_mut()?;
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key.code {
+            KeyCode::Char('r') if ctrl => search.cycle(true),
+            KeyCode::Up => search.cycle(true),
+            KeyCode::Char('s') if ctrl => search.cycle(false),
+            KeyCode::Down => search.cycle(false),
+            KeyCode::Char('c') if ctrl => {
+                self.search = None;
+                return Some(ReadResult::Interrupted);
+            }
+            KeyCode::Esc => {
+                buffer.set_text(&search.original);
+                self.search = None;
+                return None;
+            }
+            KeyCode::Char('g') if ctrl => {
+                buffer.set_text(&search.original);
+                self.search = None;
+                return None;
+            }
+            KeyCode::Enter => {
+                let line = search.current().map(|m| m.command.clone()).unwrap_or_else(|| buffer.text());
+                self.search = None;
+                return Some(ReadResult::Line(line));
+            }
+            KeyCode::Backspace => {
+                search.query.pop();
+                search.refresh(history);
+            }
+            KeyCode::Char(ch) if !ctrl => {
+                search.query.push(ch);
+                search.refresh(history);
+            }
+            _ => {
+                // Принимаем найденную команду для редактирования
+                self.search = None;
+                return None;
+            }
+        }
+
+        if let Some(found) = search.current() {
+            buffer.set_text(&found.command);
+        }
+        None
+    }
+
+    /// Дополняет слово под курсором; при нескольких вариантах
+    /// дописывает общее начало или показывает меню
+    fn complete(&mut self, buffer: &mut LineBuffer) {
+        let engine = match &self.completion {
+            Some(engine) => engine,
+            None => return,
+        };
+
+        let text = buffer.text();
+        let result = engine.complete(&text, buffer.cursor());
+        let typed: String = text.chars().skip(result.start).take(buffer.cursor() - result.start).collect();
+
+        match result.candidates.as_slice() {
+            [] => {}
+            [single] => {
+                let mut replacement = single.text.clone();
+                if single.append_space {
+                    // Закрываем кавычку, открытую пользователем
+                    if let Some(quote) = replacement.chars().next().filter(|c| *c == '\'' || *c == '"') {
+                        replacement.push(quote);
+                    }
+                    replacement.push(' ');
+                }
+                buffer.replace_before_cursor(result.start, &replacement);
+            }
+            candidates => {
+                let prefix = completion::common_prefix(candidates);
+                if prefix.chars().count() > typed.chars().count() {
+                    buffer.replace_before_cursor(result.start, &prefix);
+                } else {
+                    let width = terminal::size().map(|(w, _)| w as usize).unwrap_or(80);
+                    self.pending_menu = Some(format_menu(candidates, width));
+                }
+            }
+        }
+    }
+
+    fn draw_search(stdout: &mut io::Stdout, screen: &mut Screen, search: &SearchState) -> io::Result<()> {
+        let found = match search.current() {
+            Some(found) => format!(
+                "{}  [{}/{}]",
+                history_search::highlight(&found.command, &found.positions),
+                search.selected + 1,
+                search.matches.len()
+            ),
+            None => "не найдено".to_string(),
+        };
+        screen.draw(stdout, &format!("(поиск)`{}': {}", search.query, found), &LineBuffer::default())
+    }
+}
+
+/// Строки экрана, занятые приглашением и вводом. Длинная строка переносится
+/// терминалом, поэтому перед перерисовкой курсор поднимается на первую из них
+#[derive(Debug, Default)]
+struct Screen {
+    /// Строка курсора относительно первой строки приглашения
+    cursor_row: usize,
+    /// Сколько строк заняла последняя отрисовка
+    rows: usize,
+}
+
+impl Screen {
+    /// Стирает прежний ввод и рисует приглашение со строкой заново,
+    /// курсор ставится по ширине символов перед ним
+    fn draw(&mut self, stdout: &mut io::Stdout, prompt: &str, buffer: &LineBuffer) -> io::Result<()> {
+        let width = terminal::size().ok().map(|(w, _)| w as usize).filter(|w| *w > 0).unwrap_or(80);
+        let text = buffer.text();
+        let before_cursor: String = text.chars().take(buffer.cursor()).collect();
+
+        if self.cursor_row > 0 {
+            queue!(stdout, cursor::MoveUp(self.cursor_row as u16))?;
+        }
+        queue!(
+            stdout,
+            Print("\r"),
+            terminal::Clear(ClearType::FromCursorDown),
+            Print(prompt.replace('\n', "\r\n")),
+            Print(text.replace('\n', "\r\n"))
+        )?;
+
+        let (end_row, end_col) = screen_position(&format!("{}{}", prompt, text), width);
+        // Строка заполнена до края: терминал перенесет курсор только со следующим символом
+        let end_row = if end_col == width {
+            queue!(stdout, Print("\r\n"))?;
+            end_row + 1
+        } else {
+            end_row
+        };
+        let (row, col) = match screen_position(&format!("{}{}", prompt, before_cursor), width) {
+            (row, col) if col == width => (row + 1, 0),
+            position => position,
+        };
+        if end_row > row {
+            queue!(stdout, cursor::MoveUp((end_row - row) as u16))?;
+        }
+        queue!(stdout, Print("\r"))?;
+        if col > 0 {
+            queue!(stdout, cursor::MoveRight(col as u16))?;
+        }
+        self.cursor_row = row;
+        self.rows = end_row + 1;
+        stdout.flush()
+    }
+
+    /// Переводит курсор на новую строку под вводом
+    fn leave(&mut self, stdout: &mut io::Stdout) -> io::Result<()> {
+        let below = self.rows.saturating_sub(self.cursor_row + 1);
+        if below > 0 {
+            queue!(stdout, cursor::MoveDown(below as u16))?;
+        }
+        // В raw-режиме \n не делает возврат каретки
+        queue!(stdout, Print("\r\n"))?;
+        *self = Screen::default();
+        Ok(())
+    }
+}
+
+/// Строка и колонка курсора после вывода text с переносом по ширине
+/// терминала. Escape-последовательности цвета места не занимают; колонка
+/// равна width, если строка заполнена до края
+fn screen_position(text: &str, width: usize) -> (usize, usize) {
+    let (mut row, mut col) = (0, 0);
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\n' => {
+                row += 1;
+                col = 0;
+            }
+            // CSI: ESC [ параметры и завершающий символ
+            '\x1b' => {
+                if chars.next() == Some('[') {
+                    for ch in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&ch) {
+                            break;
+                        }
+                    }
+                }
+            }
+            ch => {
+                let char_width = char_width(ch);
+                if col + char_width > width {
+                    row += 1;
+                    col = 0;
+                }
+                col += char_width;
+            }
+        }
+    }
+    (row, col)
+}
+
+/// Ширина символа в колонках: иероглифы и эмодзи занимают две,
+/// управляющие и комбинируемые символы - ни одной
+fn char_width(ch: char) -> usize {
+    match ch as u32 {
+        0..=0x1f | 0x7f..=0x9f => 0,
+        0x0300..=0x036f | 0x200b..=0x200f | 0x20d0..=0x20ff | 0xfe00..=0xfe0f => 0,
+        0x1100..=0x115f | 0x2e80..=0x303e | 0x3041..=0x33ff | 0x3400..=0x4dbf | 0x4e00..=0x9fff
+        | 0xa000..=0xa4cf | 0xac00..=0xd7a3 | 0xf900..=0xfaff | 0xfe30..=0xfe4f | 0xff00..=0xff60
+        | 0xffe0..=0xffe6 | 0x20000..=0x3fffd => 2,
+        // Эмодзи: отдельные символы 0x2600-0x27bf и блоки 0x1f300-0x1faff
+        0x231a..=0x231b | 0x23e9..=0x23ec | 0x23f0 | 0x23f3 | 0x25fd..=0x25fe | 0x2614..=0x2615
+        | 0x2648..=0x2653 | 0x267f | 0x2693 | 0x26a1 | 0x26aa..=0x26ab | 0x26bd..=0x26be
+        | 0x26c4..=0x26c5 | 0x26ce | 0x26d4 | 0x26ea | 0x26f2..=0x26f3 | 0x26f5 | 0x26fa | 0x26fd
+        | 0x2705 | 0x270a..=0x270b | 0x2728 | 0x274c | 0x274e | 0x2753..=0x2755 | 0x2757
+        | 0x2795..=0x2797 | 0x27b0 | 0x27bf | 0x2b1b..=0x2b1c | 0x2b50 | 0x2b55
+        | 0x1f300..=0x1f64f | 0x1f680..=0x1f6ff | 0x1f7e0..=0x1f7eb | 0x1f900..=0x1faff => 2,
+        _ => 1,
+    }
+}
+
+/// Максимальное число вариантов в меню
+const MENU_LIMIT: usize = 100;
+
+/// Форматирует варианты дополнения: с описаниями - по одному в строке,
+/// без описаний - в колонки по ширине терминала
+fn format_menu(candidates: &[Candidate], width: usize) -> String {
+    let shown = &candidates[..candidates.len().min(MENU_LIMIT)];
+    let mut lines: Vec<String> = if shown.iter().any(|c| c.description.is_some()) {
+        let name_width = shown.iter().map(|c| c.text.chars().count()).max().unwrap_or(0);
+        shown.iter()
+            .map(|c| format!("{:<width$}  {}", c.text, c.description.as_deref().unwrap_or(""), width = name_width))
+            .collect()
+    } else {
+        let column = shown.iter().map(|c| c.text.chars().count()).max().unwrap_or(0) + 2;
+        let per_line = (width / column).max(1);
+        shown.chunks(per_line)
+            .map(|row| row.iter()
+                .map(|c| format!("{:<width$}", c.text, width = column))
+                .collect::<String>()
+                .trim_end()
+                .to_string())
+            .collect()
+    };
+
+    if candidates.len() > MENU_LIMIT {
+        lines.push(format!("... и еще {}", candidates.len() - MENU_LIMIT));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer(text: &str) -> LineBuffer {
+        let mut buffer = LineBuffer::default();
+        buffer.set_text(text);
+        buffer
+    }
+
+    #[test]
+    fn test_screen_position() {
+        assert_eq!(screen_position("$ ls", 80), (0, 4));
+        // Цвет приглашения не занимает места
+        assert_eq!(screen_position("\x1b[1;32muser\x1b[0m$ ", 80), (0, 6));
+        // Перенос длинной строки; строка ровно по краю - колонка равна ширине
+        assert_eq!(screen_position(&"x".repeat(25), 10), (2, 5));
+        assert_eq!(screen_position(&"x".repeat(20), 10), (1, 10));
+        // Многострочная запись из истории
+        assert_eq!(screen_position("$ for f in *\ndo echo", 80), (1, 7));
+        // Широкий символ не помещается в последнюю колонку и переносится целиком
+        assert_eq!(screen_position("日本語", 6), (0, 6));
+        assert_eq!(screen_position("x日本語", 6), (1, 2));
+        assert_eq!(screen_position("🔴 é", 80), (0, 4));
+    }
+
+    #[test]
+    fn test_insert_and_delete() {
+        let mut buf = buffer("git stats");
+        buf.move_left();
+        buf.insert('x');
+        assert_eq!(buf.text(), "git statxs");
+        buf.delete_backward();
+        buf.insert('u');
+        assert_eq!(buf.text(), "git status");
+        buf.move_home();
+        buf.delete_forward();
+        assert_eq!(buf.text(), "it status");
+        assert_eq!(buf.cursor(), 0);
+    }
+
+    #[test]
+    fn test_word_motions() {
+        let mut buf = buffer("ls -la /tmp/dir");
+        buf.move_word_left();
+        assert_eq!(buf.cursor(), 12);
+        buf.move_word_left();
+        assert_eq!(buf.cursor(), 8);
+        buf.move_word_right();
+        assert_eq!(buf.cursor(), 11);
+    }
+
+    #[test]
+    fn test_kill_and_yank() {
+        let mut editor = LineEditor::new();
+        let mut buf = buffer("echo hello world");
+        let mut history = CommandHistory::new(10);
+        let mut draft = None;
+
+        editor.handle_key(KeyEvent::new(KeyCode::Char('w'), KeyModifiers::CONTROL), &mut buf, &mut history, &mut draft);
+        assert_eq!(buf.text(), "echo hello ");
+        editor.handle_key(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL), &mut buf, &mut history, &mut draft);
+        editor.handle_key(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::CONTROL), &mut buf, &mut history, &mut draft);
+        assert_eq!(buf.text(), "worldecho hello ");
+
+        buf.move_word_left();
+        assert_eq!(buf.kill_to_end(), "worldecho hello ");
+        assert!(buf.is_empty());
+    }
+
+    #[test]
+    fn test_history_navigation_restores_draft() {
+        let mut editor = LineEditor::new();
+        let mut history = CommandHistory::new(10);
+        history.add("ls".to_string());
+        history.add("pwd".to_string());
+        history.reset_navigation();
+
+        let mut buf = buffer("ech");
+        let mut draft = None;
+        let mut press = |code: KeyCode, buf: &mut LineBuffer, draft: &mut Option<String>| {
+            editor.handle_key(KeyEvent::from(code), buf, &mut history, draft)
+        };
+
+        press(KeyCode::Up, &mut buf, &mut draft);
+        assert_eq!(buf.text(), "pwd");
+        press(KeyCode::Up, &mut buf, &mut draft);
+        assert_eq!(buf.text(), "ls");
+        press(KeyCode::Down, &mut buf, &mut draft);
+        assert_eq!(buf.text(), "pwd");
+        press(KeyCode::Down, &mut buf, &mut draft);
+        assert_eq!(buf.text(), "ech");
+    }
+
+    #[test]
+    fn test_reverse_search_cycles_and_cancels() {
+        let mut editor = LineEditor::new();
+        let mut history = CommandHistory::new(10);
+        for command in ["git status", "ls -la", "git stash"] {
+            history.add(command.to_string());
+        }
+        let mut buf = buffer("draft");
+        let mut draft = None;
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+
+        editor.handle_key(ctrl('r'), &mut buf, &mut history, &mut draft);
+        for ch in "gst".chars() {
+            editor.handle_key(KeyEvent::from(KeyCode::Char(ch)), &mut buf, &mut history, &mut draft);
+        }
+        let first = buf.text();
+        assert!(first.starts_with("git st"));
+        editor.handle_key(ctrl('r'), &mut buf, &mut history, &mut draft);
+        assert_ne!(buf.text(), first);
+        assert!(buf.text().starts_with("git st"));
+
+        editor.handle_key(KeyEvent::from(KeyCode::Esc), &mut buf, &mut history, &mut draft);
+        assert_eq!(buf.text(), "draft");
+
+        editor.handle_key(ctrl('r'), &mut buf, &mut history, &mut draft);
+        editor.handle_key(KeyEvent::from(KeyCode::Char('l')), &mut buf, &mut history, &mut draft);
+        assert_eq!(
+            editor.handle_key(KeyEvent::from(KeyCode::Enter), &mut buf, &mut history, &mut draft),
+            Some(ReadResult::Line("ls -la".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_format_menu_columns() {
+        let candidates: Vec<Candidate> = ["alpha", "beta", "gamma"].iter().map(|s| Candidate::new(s)).collect();
+        assert_eq!(format_menu(&candidates, 16), "alpha  beta\ngamma");
+    }
+
+    #[test]
+    fn test_control_keys_finish_reading() {
+        let mut editor = LineEditor::new();
+        let mut history = CommandHistory::new(10);
+        let mut draft = None;
+        let mut buf = LineBuffer::default();
+
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        assert_eq!(editor.handle_key(ctrl('u'), &mut buf, &mut history, &mut draft), Some(ReadResult::SwitchToUi));
+        assert_eq!(editor.handle_key(ctrl('d'), &mut buf, &mut history, &mut draft), Some(ReadResult::Eof));
+        buf.set_text("x");
+        assert_eq!(editor.handle_key(ctrl('c'), &mut buf, &mut history, &mut draft), Some(ReadResult::Interrupted));
+    }
+}
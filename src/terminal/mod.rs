@@ -1,31 +1,76 @@
 pub mod core;
 pub mod history;
+pub mod history_search;
+pub mod history_expansion;
 pub mod parser;
+pub mod lexer;
+pub mod pipeline;
 pub mod executor;
+pub mod builtins;
+pub mod expand;
+pub mod glob;
+pub mod line_editor;
+pub mod completion;
+pub mod correction;
+pub mod guard;
+pub mod aliases;
+pub mod rc;
+pub mod jobs;
+pub mod pty;
+pub mod signals;
+pub mod status;
+pub mod prompt;
 
 pub use core::Terminal;
 pub use history::CommandHistory;
 pub use parser::{CommandParser, ParsedCommand, CommandType};
 pub use executor::CommandExecutor;
+pub use status::{ExitStatus, ResourceUsage};
+
+use std::collections::HashMap;
+
+/// Размер истории по умолчанию
+const DEFAULT_HISTORY_SIZE: usize = 10000;
+use std::path::PathBuf;
+use std::time::Duration;
 
 /// Конфигурация терминала
 #[derive(Debug, Clone)]
 pub struct TerminalConfig {
+    /// Шаблон приглашения (см. prompt.rs); пустая строка - шаблон по умолчанию
     pub prompt: String,
+    /// Сколько ждать git status для сегмента {git}; дольше - сегмент пуст
+    pub prompt_git_timeout: Duration,
     pub history_size: usize,
+    /// Файл истории; None - история только в памяти
+    pub history_file: Option<PathBuf>,
+    /// Не сохранять команды, начинающиеся с пробела
+    pub history_ignore_space: bool,
+    /// Шаблоны команд, которые не попадают в историю
+    pub history_ignore: Vec<String>,
     pub show_welcome: bool,
+    /// Файл, выполняемый при запуске; None - не выполнять
+    pub rc_file: Option<PathBuf>,
     pub enable_syntax_highlighting: bool,
     pub auto_completion: bool,
+    /// Команды дольше этого показывают длительность в приглашении; None - не показывать
+    pub slow_command_threshold: Option<Duration>,
 }
 
 impl Default for TerminalConfig {
     fn default() -> Self {
         Self {
-            prompt: "smart-term> ".to_string(),
-            history_size: 100,
+            prompt: String::new(),
+            prompt_git_timeout: Duration::from_millis(200),
+            history_size: DEFAULT_HISTORY_SIZE,
+            history_file: history::default_history_path(),
+            history_ignore_space: true,
+            history_ignore: history::default_ignore_patterns(),
             show_welcome: true,
+            rc_file: rc::default_rc_path(),
             enable_syntax_highlighting: true,
-            auto_completion: false, // Пока не реализовано
+            auto_completion: true,
+            slow_command_threshold: Some(Duration::from_secs(5)),
         }
     }
 }
@@ -34,13 +79,46 @@ impl Default for TerminalConfig {
 #[derive(Debug, Clone)]
 pub struct CommandResult {
     pub success: bool,
+    /// Стандартный вывод
     pub output: String,
+    /// Поток ошибок внешних команд, отдельно от вывода
+    pub stderr: String,
+    /// Сообщение об ошибке для показа пользователю
     pub error: Option<String>,
+    /// Код завершения или сигнал; $? - status.code()
+    pub status: ExitStatus,
+    /// Время выполнения, процессорное время и пиковая память
+    pub usage: ResourceUsage,
+    /// Вывод уже показан по мере выполнения; output и stderr - его копия для истории и UI
+    pub streamed: bool,
 }
 
 impl CommandResult {
     pub fn new(success: bool, output: String, error: Option<String>) -> Self {
-        Self { success, output, error }
+        let status = ExitStatus::Code(if success { 0 } else { 1 });
+        Self { success, output, stderr: String::new(), error, status, usage: ResourceUsage::default(), streamed: false }
+    }
+    
+    /// Результат внешней команды, чей вывод уже показан в терминале
+    pub fn streamed(status: ExitStatus, stdout: String, stderr: String, error: Option<String>) -> Self {
+        Self { success: status.success(), output: stdout, stderr, error, status, usage: ResourceUsage::default(), streamed: true }
+    }
+    
+    /// Результат процесса, запущенного с перехваченным выводом (!bash, !rust).
+    /// Для показа при ошибке используется stderr, а если он пуст - stdout
+    pub fn from_output(output: std::process::Output) -> Self {
+        let status = ExitStatus::from_std(output.status);
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        if status.success() {
+            return Self { stderr, status, ..Self::success(stdout) };
+        }
+        let message = if stderr.is_empty() { stdout.clone() } else { stderr.clone() };
+        Self { output: stdout, stderr, status, ..Self::error(message) }
+    }
+    
+    pub fn exit_code(&self) -> i32 {
+        self.status.code()
     }
     
     pub fn success(output: String) -> Self {
@@ -52,6 +130,50 @@ impl CommandResult {
     }
 }
 
+/// Опции, переключаемые через set -o / set +o
+#[derive(Debug, Clone, Default)]
+pub struct ShellOptions {
+    /// Показывать раскрытые шаблоны и спрашивать подтверждение перед rm, mv и т.п.
+    pub glob_preview: bool,
+    /// set -e: завершить скрипт (или терминал) после первой неудачной команды
+    pub errexit: bool,
+    /// set -x: печатать каждую команду после раскрытий перед выполнением
+    pub xtrace: bool,
+}
+
+impl ShellOptions {
+    /// Имена всех опций
+    pub const NAMES: &'static [&'static str] = &["globpreview", "errexit", "xtrace"];
+
+    pub fn get(&self, name: &str) -> Option<bool> {
+        match name {
+            "globpreview" => Some(self.glob_preview),
+            "errexit" => Some(self.errexit),
+            "xtrace" => Some(self.xtrace),
+            _ => None,
+        }
+    }
+
+    pub fn set(&mut self, name: &str, value: bool) -> Result<(), String> {
+        match name {
+            "globpreview" => self.glob_preview = value,
+            "errexit" => self.errexit = value,
+            "xtrace" => self.xtrace = value,
+            _ => return Err(format!("{}: неизвестная опция", name)),
+        }
+        Ok(())
+    }
+
+    /// Имя опции по короткому флагу set (-e, -x)
+    pub fn from_flag(flag: char) -> Option<&'static str> {
+        match flag {
+            'e' => Some("errexit"),
+            'x' => Some("xtrace"),
+            _ => None,
+        }
+    }
+}
+
 /// Состояние терминала
 #[derive(Debug, Clone)]
 pub struct TerminalState {
@@ -60,6 +182,19 @@ pub struct TerminalState {
     pub hostname: String,
     pub is_running: bool,
     pub last_exit_code: i32,
+    pub previous_directory: Option<String>,
+    pub directory_stack: Vec<String>,
+    /// Экспортируемые переменные, передаются каждому запущенному процессу
+    pub environment: HashMap<String, String>,
+    /// Переменные сессии без export
+    pub shell_variables: HashMap<String, String>,
+    pub options: ShellOptions,
+    /// Позиционные параметры $1, $2, ... внутри функции или скрипта
+    pub positional_args: Vec<String>,
+    /// Значение $0: имя терминала или выполняемого скрипта
+    pub script_name: String,
+    /// История команд: встроенная команда history, !!, поиск по Ctrl+R
+    pub history: CommandHistory,
 }
 
 impl Default for TerminalState {
@@ -73,6 +208,47 @@ impl Default for TerminalState {
             hostname: whoami::fallible::hostname().unwrap_or_else(|_| "unknown".to_string()),
             is_running: true,
             last_exit_code: 0,
+            previous_directory: None,
+            directory_stack: Vec::new(),
+            environment: std::env::vars().collect(),
+            shell_variables: HashMap::new(),
+            options: ShellOptions::default(),
+            positional_args: Vec::new(),
+            script_name: "smart-term".to_string(),
+            history: CommandHistory::new(DEFAULT_HISTORY_SIZE),
+        }
+    }
+}
+
+impl TerminalState {
+    /// Значение переменной сессии или окружения
+    pub fn get_variable(&self, name: &str) -> Option<String> {
+        self.shell_variables.get(name)
+            .or_else(|| self.environment.get(name))
+            .cloned()
+    }
+    
+    /// Присваивает значение; экспортированная переменная остается в окружении
+    pub fn set_variable(&mut self, name: &str, value: &str) {
+        if self.environment.contains_key(name) {
+            self.environment.insert(name.to_string(), value.to_string());
+        } else {
+            self.shell_variables.insert(name.to_string(), value.to_string());
         }
     }
+    
+    /// Экспортирует переменную; без значения экспортирует уже существующую
+    pub fn export_variable(&mut self, name: &str, value: Option<&str>) {
+        let value = value.map(str::to_string)
+            .or_else(|| self.shell_variables.remove(name))
+            .or_else(|| self.environment.get(name).cloned())
+            .unwrap_or_default();
+        self.shell_variables.remove(name);
+        self.environment.insert(name.to_string(), value);
+    }
+    
+    pub fn unset_variable(&mut self, name: &str) {
+        self.shell_variables.remove(name);
+        self.environment.remove(name);
+    }
 }
@@ -1,29 +1,202 @@
-use crate::terminal::CommandResult;
+use crate::runners::BUILTIN_RUNNERS;
+use crate::terminal::lexer::{self, QuoteKind, RedirectOp, Token, Word, WordPart};
 
 #[derive(Debug, Clone)]
 pub enum CommandType {
-    System(String, Vec<String>),  // команда и аргументы
-    Rust(String),                 // код на Rust
-    Python(String),               // код на Python
-    Java(String),                 // код на Java
+    Shell(CommandList),           // команды shell: конвейеры, списки, перенаправления
+    Runner(String, String),       // исполнитель кода (!rust, !python, ...) и код
     Bash(String),                 // Bash команда/скрипт
     Editor(String),               // редактирование файла
     Git(String, Vec<String>),     // git команда и аргументы
     Crypto(String, Vec<String>),  // крипто-команда и аргументы
-    Internal(String),             // внутренняя команда
+    Function(String, String),     // определение функции: имя и тело
 }
 
+/// Внутренние команды терминала
+pub const INTERNAL_COMMANDS: &[&str] = &[
+    "help", "bash-help", "bash-quick", "clear",
+    "elevate", "privileges", "ui", "gui",
+    "nowelcome", "welcome",
+];
+
+/// Префиксы встроенных команд и их описание; исполнители кода знает CommandParser
+pub const LANGUAGE_PREFIXES: &[(&str, &str)] = &[
+    ("!bash", "выполнить Bash команду"),
+    ("!edit", "редактировать файл"),
+    ("!micro", "редактировать файл"),
+    ("!crypt", "крипто-команды"),
+];
+
 #[derive(Debug, Clone)]
 pub struct ParsedCommand {
     pub cmd_type: CommandType,
+    #[allow(dead_code)]
     pub raw_input: String,
 }
 
-pub struct CommandParser;
+/// Перенаправление ввода/вывода
+#[derive(Debug, Clone, PartialEq)]
+pub enum Redirection {
+    Input { fd: u32, target: Word },
+    Output { fd: u32, target: Word, append: bool },
+    Duplicate { fd: u32, target_fd: u32 },
+}
+
+/// Присваивание переменной NAME=value перед командой
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assignment {
+    pub name: String,
+    pub value: Word,
+}
+
+impl Assignment {
+    /// Распознает слово вида NAME=value (имя должно быть без кавычек)
+    pub fn from_word(word: &Word) -> Option<Self> {
+        let first = word.parts.first().filter(|p| p.quote == QuoteKind::None)?;
+        let (name, rest) = first.text.split_once('=')?;
+        if !is_valid_name(name) {
+            return None;
+        }
+        
+        let mut value = Word::default();
+        if !rest.is_empty() {
+            value.parts.push(WordPart { text: rest.to_string(), quote: QuoteKind::None });
+        }
+        value.parts.extend(word.parts[1..].iter().cloned());
+        Some(Self { name: name.to_string(), value })
+    }
+}
+
+/// Проверяет, что строка - допустимое имя переменной
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Распознает определение функции `name() { body }` или `function name { body }`.
+/// Тело может занимать несколько строк
+pub fn parse_function_definition(input: &str) -> Option<(String, String)> {
+    let input = input.trim();
+    let (rest, has_keyword) = match input.strip_prefix("function") {
+        Some(rest) if rest.starts_with(char::is_whitespace) => (rest.trim_start(), true),
+        _ => (input, false),
+    };
+    
+    let name_end = rest.find(|c: char| c.is_whitespace() || c == '(' || c == '{').unwrap_or(rest.len());
+    let (name, rest) = rest.split_at(name_end);
+    let rest = rest.trim_start();
+    let rest = match rest.strip_prefix("()") {
+        Some(rest) => rest.trim_start(),
+        // Без ключевого слова function скобки обязательны
+        None if has_keyword => rest,
+        None => return None,
+    };
+    
+    let body = rest.strip_prefix('{')?.strip_suffix('}')?;
+    let valid_name = !name.is_empty()
+        && name.chars().all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | ':'))
+        && !name.starts_with(|c: char| c.is_ascii_digit());
+    if !valid_name {
+        return None;
+    }
+    
+    let body = body.trim().trim_end_matches(';').trim_end();
+    if body.is_empty() {
+        return None;
+    }
+    Some((name.to_string(), body.to_string()))
+}
+
+/// Простая команда: присваивания, слова и перенаправления
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SimpleCommand {
+    pub assignments: Vec<Assignment>,
+    pub words: Vec<Word>,
+    pub redirections: Vec<Redirection>,
+}
+
+impl SimpleCommand {
+    fn is_empty(&self) -> bool {
+        self.words.is_empty() && self.assignments.is_empty()
+    }
+}
+
+/// Конвейер команд, соединенных через |
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Pipeline {
+    pub commands: Vec<SimpleCommand>,
+}
+
+/// Условие запуска элемента списка относительно предыдущего
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connector {
+    Always, // первый элемент или после ;
+    And,    // &&
+    Or,     // ||
+}
+
+/// Элемент списка команд
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListItem {
+    pub connector: Connector,
+    pub pipeline: Pipeline,
+    /// Запуск в фоне (cmd &)
+    pub background: bool,
+}
+
+/// Список конвейеров, соединенных через &&, || и ;
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CommandList {
+    pub items: Vec<ListItem>,
+}
+
+
+#[derive(Debug, Clone)]
+pub struct CommandParser {
+    /// Исполнители кода: имя без '!' и описание
+    runners: Vec<(String, String)>,
+}
 
 impl CommandParser {
+    /// Разборщик со встроенными исполнителями кода
     pub fn new() -> Self {
-        Self
+        Self::with_runners(BUILTIN_RUNNERS.iter()
+            .map(|(name, description)| (name.to_string(), description.to_string()))
+            .collect())
+    }
+    
+    /// Разборщик с исполнителями кода из реестра: имя без '!' и описание
+    pub fn with_runners(runners: Vec<(String, String)>) -> Self {
+        Self { runners }
+    }
+    
+    /// Все префиксы вида !имя с описанием: исполнители кода и встроенные команды
+    pub fn bang_prefixes(&self) -> Vec<(String, String)> {
+        self.runners.iter()
+            .map(|(name, description)| (format!("!{}", name), description.clone()))
+            .chain(LANGUAGE_PREFIXES.iter().map(|(prefix, description)| (prefix.to_string(), description.to_string())))
+            .collect()
+    }
+    
+    /// Начинается ли строка с префикса !имя (!python, !bash): код после
+    /// него передается как есть, без aliases и раскрытия истории
+    pub fn has_bang_prefix(&self, input: &str) -> bool {
+        let input = input.trim_start();
+        self.bang_prefixes().iter().any(|(prefix, _)| {
+            input.strip_prefix(prefix.as_str()).is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+        })
+    }
+    
+    /// "!python print(1)" -> ("python", "print(1)"), если !python - исполнитель кода.
+    /// Код может быть пустым
+    pub fn split_runner(&self, input: &str) -> Option<(String, String)> {
+        let rest = input.strip_prefix('!')?;
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let name = &rest[..end];
+        self.runners.iter()
+            .any(|(runner, _)| runner == name)
+            .then(|| (name.to_string(), rest[end..].trim().to_string()))
     }
     
     pub fn parse(&self, input: &str) -> Result<ParsedCommand, String> {
@@ -34,38 +207,17 @@ impl CommandParser {
         }
         
         // Определяем тип команды по префиксу
-        if input.starts_with("!rust ") {
-            let code = input[6..].trim().to_string();
+        if let Some((name, code)) = self.split_runner(input) {
             if code.is_empty() {
-                return Err("Пустой Rust код".to_string());
+                return Err(format!("Пустой код: !{} <код>", name));
             }
             Ok(ParsedCommand {
-                cmd_type: CommandType::Rust(code),
+                cmd_type: CommandType::Runner(name, code),
                 raw_input: input.to_string(),
             })
         }
-        else if input.starts_with("!python ") {
-            let code = input[8..].trim().to_string();
-            if code.is_empty() {
-                return Err("Пустой Python код".to_string());
-            }
-            Ok(ParsedCommand {
-                cmd_type: CommandType::Python(code),
-                raw_input: input.to_string(),
-            })
-        }
-        else if input.starts_with("!java ") {
-            let code = input[6..].trim().to_string();
-            if code.is_empty() {
-                return Err("Пустой Java код".to_string());
-            }
-            Ok(ParsedCommand {
-                cmd_type: CommandType::Java(code),
-                raw_input: input.to_string(),
-            })
-        }
-        else if input.starts_with("!bash ") {
-            let code = input[6..].trim().to_string();
+        else if let Some(code) = input.strip_prefix("!bash ") {
+            let code = code.trim().to_string();
             Ok(ParsedCommand {
                 cmd_type: CommandType::Bash(code),
                 raw_input: input.to_string(),
@@ -79,8 +231,8 @@ impl CommandParser {
                 raw_input: input.to_string(),
             })
         }
-        else if input.starts_with("!crypt ") {
-            let args_str = input[7..].trim();
+        else if let Some(args_str) = input.strip_prefix("!crypt ") {
+            let args_str = args_str.trim();
             let args: Vec<String> = args_str.split_whitespace()
                 .map(|s| s.to_string())
                 .collect();
@@ -94,79 +246,289 @@ impl CommandParser {
                 raw_input: input.to_string(),
             })
         }
-        else if input.starts_with("git ") {
-            let git_args: Vec<&str> = input[4..].split_whitespace().collect();
-            if git_args.is_empty() {
-                return Err("Не указана git команда".to_string());
-            }
-            
+        else if let Some((name, body)) = parse_function_definition(input) {
             Ok(ParsedCommand {
-                cmd_type: CommandType::Git(
-                    git_args[0].to_string(), 
-                    git_args[1..].iter().map(|s| s.to_string()).collect()
-                ),
+                cmd_type: CommandType::Function(name, body),
                 raw_input: input.to_string(),
             })
         }
         else {
             // Проверяем специальные Git алиасы
             match input {
-                "gs" | "gst" => Ok(ParsedCommand {
+                "gs" | "gst" => return Ok(ParsedCommand {
                     cmd_type: CommandType::Git("status".to_string(), vec!["--short".to_string()]),
                     raw_input: "git status --short".to_string(),
                 }),
-                "ga" => Ok(ParsedCommand {
+                "ga" => return Ok(ParsedCommand {
                     cmd_type: CommandType::Git("add".to_string(), vec![".".to_string()]),
                     raw_input: "git add .".to_string(),
                 }),
-                "gc" => Ok(ParsedCommand {
+                "gc" => return Ok(ParsedCommand {
                     cmd_type: CommandType::Git("commit".to_string(), vec!["-m".to_string(), "update".to_string()]),
                     raw_input: "git commit -m update".to_string(),
                 }),
-                "gp" => Ok(ParsedCommand {
+                "gp" => return Ok(ParsedCommand {
                     cmd_type: CommandType::Git("push".to_string(), vec![]),
                     raw_input: "git push".to_string(),
                 }),
-                "gl" => Ok(ParsedCommand {
+                "gl" => return Ok(ParsedCommand {
                     cmd_type: CommandType::Git("log".to_string(), vec!["--oneline".to_string(), "-10".to_string()]),
                     raw_input: "git log --oneline -10".to_string(),
                 }),
-                _ => {
-                    // По умолчанию - системная команда или Bash
-                    let parts: Vec<&str> = input.split_whitespace().collect();
-                    if parts.is_empty() {
-                        return Err("Пустая команда".to_string());
+                _ => {}
+            }
+            
+            // git и внутренние команды выбирает исполнитель после раскрытия слов
+            let list = self.parse_list(input)?;
+            
+            Ok(ParsedCommand {
+                cmd_type: CommandType::Shell(list),
+                raw_input: input.to_string(),
+            })
+        }
+    }
+    
+    /// Разбирает строку в список конвейеров
+    pub fn parse_list(&self, input: &str) -> Result<CommandList, String> {
+        let tokens = lexer::tokenize(input)?;
+        let mut list = CommandList::default();
+        let mut pipeline = Pipeline::default();
+        let mut command = SimpleCommand::default();
+        let mut connector = Connector::Always;
+        let mut iter = tokens.into_iter().peekable();
+        
+        while let Some(token) = iter.next() {
+            match token {
+                Token::Word(word) => {
+                    // Присваивания допустимы только до имени команды
+                    match Assignment::from_word(&word) {
+                        Some(assignment) if command.words.is_empty() => command.assignments.push(assignment),
+                        _ => command.words.push(word),
                     }
-                    
-                    let cmd = parts[0].to_string();
-                    let args = parts[1..].iter().map(|&s| s.to_string()).collect();
-                    
-                    // Проверяем, является ли команда внутренней
-                    if self.is_internal_command(&cmd) {
-                        Ok(ParsedCommand {
-                            cmd_type: CommandType::Internal(input.to_string()),
-                            raw_input: input.to_string(),
-                        })
-                    } else {
-                        Ok(ParsedCommand {
-                            cmd_type: CommandType::System(cmd, args),
-                            raw_input: input.to_string(),
-                        })
+                }
+                Token::Redirect { fd, op } => {
+                    let redirect_target = |iter: &mut std::iter::Peekable<std::vec::IntoIter<Token>>| {
+                        match iter.next() {
+                            Some(Token::Word(word)) => Ok(word),
+                            _ => Err("Ожидалось имя файла после перенаправления".to_string()),
+                        }
+                    };
+                    match op {
+                        RedirectOp::Input => command.redirections.push(Redirection::Input {
+                            fd: fd.unwrap_or(0),
+                            target: redirect_target(&mut iter)?,
+                        }),
+                        RedirectOp::Output | RedirectOp::Append => command.redirections.push(Redirection::Output {
+                            fd: fd.unwrap_or(1),
+                            target: redirect_target(&mut iter)?,
+                            append: op == RedirectOp::Append,
+                        }),
+                        RedirectOp::Duplicate(target_fd) => command.redirections.push(Redirection::Duplicate {
+                            fd: fd.unwrap_or(1),
+                            target_fd,
+                        }),
+                        RedirectOp::OutputAll => {
+                            command.redirections.push(Redirection::Output {
+                                fd: 1,
+                                target: redirect_target(&mut iter)?,
+                                append: false,
+                            });
+                            command.redirections.push(Redirection::Duplicate { fd: 2, target_fd: 1 });
+                        }
+                    }
+                }
+                Token::Pipe => {
+                    if command.is_empty() {
+                        return Err("Синтаксическая ошибка рядом с '|'".to_string());
+                    }
+                    pipeline.commands.push(std::mem::take(&mut command));
+                }
+                Token::Background => {
+                    if command.is_empty() {
+                        return Err("Синтаксическая ошибка рядом с '&'".to_string());
+                    }
+                    // Фоном запускается только отдельный конвейер, а не цепочка a && b
+                    if connector != Connector::Always {
+                        return Err("Фоновый запуск цепочек с && и || не поддерживается".to_string());
+                    }
+                    pipeline.commands.push(std::mem::take(&mut command));
+                    list.items.push(ListItem { connector, pipeline: std::mem::take(&mut pipeline), background: true });
+                    connector = Connector::Always;
+                }
+                Token::And | Token::Or | Token::Semicolon => {
+                    let is_semicolon = token == Token::Semicolon;
+                    if command.is_empty() {
+                        // Пустые команды допустимы только между ; (например, "a;;" или "a;")
+                        if is_semicolon && pipeline.commands.is_empty() && connector == Connector::Always {
+                            continue;
+                        }
+                        return Err("Синтаксическая ошибка: пропущена команда".to_string());
+                    }
+                    pipeline.commands.push(std::mem::take(&mut command));
+                    list.items.push(ListItem { connector, pipeline: std::mem::take(&mut pipeline), background: false });
+                    connector = match token {
+                        Token::And => Connector::And,
+                        Token::Or => Connector::Or,
+                        _ => Connector::Always,
+                    };
+                    if !is_semicolon && iter.peek().is_none() {
+                        return Err("Синтаксическая ошибка: неожиданный конец строки".to_string());
                     }
                 }
             }
         }
+        
+        if !command.is_empty() {
+            pipeline.commands.push(command);
+        } else if !command.redirections.is_empty() || !pipeline.commands.is_empty() {
+            return Err("Синтаксическая ошибка: пропущена команда".to_string());
+        }
+        if !pipeline.commands.is_empty() {
+            list.items.push(ListItem { connector, pipeline, background: false });
+        }
+        
+        if list.items.is_empty() {
+            return Err("Пустая команда".to_string());
+        }
+        
+        Ok(list)
     }
-    
-    fn is_internal_command(&self, cmd: &str) -> bool {
-        matches!(cmd, 
-            "help" | "bash-help" | "bash-quick" | "history" | "clear" | 
-            "exit" | "quit" | "elevate" | "privileges" | "ui" | "gui" |
-            "nowelcome" | "welcome"
-        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shell(input: &str) -> CommandList {
+        match CommandParser::new().parse(input).unwrap().cmd_type {
+            CommandType::Shell(list) => list,
+            other => panic!("ожидался Shell, получено {:?}", other),
+        }
     }
-    
-    pub fn parse_args(&self, args: &[String]) -> Vec<String> {
-        args.to_vec()
+
+    #[test]
+    fn test_pipeline() {
+        let list = shell("ls -la | grep foo | wc -l");
+        assert_eq!(list.items.len(), 1);
+        let commands = &list.items[0].pipeline.commands;
+        assert_eq!(commands.len(), 3);
+        assert_eq!(commands[1].words[1].to_plain(), "foo");
+    }
+
+    #[test]
+    fn test_command_list_connectors() {
+        let list = shell("a && b || c; d");
+        let connectors: Vec<Connector> = list.items.iter().map(|i| i.connector).collect();
+        assert_eq!(connectors, vec![Connector::Always, Connector::And, Connector::Or, Connector::Always]);
+    }
+
+    #[test]
+    fn test_redirections() {
+        let list = shell("make > build.log 2>&1");
+        let command = &list.items[0].pipeline.commands[0];
+        assert_eq!(command.redirections, vec![
+            Redirection::Output { fd: 1, target: Word::literal("build.log"), append: false },
+            Redirection::Duplicate { fd: 2, target_fd: 1 },
+        ]);
+    }
+
+    #[test]
+    fn test_git_with_quoted_message() {
+        // Слова git раскрываются при выполнении, как у любой команды
+        let list = shell("git commit -m \"fix $ISSUE\" ~/notes");
+        let words = &list.items[0].pipeline.commands[0].words;
+        assert_eq!(words.len(), 5);
+        assert_eq!(words[3].to_plain(), "fix $ISSUE");
+        assert_eq!(words[3].parts[0].quote, QuoteKind::Double);
+        assert_eq!(words[4].to_plain(), "~/notes");
+    }
+
+    #[test]
+    fn test_git_words_keep_patterns() {
+        // Фигурные скобки и шаблоны git раскрываются вместе с остальными командами
+        let list = shell("git add src/{a,b}.rs *.rs");
+        let words = &list.items[0].pipeline.commands[0].words;
+        let braces: Vec<String> = crate::terminal::glob::expand_braces(&words[2]).unwrap().iter().map(Word::to_plain).collect();
+        assert_eq!(braces, vec!["src/a.rs", "src/b.rs"]);
+        assert_eq!(words[3].parts[0].quote, QuoteKind::None);
+    }
+
+    #[test]
+    fn test_assignments() {
+        let list = shell("FOO=bar BAZ=\"a b\" env");
+        let command = &list.items[0].pipeline.commands[0];
+        assert_eq!(command.assignments.len(), 2);
+        assert_eq!(command.assignments[0].name, "FOO");
+        assert_eq!(command.assignments[1].value.to_plain(), "a b");
+        assert_eq!(command.words[0].to_plain(), "env");
+        
+        // После имени команды NAME=value - обычный аргумент
+        let list = shell("make CC=clang");
+        assert_eq!(list.items[0].pipeline.commands[0].words.len(), 2);
+    }
+
+    #[test]
+    fn test_function_definition() {
+        assert_eq!(
+            parse_function_definition("mkcd() { mkdir -p \"$1\" && cd \"$1\"; }"),
+            Some(("mkcd".to_string(), "mkdir -p \"$1\" && cd \"$1\"".to_string()))
+        );
+        assert_eq!(
+            parse_function_definition("function greet {\n  echo hi\n  echo $1\n}"),
+            Some(("greet".to_string(), "echo hi\n  echo $1".to_string()))
+        );
+        assert_eq!(parse_function_definition("echo {a,b}"), None);
+        assert_eq!(parse_function_definition("f() { }"), None);
+        
+        match CommandParser::new().parse("f () { ls; }").unwrap().cmd_type {
+            CommandType::Function(name, body) => assert_eq!((name.as_str(), body.as_str()), ("f", "ls")),
+            other => panic!("ожидалась Function, получено {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_background() {
+        let list = shell("sleep 10 & echo done");
+        assert_eq!(list.items.len(), 2);
+        assert!(list.items[0].background);
+        assert!(!list.items[1].background);
+        assert_eq!(list.items[1].connector, Connector::Always);
+        
+        let list = shell("make | tee log &");
+        assert_eq!(list.items.len(), 1);
+        assert!(list.items[0].background);
+        
+        let parser = CommandParser::new();
+        assert!(parser.parse("& ls").is_err());
+        assert!(parser.parse("a && b &").is_err());
+    }
+
+    #[test]
+    fn test_runner_prefixes() {
+        let parser = CommandParser::new();
+        match parser.parse("!python  print(1)").unwrap().cmd_type {
+            CommandType::Runner(name, code) => assert_eq!((name.as_str(), code.as_str()), ("python", "print(1)")),
+            other => panic!("ожидался Runner, получено {:?}", other),
+        }
+        assert!(parser.parse("!python").is_err());
+        assert_eq!(parser.split_runner("!rust"), Some(("rust".to_string(), String::new())));
+        assert_eq!(parser.split_runner("!pythonx 1"), None);
+        assert_eq!(parser.split_runner("!bash ls"), None);
+        assert!(parser.has_bang_prefix("!bash ls") && parser.has_bang_prefix("  !rust"));
+        assert!(!parser.has_bang_prefix("!rustc") && !parser.has_bang_prefix("!!"));
+
+        // Исполнители из конфигурации заменяют встроенные
+        let parser = CommandParser::with_runners(vec![("node".to_string(), "JavaScript".to_string())]);
+        assert_eq!(parser.split_runner("!node 1"), Some(("node".to_string(), "1".to_string())));
+        assert_eq!(parser.split_runner("!python 1"), None);
+        assert!(parser.bang_prefixes().iter().any(|(prefix, _)| prefix == "!bash"));
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        let parser = CommandParser::new();
+        assert!(parser.parse("| grep foo").is_err());
+        assert!(parser.parse("ls &&").is_err());
+        assert!(parser.parse("cat <").is_err());
     }
 }
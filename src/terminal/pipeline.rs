@@ -0,0 +1,492 @@
+//! Запуск конвейеров: соединение процессов каналами и перенаправления
+
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, PipeReader, PipeWriter, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use crate::terminal::{ExitStatus, ResourceUsage};
+use crate::terminal::jobs::{Capture, Job, Process, ProcessStatus};
+use crate::terminal::pty::{self, JobTerminal};
+
+/// Перенаправление с уже вычисленным именем файла
+#[derive(Debug, Clone)]
+pub enum StageRedirect {
+    Input { fd: u32, path: String },
+    Output { fd: u32, path: String, append: bool },
+    Duplicate { fd: u32, target_fd: u32 },
+}
+
+/// Подготовленная к запуску стадия конвейера
+#[derive(Debug, Clone)]
+pub struct Stage {
+    pub program: String,
+    pub args: Vec<String>,
+    pub env: HashMap<String, String>,
+    pub redirects: Vec<StageRedirect>,
+}
+
+impl Stage {
+    /// Перенаправлен ли вывод дескриптора fd (в файл или в другой дескриптор)
+    fn redirects_output(&self, fd: u32) -> bool {
+        self.redirects.iter().any(|redirect| match redirect {
+            StageRedirect::Output { fd: target, .. } | StageRedirect::Duplicate { fd: target, .. } => *target == fd,
+            StageRedirect::Input { .. } => false,
+        })
+    }
+}
+
+/// Результат выполнения конвейера
+#[derive(Debug, Clone)]
+pub struct PipelineOutput {
+    /// Статус последней стадии
+    pub status: ExitStatus,
+    /// Копии stdout и stderr, уже показанных в терминале
+    pub stdout: String,
+    pub stderr: String,
+    /// Ошибки запуска, которые еще не показаны
+    pub errors: String,
+    /// Время выполнения и сумма ресурсов всех стадий
+    pub usage: ResourceUsage,
+}
+
+/// Куда направлен вывод дочернего процесса
+enum OutputTarget {
+    Inherit,
+    Pipe(PipeWriter),
+    File(File),
+}
+
+impl OutputTarget {
+    fn try_clone(&self) -> io::Result<Self> {
+        Ok(match self {
+            OutputTarget::Inherit => OutputTarget::Inherit,
+            OutputTarget::Pipe(writer) => OutputTarget::Pipe(writer.try_clone()?),
+            OutputTarget::File(file) => OutputTarget::File(file.try_clone()?),
+        })
+    }
+
+    fn into_stdio(self) -> Stdio {
+        match self {
+            OutputTarget::Inherit => Stdio::inherit(),
+            OutputTarget::Pipe(writer) => writer.into(),
+            OutputTarget::File(file) => file.into(),
+        }
+    }
+}
+
+/// Откуда дочерний процесс читает ввод
+enum InputSource {
+    Inherit,
+    Null,
+    Pipe(PipeReader),
+    File(File),
+}
+
+impl InputSource {
+    fn into_stdio(self) -> Stdio {
+        match self {
+            InputSource::Inherit => Stdio::inherit(),
+            InputSource::Null => Stdio::null(),
+            InputSource::Pipe(reader) => reader.into(),
+            InputSource::File(file) => file.into(),
+        }
+    }
+}
+
+/// Потоки одной стадии после применения перенаправлений
+struct StageStreams {
+    stdin: InputSource,
+    stdout: OutputTarget,
+    stderr: OutputTarget,
+}
+
+impl StageStreams {
+    fn apply(&mut self, redirect: &StageRedirect) -> Result<(), String> {
+        match redirect {
+            StageRedirect::Input { fd: 0, path } => {
+                let file = File::open(path)
+                    .map_err(|e| format!("{}: {}", path, e))?;
+                self.stdin = InputSource::File(file);
+            }
+            StageRedirect::Output { fd, path, append } => {
+                let file = OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .append(*append)
+                    .truncate(!*append)
+                    .open(path)
+                    .map_err(|e| format!("{}: {}", path, e))?;
+                *self.output_mut(*fd)? = OutputTarget::File(file);
+            }
+            StageRedirect::Duplicate { fd, target_fd } => {
+                let target = match target_fd {
+                    1 => self.stdout.try_clone(),
+                    2 => self.stderr.try_clone(),
+                    other => return Err(format!("Перенаправление в дескриптор {} не поддерживается", other)),
+                }
+                .map_err(|e| format!("Ошибка дублирования дескриптора: {}", e))?;
+                *self.output_mut(*fd)? = target;
+            }
+            StageRedirect::Input { fd, .. } => {
+                return Err(format!("Перенаправление ввода в дескриптор {} не поддерживается", fd));
+            }
+        }
+        Ok(())
+    }
+
+    fn output_mut(&mut self, fd: u32) -> Result<&mut OutputTarget, String> {
+        match fd {
+            1 => Ok(&mut self.stdout),
+            2 => Ok(&mut self.stderr),
+            other => Err(format!("Перенаправление дескриптора {} не поддерживается", other)),
+        }
+    }
+}
+
+/// Пишет текст в файл перенаправления. Текст, который остался на терминале,
+/// возвращается для показа
+fn write_text(target: &mut OutputTarget, text: &str) -> Result<Option<String>, String> {
+    let file = match target {
+        OutputTarget::File(file) => file,
+        OutputTarget::Inherit | OutputTarget::Pipe(_) => return Ok(Some(text.to_string())),
+    };
+    if !text.is_empty() {
+        writeln!(file, "{}", text).map_err(|e| format!("Ошибка записи: {}", e))?;
+    }
+    Ok(None)
+}
+
+/// Применяет перенаправления к выводу встроенной команды: то, что ушло в
+/// файлы, записывается, остальное (вывод и ошибка) возвращается для терминала
+pub fn redirect_text(redirects: &[StageRedirect], output: &str, error: Option<&str>) -> Result<(String, Option<String>), String> {
+    let mut streams = StageStreams { stdin: InputSource::Inherit, stdout: OutputTarget::Inherit, stderr: OutputTarget::Inherit };
+    for redirect in redirects {
+        streams.apply(redirect)?;
+    }
+    let output = write_text(&mut streams.stdout, output)?.unwrap_or_default();
+    let error = match error {
+        Some(error) => write_text(&mut streams.stderr, error)?,
+        None => None,
+    };
+    Ok((output, error))
+}
+
+/// Показывает вывод стадии по мере поступления и копирует его в transcript
+fn tee_in_background(mut reader: PipeReader, mut terminal: impl Write + Send + 'static, transcript: Arc<Mutex<Vec<u8>>>) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let mut buffer = [0u8; 4096];
+        loop {
+            match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => {
+                    let _ = terminal.write_all(&buffer[..read]);
+                    let _ = terminal.flush();
+                    pty::append_transcript(&transcript, &buffer[..read]);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            }
+        }
+    })
+}
+
+/// Как запускается конвейер
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LaunchMode {
+    /// Фоновый конвейер пишет прямо в терминал, вывод переднего плана
+    /// показывается и сохраняется в transcript
+    pub background: bool,
+    /// Отдельная группа процессов и передача ей терминала (интерактивный режим)
+    pub job_control: bool,
+}
+
+/// Текст задания для jobs: стадии через |
+fn describe(stages: &[Stage]) -> String {
+    stages.iter()
+        .map(|stage| std::iter::once(&stage.program).chain(&stage.args).cloned().collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// Настраивает группу процессов и сигналы дочернего процесса. С own_terminal
+/// процесс открывает новую сессию, где псевдотерминал - управляющий терминал
+#[cfg(unix)]
+fn setup_process_group(command: &mut Command, pgid: Option<i32>, mode: LaunchMode, own_terminal: bool) {
+    use std::os::unix::process::CommandExt;
+
+    if !mode.job_control {
+        return;
+    }
+    // Лидер группы не может открыть сессию, поэтому группа задается только без псевдотерминала.
+    // Первая стадия создает группу, остальные присоединяются к ней
+    if !own_terminal {
+        command.process_group(pgid.unwrap_or(0));
+    }
+    let foreground = !mode.background;
+    // В pre_exec допустимы только async-signal-safe вызовы
+    unsafe {
+        command.pre_exec(move || {
+            if own_terminal {
+                // Ctrl+C и Ctrl+Z псевдотерминал доставит группе этого процесса
+                libc::setsid();
+                let _ = (0..3).any(|fd| libc::ioctl(fd, libc::TIOCSCTTY, 0) == 0);
+            } else if foreground {
+                libc::tcsetpgrp(libc::STDIN_FILENO, libc::getpgrp());
+            }
+            // Терминал игнорирует эти сигналы, а игнорирование наследуется через exec
+            for signal in [libc::SIGTSTP, libc::SIGTTIN, libc::SIGTTOU, libc::SIGINT, libc::SIGQUIT] {
+                libc::signal(signal, libc::SIG_DFL);
+            }
+            Ok(())
+        });
+    }
+}
+
+#[cfg(not(unix))]
+fn setup_process_group(_command: &mut Command, _pgid: Option<i32>, _mode: LaunchMode, _own_terminal: bool) {}
+
+/// Запускает стадии конвейера, соединяя stdout каждой со stdin следующей.
+/// В интерактивном режиме stdout последней стадии и stderr всех стадий идут
+/// через отдельные псевдотерминалы: программы видят терминал (vim, less,
+/// grep --color=auto), вывод показывается сразу и копируется в задание.
+/// Одиночная команда открывает на псевдотерминале свою сессию и получает
+/// с него ввод, конвейер читает настоящий терминал. Без терминала вывод
+/// идет через каналы, фоновый конвейер пишет прямо в терминал. Ожидание - в JobTable
+pub fn spawn(stages: &[Stage], mode: LaunchMode) -> Result<Job, String> {
+    launch(stages, None, mode)
+}
+
+/// Как spawn, но первая стадия читает input - вывод встроенной команды,
+/// стоящей в начале конвейера (history | grep x)
+pub fn spawn_with_input(stages: &[Stage], input: String, mode: LaunchMode) -> Result<Job, String> {
+    launch(stages, Some(input), mode)
+}
+
+fn launch(stages: &[Stage], input: Option<String>, mode: LaunchMode) -> Result<Job, String> {
+    if stages.is_empty() {
+        return Err("Пустой конвейер".to_string());
+    }
+    let last = stages.len() - 1;
+
+    // Псевдотерминал нужен только для вывода, который не ушел в файл.
+    // Если их не хватило, вывод идет через каналы
+    let interactive = !mode.background && mode.job_control && pty::is_terminal();
+    let open_pty = |fd| if interactive && !stages[last].redirects_output(fd) { pty::open().ok() } else { None };
+    let (output_pty, errors_pty) = (open_pty(1), open_pty(2));
+    // Одиночная команда получает псевдотерминал как управляющий, если ее
+    // ввод не занят выводом встроенной команды
+    let own_terminal = stages.len() == 1 && input.is_none() && output_pty.is_some();
+
+    let pipe_error = |e: io::Error| format!("Не удалось создать канал: {}", e);
+    let slave_error = |e: io::Error| format!("Не удалось открыть псевдотерминал: {}", e);
+    let output_pipe = if mode.background || output_pty.is_some() { None } else { Some(io::pipe().map_err(pipe_error)?) };
+    let errors_pipe = if mode.background || errors_pty.is_some() { None } else { Some(io::pipe().map_err(pipe_error)?) };
+
+    let mut processes = Vec::with_capacity(stages.len());
+    let mut pgid: Option<i32> = None;
+    let mut spawn_errors = String::new();
+    // Фоновое задание без управления заданиями не должно читать с терминала
+    let mut next_stdin = if mode.background && !mode.job_control { InputSource::Null } else { InputSource::Inherit };
+    if let Some(input) = input {
+        // Канал заполняется из отдельного потока: вывод может не поместиться в буфер канала
+        let (reader, mut writer) = io::pipe().map_err(pipe_error)?;
+        thread::spawn(move || {
+            let _ = writer.write_all(input.as_bytes());
+        });
+        next_stdin = InputSource::Pipe(reader);
+    }
+
+    for (index, stage) in stages.iter().enumerate() {
+        let mut stdin = std::mem::replace(&mut next_stdin, InputSource::Inherit);
+        let stdout = if index == last {
+            match (&output_pty, &output_pipe) {
+                (Some((_, slave)), _) => {
+                    if own_terminal {
+                        stdin = InputSource::File(slave.try_clone().map_err(slave_error)?);
+                    }
+                    OutputTarget::File(slave.try_clone().map_err(slave_error)?)
+                }
+                (None, Some((_, writer))) => OutputTarget::Pipe(writer.try_clone().map_err(pipe_error)?),
+                (None, None) => OutputTarget::Inherit,
+            }
+        } else {
+            let (reader, writer) = io::pipe().map_err(pipe_error)?;
+            next_stdin = InputSource::Pipe(reader);
+            OutputTarget::Pipe(writer)
+        };
+        let stderr = match (&errors_pty, &errors_pipe) {
+            (Some((_, slave)), _) => OutputTarget::File(slave.try_clone().map_err(slave_error)?),
+            (None, Some((_, writer))) => OutputTarget::Pipe(writer.try_clone().map_err(pipe_error)?),
+            (None, None) => OutputTarget::Inherit,
+        };
+
+        let failed = |code| Process { pid: None, status: ProcessStatus::Exited(code) };
+        let mut streams = StageStreams { stdin, stdout, stderr };
+        if let Err(e) = stage.redirects.iter().try_for_each(|r| streams.apply(r)) {
+            spawn_errors.push_str(&format!("{}\n", e));
+            processes.push(failed(1));
+            continue;
+        }
+
+        // Command держит копии дескрипторов до конца итерации,
+        // после чего они закрываются и соседние стадии получают EOF
+        let mut command = Command::new(&stage.program);
+        command
+            .args(&stage.args)
+            .env_clear()
+            .envs(&stage.env)
+            .stdin(streams.stdin.into_stdio())
+            .stdout(streams.stdout.into_stdio())
+            .stderr(streams.stderr.into_stdio());
+        setup_process_group(&mut command, pgid, mode, own_terminal);
+
+        match command.spawn() {
+            Ok(child) => {
+                let pid = child.id() as i32;
+                pgid.get_or_insert(pid);
+                processes.push(Process { pid: Some(pid), status: ProcessStatus::Running });
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                spawn_errors.push_str(&format!("{}: команда не найдена\n", stage.program));
+                processes.push(failed(127));
+            }
+            Err(e) => {
+                spawn_errors.push_str(&format!("{}: {}\n", stage.program, e));
+                processes.push(failed(126));
+            }
+        }
+    }
+
+    let capture = if mode.background {
+        // У фонового задания ошибки запуска сразу идут в терминал
+        eprint!("{}", spawn_errors);
+        None
+    } else {
+        let (stdout, stderr) = (Arc::new(Mutex::new(Vec::new())), Arc::new(Mutex::new(Vec::new())));
+        // Закрываем свои концы каналов, иначе чтение никогда не завершится
+        let mut readers = Vec::new();
+        if let Some((reader, writer)) = output_pipe {
+            drop(writer);
+            readers.push(tee_in_background(reader, io::stdout(), stdout.clone()));
+        }
+        if let Some((reader, writer)) = errors_pipe {
+            drop(writer);
+            readers.push(tee_in_background(reader, io::stderr(), stderr.clone()));
+        }
+        Some(Capture { readers, stdout, stderr, spawn_errors })
+    };
+
+    let pgid = if mode.job_control { pgid } else { None };
+    let job = Job::new(describe(stages), pgid, processes, capture);
+    if output_pty.is_none() && errors_pty.is_none() {
+        return Ok(job);
+    }
+    // Подчиненные стороны остаются только у дочерних процессов
+    Ok(job.with_terminal(JobTerminal {
+        output: output_pty.map(|(pty, _slave)| pty),
+        errors: errors_pty.map(|(pty, _slave)| pty),
+        owns_input: own_terminal,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terminal::jobs::{Foreground, JobTable};
+
+    fn stage(program: &str, args: &[&str]) -> Stage {
+        Stage {
+            program: program.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            env: std::env::vars().collect(),
+            redirects: Vec::new(),
+        }
+    }
+
+    fn run(stages: &[Stage]) -> PipelineOutput {
+        let mode = LaunchMode { background: false, job_control: false };
+        let job = spawn(stages, mode).unwrap();
+        match JobTable::new().foreground(job).unwrap() {
+            Foreground::Finished(output) => output,
+            Foreground::Stopped(_) => panic!("задание остановлено"),
+        }
+    }
+
+    #[test]
+    fn test_two_stage_pipe() {
+        let output = run(&[stage("printf", &["b\\na\\n"]), stage("sort", &[])]);
+        assert_eq!(output.stdout, "a\nb\n");
+        assert_eq!(output.status, ExitStatus::Code(0));
+    }
+
+    #[test]
+    fn test_status_of_last_stage() {
+        assert_eq!(run(&[stage("false", &[]), stage("true", &[])]).status, ExitStatus::Code(0));
+        assert_eq!(run(&[stage("true", &[]), stage("sh", &["-c", "exit 3"])]).status, ExitStatus::Code(3));
+    }
+
+    #[test]
+    fn test_input_of_first_stage() {
+        let mode = LaunchMode { background: false, job_control: false };
+        let job = spawn_with_input(&[stage("sort", &[])], "b\na\n".to_string(), mode).unwrap();
+        match JobTable::new().foreground(job).unwrap() {
+            Foreground::Finished(output) => assert_eq!(output.stdout, "a\nb\n"),
+            Foreground::Stopped(_) => panic!("задание остановлено"),
+        }
+    }
+
+    #[test]
+    fn test_redirect_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out").to_string_lossy().to_string();
+
+        let redirects = [StageRedirect::Output { fd: 1, path: path.clone(), append: false }];
+        let (output, error) = redirect_text(&redirects, "a=1", Some("ошибка")).unwrap();
+        assert_eq!(output, "");
+        assert_eq!(error.as_deref(), Some("ошибка"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "a=1\n");
+
+        // > f 2>&1: ошибка дописывается в тот же файл
+        let redirects = [
+            StageRedirect::Output { fd: 1, path: path.clone(), append: true },
+            StageRedirect::Duplicate { fd: 2, target_fd: 1 },
+        ];
+        let (_, error) = redirect_text(&redirects, "b=2", Some("ошибка")).unwrap();
+        assert_eq!(error, None);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "a=1\nb=2\nошибка\n");
+
+        // Вывод без перенаправления остается для терминала
+        let redirects = [StageRedirect::Output { fd: 2, path: "/nonexistent/dir/f".to_string(), append: false }];
+        assert!(redirect_text(&redirects, "x", None).is_err());
+    }
+
+    #[test]
+    fn test_stderr_kept_separate() {
+        let output = run(&[stage("sh", &["-c", "echo out; echo err >&2"])]);
+        assert_eq!(output.stdout, "out\n");
+        assert_eq!(output.stderr, "err\n");
+
+        // stderr первых стадий тоже сохраняется
+        let output = run(&[stage("sh", &["-c", "echo first >&2"]), stage("cat", &[])]);
+        assert_eq!(output.stderr, "first\n");
+    }
+
+    #[test]
+    fn test_redirected_output() {
+        let mut redirected = stage("echo", &["x"]);
+        assert!(!redirected.redirects_output(1));
+        redirected.redirects.push(StageRedirect::Duplicate { fd: 1, target_fd: 2 });
+        assert!(redirected.redirects_output(1));
+        assert!(!redirected.redirects_output(2));
+    }
+
+    #[test]
+    fn test_missing_command_in_the_middle() {
+        let output = run(&[stage("echo", &["x"]), stage("no-such-command-st", &[]), stage("wc", &["-c"])]);
+        assert_eq!(output.errors, "no-such-command-st: команда не найдена\n");
+        assert_eq!(output.stdout.trim(), "0");
+        assert_eq!(output.status, ExitStatus::Code(0));
+    }
+}
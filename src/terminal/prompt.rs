@@ -0,0 +1,427 @@
+//! Шаблон приглашения: текст с сегментами `{user}@{host} {cwd:short} {git}`.
+//! `{сегмент|стиль}` - цвет одного сегмента, `{color:стиль}` и `{reset}` -
+//! цвет следующего текста. Стиль - как в [colors] ("bold green", "#ff8800")
+//! или цвет схемы: prompt_user, prompt_directory, error, success, warning, info.
+//! `( ... )` - условная группа: скрывается, если все сегменты в ней пусты.
+//! `\{`, `\(` и т.п. - обычные символы
+
+use std::path::Path;
+use std::time::Duration;
+use crate::git::manager::RepositoryStatus;
+use crate::terminal::status;
+use crate::utils::helpers::{Color, ColorScheme};
+use crate::utils::privileges::PrivilegeLevel;
+
+/// Приглашение по умолчанию (пустой prompt в конфигурации)
+pub const DEFAULT_TEMPLATE: &str = "({duration|warning} ){color:prompt_user}{privilege:icon} {user}@{host}:\
+{color:prompt_directory}{cwd}{reset}( {git|info})( {status|error}){privilege} ";
+
+/// Цвета схемы, которые можно указать как стиль
+const SCHEME_STYLES: &[&str] = &["prompt_user", "prompt_directory", "error", "success", "warning", "info"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Segment {
+    User,
+    /// Имя хоста до первой точки
+    Host,
+    /// Текущая директория, домашняя - как ~
+    Cwd,
+    CwdFull,
+    /// Последний компонент пути
+    CwdShort,
+    /// Ветка, опережение/отставание и * при изменениях
+    Git,
+    /// Код завершения предыдущей команды, если он не 0
+    Status,
+    /// Длительность предыдущей команды, если она превысила порог
+    Duration,
+    /// # для root, $ для пользователя
+    Privilege,
+    PrivilegeIcon,
+    Time,
+}
+
+impl Segment {
+    fn parse(name: &str, arg: Option<&str>) -> Option<Self> {
+        Some(match (name, arg) {
+            ("user", None) => Segment::User,
+            ("host", None) => Segment::Host,
+            ("cwd", None) => Segment::Cwd,
+            ("cwd", Some("full")) => Segment::CwdFull,
+            ("cwd", Some("short")) => Segment::CwdShort,
+            ("git", None) => Segment::Git,
+            ("status", None) => Segment::Status,
+            ("duration", None) => Segment::Duration,
+            ("privilege", None) => Segment::Privilege,
+            ("privilege", Some("icon")) => Segment::PrivilegeIcon,
+            ("time", None) => Segment::Time,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Text(String),
+    Segment { segment: Segment, style: Option<String> },
+    /// Цвет текста до {reset}
+    Color(String),
+    Reset,
+    Group(Vec<Node>),
+}
+
+/// Разобранный шаблон приглашения
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptTemplate {
+    nodes: Vec<Node>,
+}
+
+/// Значения сегментов
+pub struct PromptContext<'a> {
+    pub user: &'a str,
+    pub host: &'a str,
+    pub cwd: &'a str,
+    pub home: Option<&'a str>,
+    pub privilege: PrivilegeLevel,
+    pub exit_code: i32,
+    /// Длительность предыдущей команды, если она была медленной
+    pub duration: Option<Duration>,
+    /// None - не репозиторий или git не ответил вовремя
+    pub git: Option<&'a RepositoryStatus>,
+    pub colors: &'a ColorScheme,
+}
+
+/// Результат отрисовки узлов: текст и сколько сегментов в нем непусты
+struct Rendered {
+    text: String,
+    segments: usize,
+    filled: usize,
+}
+
+impl Default for PromptTemplate {
+    fn default() -> Self {
+        Self::parse(DEFAULT_TEMPLATE).expect("шаблон приглашения по умолчанию")
+    }
+}
+
+impl PromptTemplate {
+    pub fn parse(template: &str) -> Result<Self, String> {
+        // Вершина стека - узлы текущей группы
+        let mut groups: Vec<Vec<Node>> = vec![Vec::new()];
+        let mut text = String::new();
+        let mut chars = template.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => text.push(chars.next().unwrap_or('\\')),
+                '{' => {
+                    let mut spec = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => spec.push(c),
+                            None => return Err(format!("незакрытая скобка в '{{{}'", spec)),
+                        }
+                    }
+                    Self::flush_text(&mut text, &mut groups);
+                    let node = Self::parse_spec(&spec)?;
+                    Self::current(&mut groups).push(node);
+                }
+                '(' => {
+                    Self::flush_text(&mut text, &mut groups);
+                    groups.push(Vec::new());
+                }
+                ')' => {
+                    if groups.len() == 1 {
+                        return Err("лишняя ')' в шаблоне приглашения".to_string());
+                    }
+                    Self::flush_text(&mut text, &mut groups);
+                    let group = groups.pop().unwrap_or_default();
+                    Self::current(&mut groups).push(Node::Group(group));
+                }
+                _ => text.push(c),
+            }
+        }
+
+        if groups.len() > 1 {
+            return Err("незакрытая '(' в шаблоне приглашения".to_string());
+        }
+        Self::flush_text(&mut text, &mut groups);
+        Ok(Self { nodes: groups.pop().unwrap_or_default() })
+    }
+
+    fn current(groups: &mut Vec<Vec<Node>>) -> &mut Vec<Node> {
+        if groups.is_empty() {
+            groups.push(Vec::new());
+        }
+        let last = groups.len() - 1;
+        &mut groups[last]
+    }
+
+    fn flush_text(text: &mut String, groups: &mut Vec<Vec<Node>>) {
+        if !text.is_empty() {
+            Self::current(groups).push(Node::Text(std::mem::take(text)));
+        }
+    }
+
+    /// name, name:arg, name|style, color:style, reset
+    fn parse_spec(spec: &str) -> Result<Node, String> {
+        let (name, style) = match spec.split_once('|') {
+            Some((name, style)) => (name, Some(style.trim())),
+            None => (spec, None),
+        };
+        let (name, arg) = match name.split_once(':') {
+            Some((name, arg)) => (name.trim(), Some(arg.trim())),
+            None => (name.trim(), None),
+        };
+        match (name, arg, style) {
+            ("color", Some(color), None) => {
+                check_style(color)?;
+                Ok(Node::Color(color.to_string()))
+            }
+            ("reset", None, None) => Ok(Node::Reset),
+            _ => {
+                let segment = Segment::parse(name, arg)
+                    .ok_or_else(|| format!("неизвестный сегмент '{{{}}}'", spec))?;
+                if let Some(style) = style {
+                    check_style(style)?;
+                }
+                Ok(Node::Segment { segment, style: style.map(str::to_string) })
+            }
+        }
+    }
+
+    /// Нужен ли шаблону статус git (его получение - самая долгая часть)
+    pub fn uses_git(&self) -> bool {
+        fn contains(nodes: &[Node]) -> bool {
+            nodes.iter().any(|node| match node {
+                Node::Segment { segment, .. } => *segment == Segment::Git,
+                Node::Group(children) => contains(children),
+                _ => false,
+            })
+        }
+        contains(&self.nodes)
+    }
+
+    pub fn render(&self, context: &PromptContext) -> String {
+        render_nodes(&self.nodes, context).text
+    }
+}
+
+fn check_style(style: &str) -> Result<(), String> {
+    if SCHEME_STYLES.contains(&style) || Color::parse(style).is_some() {
+        Ok(())
+    } else {
+        Err(format!("неизвестный цвет '{}'", style))
+    }
+}
+
+fn style_code(style: &str, colors: &ColorScheme) -> String {
+    match style {
+        "prompt_user" => colors.prompt_user.clone(),
+        "prompt_directory" => colors.prompt_directory.clone(),
+        "error" => colors.error.clone(),
+        "success" => colors.success.clone(),
+        "warning" => colors.warning.clone(),
+        "info" => colors.info.clone(),
+        _ => Color::parse(style).unwrap_or_default(),
+    }
+}
+
+fn render_nodes(nodes: &[Node], context: &PromptContext) -> Rendered {
+    let mut rendered = Rendered { text: String::new(), segments: 0, filled: 0 };
+    for node in nodes {
+        match node {
+            Node::Text(text) => rendered.text.push_str(text),
+            Node::Segment { segment, style } => {
+                rendered.segments += 1;
+                let value = segment_value(*segment, context);
+                if value.is_empty() {
+                    continue;
+                }
+                rendered.filled += 1;
+                match style {
+                    Some(style) => {
+                        rendered.text.push_str(&style_code(style, context.colors));
+                        rendered.text.push_str(&value);
+                        rendered.text.push_str(Color::RESET);
+                    }
+                    None => rendered.text.push_str(&value),
+                }
+            }
+            Node::Color(style) => rendered.text.push_str(&style_code(style, context.colors)),
+            Node::Reset => rendered.text.push_str(Color::RESET),
+            Node::Group(children) => {
+                let group = render_nodes(children, context);
+                rendered.segments += group.segments;
+                if group.segments > 0 && group.filled == 0 {
+                    continue;
+                }
+                rendered.filled += group.filled;
+                rendered.text.push_str(&group.text);
+            }
+        }
+    }
+    rendered
+}
+
+fn segment_value(segment: Segment, context: &PromptContext) -> String {
+    match segment {
+        Segment::User => context.user.to_string(),
+        Segment::Host => context.host.split('.').next().unwrap_or(context.host).to_string(),
+        Segment::Cwd => abbreviate_home(context.cwd, context.home),
+        Segment::CwdFull => context.cwd.to_string(),
+        Segment::CwdShort => {
+            if context.home.is_some_and(|home| home == context.cwd) {
+                "~".to_string()
+            } else {
+                Path::new(context.cwd).file_name()
+                    .map_or_else(|| context.cwd.to_string(), |name| name.to_string_lossy().to_string())
+            }
+        }
+        Segment::Git => context.git.map(format_git).unwrap_or_default(),
+        Segment::Status if context.exit_code != 0 => format!("✘ {}", context.exit_code),
+        Segment::Status => String::new(),
+        Segment::Duration => context.duration
+            .map(|duration| format!("⏱ {}", status::format_duration(duration)))
+            .unwrap_or_default(),
+        Segment::Privilege => match context.privilege {
+            PrivilegeLevel::Root | PrivilegeLevel::Admin => "#".to_string(),
+            _ => "$".to_string(),
+        },
+        Segment::PrivilegeIcon => match context.privilege {
+            PrivilegeLevel::Root | PrivilegeLevel::Admin => "🔴".to_string(),
+            PrivilegeLevel::User => "🟢".to_string(),
+            PrivilegeLevel::Unknown => "⚪".to_string(),
+        },
+        Segment::Time => chrono::Local::now().format("%H:%M:%S").to_string(),
+    }
+}
+
+/// /home/user/src -> ~/src
+fn abbreviate_home(cwd: &str, home: Option<&str>) -> String {
+    let home = match home.map(|home| home.trim_end_matches('/')).filter(|home| !home.is_empty()) {
+        Some(home) => home,
+        None => return cwd.to_string(),
+    };
+    match cwd.strip_prefix(home) {
+        Some("") => "~".to_string(),
+        Some(rest) if rest.starts_with('/') => format!("~{}", rest),
+        _ => cwd.to_string(),
+    }
+}
+
+/// main ↑1↓2 * - ветка (HEAD при отсоединенном HEAD), расхождение с
+/// upstream и * при незакоммиченных изменениях
+pub fn format_git(status: &RepositoryStatus) -> String {
+    let mut text = if status.branch.is_empty() { "HEAD".to_string() } else { status.branch.clone() };
+    if status.ahead > 0 || status.behind > 0 {
+        text.push(' ');
+        if status.ahead > 0 {
+            text.push_str(&format!("↑{}", status.ahead));
+        }
+        if status.behind > 0 {
+            text.push_str(&format!("↓{}", status.behind));
+        }
+    }
+    if !status.staged_files.is_empty() || !status.unstaged_files.is_empty() || !status.untracked_files.is_empty() {
+        text.push_str(" *");
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn context<'a>(colors: &'a ColorScheme, git: Option<&'a RepositoryStatus>) -> PromptContext<'a> {
+        PromptContext {
+            user: "alice",
+            host: "box.example.org",
+            cwd: "/home/alice/src/smart-term",
+            home: Some("/home/alice"),
+            privilege: PrivilegeLevel::User,
+            exit_code: 0,
+            duration: None,
+            git,
+            colors,
+        }
+    }
+
+    fn repository(branch: &str, ahead: usize, behind: usize, dirty: bool) -> RepositoryStatus {
+        RepositoryStatus {
+            branch: branch.to_string(),
+            upstream: None,
+            ahead,
+            behind,
+            staged_files: Vec::new(),
+            unstaged_files: Vec::new(),
+            untracked_files: if dirty { vec![PathBuf::from("new.txt")] } else { Vec::new() },
+        }
+    }
+
+    #[test]
+    fn test_segments() {
+        let colors = ColorScheme::default();
+        let template = PromptTemplate::parse("{user}@{host} {cwd:short} [{cwd}] {privilege}").unwrap();
+        assert_eq!(template.render(&context(&colors, None)), "alice@box smart-term [~/src/smart-term] $");
+        assert!(!template.uses_git());
+
+        let mut root = context(&colors, None);
+        root.privilege = PrivilegeLevel::Root;
+        root.cwd = "/home/alice";
+        assert_eq!(template.render(&root), "alice@box ~ [~] #");
+
+        let template = PromptTemplate::parse("\\{{cwd:full}\\}").unwrap();
+        assert_eq!(template.render(&context(&colors, None)), "{/home/alice/src/smart-term}");
+    }
+
+    #[test]
+    fn test_conditional_groups() {
+        let colors = ColorScheme::default();
+        let template = PromptTemplate::parse("{cwd:short}( on {git})( [{status}])> ").unwrap();
+        assert!(template.uses_git());
+        assert_eq!(template.render(&context(&colors, None)), "smart-term> ");
+
+        let status = repository("main", 1, 2, true);
+        let mut failed = context(&colors, Some(&status));
+        failed.exit_code = 130;
+        assert_eq!(template.render(&failed), "smart-term on main ↑1↓2 * [✘ 130]> ");
+
+        // Группа без сегментов показывается всегда, скобки - часть синтаксиса
+        let template = PromptTemplate::parse("(x) \\({user}\\)").unwrap();
+        assert_eq!(template.render(&context(&colors, None)), "x (alice)");
+    }
+
+    #[test]
+    fn test_styles() {
+        let colors = ColorScheme::default();
+        let template = PromptTemplate::parse("{user|bold red} {color:prompt_directory}{cwd:short}{reset}").unwrap();
+        assert_eq!(
+            template.render(&context(&colors, None)),
+            "\x1b[1;31malice\x1b[0m \x1b[1;34msmart-term\x1b[0m"
+        );
+        // Пустой сегмент не оставляет escape-последовательностей
+        let template = PromptTemplate::parse("{status|error}$").unwrap();
+        assert_eq!(template.render(&context(&colors, None)), "$");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(PromptTemplate::parse("{nope}").unwrap_err().contains("nope"));
+        assert!(PromptTemplate::parse("{cwd:tiny}").is_err());
+        assert!(PromptTemplate::parse("{user|purple}").unwrap_err().contains("purple"));
+        assert!(PromptTemplate::parse("{user").is_err());
+        assert!(PromptTemplate::parse("( {git}").is_err());
+        assert!(PromptTemplate::parse("{git})").is_err());
+        assert_eq!(PromptTemplate::default(), PromptTemplate::parse(DEFAULT_TEMPLATE).unwrap());
+    }
+
+    #[test]
+    fn test_format_git() {
+        assert_eq!(format_git(&repository("main", 0, 0, false)), "main");
+        assert_eq!(format_git(&repository("", 3, 0, false)), "HEAD ↑3");
+        assert_eq!(format_git(&repository("dev", 0, 0, true)), "dev *");
+    }
+}
@@ -0,0 +1,307 @@
+//! Псевдотерминал для интерактивных программ (vim, htop, less, ssh):
+//! программа видит настоящий терминал, вывод показывается сразу и
+//! сохраняется в текст для истории и UI. stderr идет через отдельный
+//! псевдотерминал, чтобы не смешиваться с stdout
+
+use std::fs::File;
+use std::io::{self, IsTerminal, Read, Write};
+use std::iter::Peekable;
+use std::os::fd::{AsRawFd, FromRawFd};
+use std::str::Chars;
+use std::sync::Mutex;
+use crossterm::terminal;
+use crate::terminal::signals;
+
+/// Как часто проверяется состояние задания, мс
+const POLL_INTERVAL_MS: i32 = 50;
+
+/// Ограничение размера сохраняемого вывода одной команды
+const TRANSCRIPT_LIMIT: usize = 1 << 20;
+
+/// Ведущая сторона псевдотерминала
+pub struct Pty {
+    master: File,
+    /// Последний переданный размер окна (строки, столбцы)
+    size: (u16, u16),
+}
+
+/// Подключены ли ввод и вывод терминала к настоящему терминалу
+pub fn is_terminal() -> bool {
+    io::stdin().is_terminal() && io::stdout().is_terminal()
+}
+
+fn window_size() -> libc::winsize {
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    unsafe {
+        libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size);
+    }
+    size
+}
+
+/// Открывает псевдотерминал с размером окна текущего терминала.
+/// Возвращает ведущую сторону и подчиненную - для дочернего процесса
+pub fn open() -> Result<(Pty, File), String> {
+    let size = window_size();
+    let mut master = -1;
+    let mut slave = -1;
+    if unsafe { libc::openpty(&mut master, &mut slave, std::ptr::null_mut(), std::ptr::null(), &size) } != 0 {
+        return Err(format!("Не удалось открыть псевдотерминал: {}", io::Error::last_os_error()));
+    }
+    // Дескрипторы не должны утекать в другие процессы; stdin/stdout/stderr
+    // дочернего процесса получают копии без этого флага
+    for fd in [master, slave] {
+        unsafe {
+            libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+        }
+    }
+    let (master, slave) = unsafe { (File::from_raw_fd(master), File::from_raw_fd(slave)) };
+    Ok((Pty { master, size: (size.ws_row, size.ws_col) }, slave))
+}
+
+impl Pty {
+    /// Передает псевдотерминалу новый размер окна; программа получит SIGWINCH
+    fn sync_size(&mut self) {
+        let size = window_size();
+        if (size.ws_row, size.ws_col) != self.size {
+            self.size = (size.ws_row, size.ws_col);
+            unsafe {
+                libc::ioctl(self.master.as_raw_fd(), libc::TIOCSWINSZ, &size);
+            }
+        }
+    }
+
+    fn has_output(&self) -> bool {
+        let mut fd = libc::pollfd { fd: self.master.as_raw_fd(), events: libc::POLLIN, revents: 0 };
+        unsafe { libc::poll(&mut fd, 1, 0) > 0 }
+    }
+
+    /// Показывает порцию вывода программы. false - псевдотерминал закрыт
+    /// (после выхода всех процессов чтение возвращает EIO)
+    fn copy_output(&mut self, buffer: &mut [u8], terminal: &mut dyn Write, transcript: &Mutex<Vec<u8>>) -> bool {
+        match self.master.read(buffer) {
+            Ok(0) => false,
+            Ok(read) => {
+                let _ = terminal.write_all(&buffer[..read]);
+                let _ = terminal.flush();
+                append_transcript(transcript, &buffer[..read]);
+                true
+            }
+            Err(e) => e.kind() == io::ErrorKind::Interrupted,
+        }
+    }
+}
+
+/// Псевдотерминалы задания переднего плана. Вывод копируется в transcript,
+/// только если он не перенаправлен в файл - иначе псевдотерминал не нужен
+pub struct JobTerminal {
+    /// stdout последней стадии
+    pub output: Option<Pty>,
+    /// stderr всех стадий
+    pub errors: Option<Pty>,
+    /// Процесс открыл сессию на output (одиночная команда): нажатия клавиш
+    /// передаются ему. Иначе задание читает настоящий терминал само
+    /// (less и fzf в конце конвейера открывают /dev/tty)
+    pub owns_input: bool,
+}
+
+impl JobTerminal {
+    /// Передает псевдотерминалам новый размер окна
+    pub fn sync_size(&mut self) {
+        self.output.iter_mut().chain(self.errors.iter_mut()).for_each(Pty::sync_size);
+    }
+
+    /// Показывает вывод задания, копируя его в transcript, и передает ему
+    /// нажатия клавиш, пока finished() не вернет true (завершение или Ctrl+Z)
+    pub fn pump(&mut self, stdout: &Mutex<Vec<u8>>, stderr: &Mutex<Vec<u8>>, mut finished: impl FnMut() -> bool) -> Result<(), String> {
+        if !self.owns_input {
+            return self.pump_output(stdout, stderr, &mut finished);
+        }
+        // Ctrl+C, Ctrl+Z и эхо обрабатывает псевдотерминал, а не наш терминал
+        terminal::enable_raw_mode().map_err(|e| format!("Не удалось включить raw режим: {}", e))?;
+        let result = self.pump_output(stdout, stderr, &mut finished);
+        let _ = terminal::disable_raw_mode();
+        result
+    }
+
+    fn pump_output(&mut self, stdout: &Mutex<Vec<u8>>, stderr: &Mutex<Vec<u8>>, finished: &mut impl FnMut() -> bool) -> Result<(), String> {
+        let mut stdin_open = self.owns_input;
+        let mut output_open = self.output.is_some();
+        let mut errors_open = self.errors.is_some();
+        let mut buffer = [0u8; 4096];
+        // Окно могло измениться, пока задание было остановлено
+        self.sync_size();
+
+        loop {
+            // SIGWINCH прерывает poll, так что новый размер передается сразу
+            if signals::take_resize() {
+                self.sync_size();
+            }
+
+            // Отрицательный дескриптор poll пропускает
+            let fd = |open: bool, pty: &Option<Pty>| match pty {
+                Some(pty) if open => pty.master.as_raw_fd(),
+                _ => -1,
+            };
+            let mut fds = [
+                libc::pollfd { fd: if stdin_open { libc::STDIN_FILENO } else { -1 }, events: libc::POLLIN, revents: 0 },
+                libc::pollfd { fd: fd(output_open, &self.output), events: libc::POLLIN, revents: 0 },
+                libc::pollfd { fd: fd(errors_open, &self.errors), events: libc::POLLIN, revents: 0 },
+            ];
+            if unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, POLL_INTERVAL_MS) } < 0 {
+                let error = io::Error::last_os_error();
+                if error.kind() != io::ErrorKind::Interrupted {
+                    return Err(format!("Ошибка ожидания ввода: {}", error));
+                }
+                continue;
+            }
+
+            if fds[0].revents != 0 {
+                // Читаем мимо буфера std::io::stdin, чтобы не отнять ввод у редактора строки
+                let read = unsafe { libc::read(libc::STDIN_FILENO, buffer.as_mut_ptr().cast(), buffer.len()) };
+                if read > 0 {
+                    if let Some(output) = &mut self.output {
+                        let _ = output.master.write_all(&buffer[..read as usize]);
+                    }
+                } else if read == 0 {
+                    stdin_open = false;
+                }
+            }
+            if fds[1].revents != 0 {
+                output_open = self.copy_output(&mut buffer, stdout);
+            }
+            if fds[2].revents != 0 {
+                errors_open = self.copy_errors(&mut buffer, stderr);
+            }
+
+            if finished() {
+                // Дочитываем то, что программа успела вывести перед завершением
+                while output_open && self.output.as_ref().is_some_and(Pty::has_output) {
+                    output_open = self.copy_output(&mut buffer, stdout);
+                }
+                while errors_open && self.errors.as_ref().is_some_and(Pty::has_output) {
+                    errors_open = self.copy_errors(&mut buffer, stderr);
+                }
+                return Ok(());
+            }
+        }
+    }
+
+    fn copy_output(&mut self, buffer: &mut [u8], transcript: &Mutex<Vec<u8>>) -> bool {
+        self.output.as_mut().is_some_and(|pty| pty.copy_output(buffer, &mut io::stdout().lock(), transcript))
+    }
+
+    fn copy_errors(&mut self, buffer: &mut [u8], transcript: &Mutex<Vec<u8>>) -> bool {
+        self.errors.as_mut().is_some_and(|pty| pty.copy_output(buffer, &mut io::stderr().lock(), transcript))
+    }
+}
+
+/// Добавляет вывод в transcript, сохраняя не больше TRANSCRIPT_LIMIT последних байт
+pub fn append_transcript(transcript: &Mutex<Vec<u8>>, chunk: &[u8]) {
+    let mut transcript = transcript.lock().unwrap_or_else(|e| e.into_inner());
+    transcript.extend_from_slice(chunk);
+    if transcript.len() > TRANSCRIPT_LIMIT {
+        let excess = transcript.len() - TRANSCRIPT_LIMIT;
+        transcript.drain(..excess);
+    }
+}
+
+/// Текст вывода без управляющих последовательностей: цвета и перемещения
+/// курсора отбрасываются, \r затирает строку (индикаторы прогресса),
+/// backspace удаляет символ, содержимое альтернативного экрана (vim, htop)
+/// не сохраняется
+pub fn clean_transcript(raw: &[u8]) -> String {
+    let text = String::from_utf8_lossy(raw);
+    let mut chars = text.chars().peekable();
+    let mut result = String::new();
+    let mut line_start = 0;
+    let mut alternate_screen = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match skip_escape(&mut chars).as_deref() {
+                Some("?1049h") | Some("?1047h") | Some("?47h") => alternate_screen = true,
+                Some("?1049l") | Some("?1047l") | Some("?47l") => alternate_screen = false,
+                _ => {}
+            },
+            _ if alternate_screen => {}
+            '\r' if chars.peek() != Some(&'\n') => result.truncate(line_start),
+            '\n' => {
+                result.push('\n');
+                line_start = result.len();
+            }
+            '\x08' => {
+                if result.len() > line_start {
+                    result.pop();
+                }
+            }
+            c if c.is_control() && c != '\t' => {}
+            c => result.push(c),
+        }
+    }
+    result
+}
+
+/// Пропускает escape-последовательность после ESC. Для CSI (ESC [)
+/// возвращает ее параметры вместе с финальным символом
+fn skip_escape(chars: &mut Peekable<Chars>) -> Option<String> {
+    match chars.next()? {
+        '[' => {
+            let mut sequence = String::new();
+            for c in chars.by_ref() {
+                sequence.push(c);
+                // Финальный символ CSI - из диапазона @..~
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+            Some(sequence)
+        }
+        // OSC (заголовок окна и т.п.) заканчивается BEL или ESC \
+        ']' => {
+            while let Some(c) = chars.next() {
+                if c == '\x07' {
+                    break;
+                }
+                if c == '\x1b' {
+                    chars.next();
+                    break;
+                }
+            }
+            None
+        }
+        // Выбор набора символов: ESC ( B
+        '(' | ')' => {
+            chars.next();
+            None
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_colors_and_progress() {
+        let raw = b"\x1b[1;32mok\x1b[0m\r\nDownloading 10%\rDownloading 100%\r\ndone\x1b]0;title\x07\n";
+        assert_eq!(clean_transcript(raw), "ok\nDownloading 100%\ndone\n");
+    }
+
+    #[test]
+    fn test_clean_backspace_and_alternate_screen() {
+        assert_eq!(clean_transcript(b"abx\x08c\n"), "abc\n");
+        let raw = b"before\n\x1b[?1049h\x1b[H\x1b[2Jvim screen\x1b[?1049lafter\n";
+        assert_eq!(clean_transcript(raw), "before\nafter\n");
+    }
+
+    #[test]
+    fn test_transcript_limit() {
+        let transcript = Mutex::new(Vec::new());
+        append_transcript(&transcript, &vec![b'a'; TRANSCRIPT_LIMIT]);
+        append_transcript(&transcript, b"tail");
+        let transcript = transcript.into_inner().unwrap();
+        assert_eq!(transcript.len(), TRANSCRIPT_LIMIT);
+        assert!(transcript.ends_with(b"tail"));
+    }
+}
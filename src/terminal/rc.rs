@@ -0,0 +1,131 @@
+//! Стартовый файл ~/.smarttermrc: aliases, функции, export и set -o,
+//! выполняемые перед первым приглашением
+
+use std::path::{Path, PathBuf};
+
+/// Команда из rc файла с номером строки, на которой она начинается
+#[derive(Debug, Clone, PartialEq)]
+pub struct RcCommand {
+    pub line: usize,
+    pub text: String,
+}
+
+/// ~/.smarttermrc
+pub fn default_rc_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".smarttermrc"))
+}
+
+/// Читает rc файл. Отсутствующий файл - не ошибка
+pub fn load(path: &Path) -> Result<Vec<RcCommand>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(source) => Ok(split_commands(&source)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("{}: {}", path.display(), e)),
+    }
+}
+
+/// Разбивает текст на команды: пропускает пустые строки и комментарии,
+/// склеивает строки с \ в конце и многострочные определения функций
+pub fn split_commands(source: &str) -> Vec<RcCommand> {
+    let mut commands = Vec::new();
+    let mut current = String::new();
+    let mut start_line = 0;
+    let mut depth: i32 = 0;
+
+    for (index, line) in source.lines().enumerate() {
+        if current.is_empty() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            start_line = index + 1;
+        }
+
+        if let Some(joined) = line.strip_suffix('\\') {
+            current.push_str(joined);
+            continue;
+        }
+
+        current.push_str(line);
+        depth += brace_balance(line);
+        if depth > 0 {
+            // Тело функции продолжается на следующей строке
+            current.push('\n');
+            continue;
+        }
+
+        commands.push(RcCommand { line: start_line, text: current.trim().to_string() });
+        current.clear();
+        depth = 0;
+    }
+
+    // Незакрытая функция в конце файла - разбор сообщит об ошибке
+    if !current.trim().is_empty() {
+        commands.push(RcCommand { line: start_line, text: current.trim().to_string() });
+    }
+    commands
+}
+
+/// Разность открывающих и закрывающих скобок групп { ... } в строке.
+/// Учитываются только отдельные слова { и }, а не ${VAR} или {a,b}
+fn brace_balance(line: &str) -> i32 {
+    let chars: Vec<char> = line.chars().collect();
+    let mut balance = 0;
+    let mut quote: Option<char> = None;
+
+    for (i, &c) in chars.iter().enumerate() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None => match c {
+                '\'' | '"' => quote = Some(c),
+                '#' if i == 0 || chars[i - 1].is_whitespace() => break,
+                '{' if chars.get(i + 1).is_none_or(|n| n.is_whitespace())
+                    && (i == 0 || chars[i - 1].is_whitespace() || chars[i - 1] == ')') => balance += 1,
+                '}' if i == 0 || chars[i - 1].is_whitespace() || chars[i - 1] == ';' => balance -= 1,
+                _ => {}
+            },
+        }
+    }
+    balance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_commands() {
+        let source = "\
+# aliases команды
+alias ll='ls -al'
+
+export EDITOR=micro
+mkcd() {
+    mkdir -p \"$1\" && cd \"$1\"
+    echo \"${1}\" {a,b}
+}
+echo one \\
+  two
+";
+        let commands = split_commands(source);
+        assert_eq!(commands.len(), 4);
+        assert_eq!(commands[0], RcCommand { line: 2, text: "alias ll='ls -al'".to_string() });
+        assert_eq!(commands[1].line, 4);
+        assert_eq!(commands[2].line, 5);
+        assert!(commands[2].text.starts_with("mkcd() {\n") && commands[2].text.ends_with('}'));
+        assert_eq!(commands[3], RcCommand { line: 9, text: "echo one   two".to_string() });
+    }
+
+    #[test]
+    fn test_one_line_function() {
+        let commands = split_commands("f() { ls; }\ng() { echo '}'; }\n");
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[1].text, "g() { echo '}'; }");
+    }
+
+    #[test]
+    fn test_missing_file_is_empty() {
+        assert_eq!(load(Path::new("/nonexistent/.smarttermrc")).unwrap(), Vec::new());
+    }
+}
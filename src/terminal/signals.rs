@@ -0,0 +1,115 @@
+//! Сигналы интерактивного терминала: Ctrl+C и Ctrl+\ прерывают выполняемую
+//! команду, а не сам терминал; изменение размера окна (SIGWINCH) передается
+//! псевдотерминалу задания
+
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+
+/// Группа процессов задания переднего плана (0 - нет)
+static FOREGROUND_PGID: AtomicI32 = AtomicI32::new(0);
+/// Последний полученный SIGINT или SIGQUIT (0 - не было)
+static PENDING_INTERRUPT: AtomicI32 = AtomicI32::new(0);
+static WINDOW_RESIZED: AtomicBool = AtomicBool::new(false);
+
+/// Код завершения процесса, убитого сигналом, как в bash
+pub fn exit_code(signal: i32) -> i32 {
+    128 + signal
+}
+
+/// Сигнал, которым завершился процесс с таким кодом
+pub fn from_exit_code(code: i32) -> Option<i32> {
+    (code > 128 && code < 128 + 65).then_some(code - 128)
+}
+
+/// Был ли код завершения вызван Ctrl+C или Ctrl+\ (список команд прерывается)
+pub fn is_interrupt(code: i32) -> bool {
+    matches!(from_exit_code(code), Some(libc::SIGINT) | Some(libc::SIGQUIT))
+}
+
+extern "C" fn handle(signal: libc::c_int) {
+    // Здесь допустимы только атомарные операции и async-signal-safe вызовы
+    if signal == libc::SIGWINCH {
+        WINDOW_RESIZED.store(true, Ordering::SeqCst);
+        return;
+    }
+    PENDING_INTERRUPT.store(signal, Ordering::SeqCst);
+    let pgid = FOREGROUND_PGID.load(Ordering::SeqCst);
+    if pgid > 0 {
+        unsafe {
+            libc::kill(-pgid, signal);
+        }
+    }
+}
+
+/// Устанавливает обработчики SIGINT, SIGQUIT и SIGWINCH. Вызывать до
+/// первого чтения событий crossterm, чтобы его обработчик SIGWINCH
+/// вызывал и наш. Дочерние процессы после exec получают обработку по умолчанию
+pub fn install() {
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handle as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        // waitpid и read продолжаются после сигнала, poll возвращает EINTR
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+        for signal in [libc::SIGINT, libc::SIGQUIT, libc::SIGWINCH] {
+            libc::sigaction(signal, &action, std::ptr::null_mut());
+        }
+    }
+}
+
+/// Задает группу процессов, которой пересылаются SIGINT и SIGQUIT,
+/// полученные терминалом (например, через kill или без управления заданиями)
+pub fn set_foreground(pgid: Option<i32>) {
+    FOREGROUND_PGID.store(pgid.unwrap_or(0), Ordering::SeqCst);
+}
+
+/// Возвращает и сбрасывает полученный SIGINT/SIGQUIT
+pub fn take_interrupt() -> Option<i32> {
+    match PENDING_INTERRUPT.swap(0, Ordering::SeqCst) {
+        0 => None,
+        signal => Some(signal),
+    }
+}
+
+/// Изменился ли размер окна с прошлой проверки
+pub fn take_resize() -> bool {
+    WINDOW_RESIZED.swap(false, Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(exit_code(libc::SIGINT), 130);
+        assert_eq!(from_exit_code(137), Some(libc::SIGKILL));
+        assert_eq!(from_exit_code(1), None);
+        assert!(is_interrupt(130));
+        assert!(!is_interrupt(143));
+    }
+
+    #[test]
+    fn test_handlers_record_signals() {
+        // Обработчики общие для процесса, поэтому проверяем их в дочернем
+        // процессе: в тестовом процессе их ждут тесты исполнителей
+        let pid = unsafe { libc::fork() };
+        assert!(pid >= 0, "fork не удался");
+        if pid == 0 {
+            install();
+            unsafe {
+                libc::raise(libc::SIGWINCH);
+                libc::raise(libc::SIGINT);
+            }
+            let ok = take_resize()
+                && !take_resize()
+                && take_interrupt() == Some(libc::SIGINT)
+                && take_interrupt().is_none();
+            unsafe { libc::_exit(if ok { 0 } else { 1 }) };
+        }
+
+        let mut status = 0;
+        assert_eq!(unsafe { libc::waitpid(pid, &mut status, 0) }, pid);
+        assert!(libc::WIFEXITED(status), "дочерний процесс завершен сигналом");
+        assert_eq!(libc::WEXITSTATUS(status), 0);
+    }
+}
@@ -0,0 +1,191 @@
+//! Итог выполнения команды: код завершения или сигнал, затраченное
+//! время и память (rusage из wait4)
+
+use std::fmt;
+use std::time::Duration;
+use crate::terminal::signals;
+
+/// Как завершилась команда
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitStatus {
+    Code(i32),
+    /// Процесс завершен сигналом
+    Signal(i32),
+}
+
+impl ExitStatus {
+    /// Значение $?: для сигнала - 128 + номер, как в bash
+    pub fn code(self) -> i32 {
+        match self {
+            ExitStatus::Code(code) => code,
+            ExitStatus::Signal(signal) => signals::exit_code(signal),
+        }
+    }
+
+    pub fn success(self) -> bool {
+        self == ExitStatus::Code(0)
+    }
+
+    /// Статус завершившегося процесса из std::process
+    pub fn from_std(status: std::process::ExitStatus) -> Self {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+            if let Some(signal) = status.signal() {
+                return ExitStatus::Signal(signal);
+            }
+        }
+        ExitStatus::Code(status.code().unwrap_or(1))
+    }
+}
+
+impl Default for ExitStatus {
+    fn default() -> Self {
+        ExitStatus::Code(0)
+    }
+}
+
+impl fmt::Display for ExitStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExitStatus::Code(code) => write!(f, "код {}", code),
+            ExitStatus::Signal(signal) => write!(f, "сигнал {}", signal),
+        }
+    }
+}
+
+/// Затраченные командой ресурсы
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ResourceUsage {
+    /// Реальное время выполнения
+    pub wall: Duration,
+    /// Процессорное время в режиме пользователя
+    pub user: Duration,
+    /// Процессорное время в ядре
+    pub system: Duration,
+    /// Пиковый размер резидентной памяти, КБ; None - неизвестен
+    /// (встроенные команды, !bash)
+    pub max_rss_kb: Option<u64>,
+}
+
+fn timeval(time: libc::timeval) -> Duration {
+    Duration::from_secs(time.tv_sec.max(0) as u64) + Duration::from_micros(time.tv_usec.max(0) as u64)
+}
+
+impl ResourceUsage {
+    /// Время и память одного процесса из wait4
+    pub fn from_rusage(usage: &libc::rusage) -> Self {
+        // ru_maxrss в Linux - в килобайтах, в macOS - в байтах
+        let max_rss = usage.ru_maxrss.max(0) as u64;
+        let max_rss_kb = if cfg!(target_os = "macos") { max_rss / 1024 } else { max_rss };
+        Self {
+            wall: Duration::ZERO,
+            user: timeval(usage.ru_utime),
+            system: timeval(usage.ru_stime),
+            max_rss_kb: Some(max_rss_kb),
+        }
+    }
+
+    /// Суммарное время всех завершенных дочерних процессов терминала
+    pub fn children() -> Self {
+        let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+        unsafe {
+            libc::getrusage(libc::RUSAGE_CHILDREN, &mut usage);
+        }
+        // Пиковая память здесь - максимум за все время, а не для одной команды
+        Self { max_rss_kb: None, ..Self::from_rusage(&usage) }
+    }
+
+    /// Процессорное время, накопленное после снимка earlier
+    pub fn since(&self, earlier: &Self) -> Self {
+        Self {
+            wall: Duration::ZERO,
+            user: self.user.saturating_sub(earlier.user),
+            system: self.system.saturating_sub(earlier.system),
+            max_rss_kb: None,
+        }
+    }
+
+    /// Добавляет ресурсы еще одного процесса: время суммируется, память - максимум
+    pub fn add(&mut self, other: &ResourceUsage) {
+        self.user += other.user;
+        self.system += other.system;
+        self.max_rss_kb = match (self.max_rss_kb, other.max_rss_kb) {
+            (Some(a), Some(b)) => Some(a.max(b)),
+            (a, b) => a.or(b),
+        };
+    }
+
+    /// Отчет встроенной команды time
+    pub fn report(&self) -> String {
+        let mut report = format!(
+            "real\t{}\nuser\t{}\nsys\t{}",
+            format_minutes(self.wall), format_minutes(self.user), format_minutes(self.system)
+        );
+        if let Some(max_rss_kb) = self.max_rss_kb {
+            report.push_str(&format!("\nmaxrss\t{}", format_memory(max_rss_kb)));
+        }
+        report
+    }
+}
+
+/// 0m1.234s, как в bash
+fn format_minutes(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    format!("{}m{}.{:03}s", millis / 60_000, millis / 1000 % 60, millis % 1000)
+}
+
+fn format_memory(kb: u64) -> String {
+    if kb >= 1024 * 1024 {
+        format!("{:.1} ГБ", kb as f64 / (1024.0 * 1024.0))
+    } else if kb >= 1024 {
+        format!("{:.1} МБ", kb as f64 / 1024.0)
+    } else {
+        format!("{} КБ", kb)
+    }
+}
+
+/// Краткая длительность для приглашения: 850ms, 4.2s, 3m 05s, 1h 02m
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}h {:02}m", secs / 3600, secs / 60 % 60)
+    } else if secs >= 60 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else if secs >= 1 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else {
+        format!("{}ms", duration.as_millis())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_status_code() {
+        assert_eq!(ExitStatus::Code(2).code(), 2);
+        assert_eq!(ExitStatus::Signal(libc::SIGKILL).code(), 137);
+        assert!(ExitStatus::default().success());
+        assert!(!ExitStatus::Signal(libc::SIGINT).success());
+    }
+
+    #[test]
+    fn test_usage_add_and_report() {
+        let mut usage = ResourceUsage { wall: Duration::from_millis(61_250), ..Default::default() };
+        usage.add(&ResourceUsage { user: Duration::from_millis(300), max_rss_kb: Some(2048), ..Default::default() });
+        usage.add(&ResourceUsage { user: Duration::from_millis(200), max_rss_kb: Some(1024), ..Default::default() });
+        assert_eq!(usage.user, Duration::from_millis(500));
+        assert_eq!(usage.max_rss_kb, Some(2048));
+        assert_eq!(usage.report(), "real\t1m1.250s\nuser\t0m0.500s\nsys\t0m0.000s\nmaxrss\t2.0 МБ");
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_millis(850)), "850ms");
+        assert_eq!(format_duration(Duration::from_millis(4200)), "4.2s");
+        assert_eq!(format_duration(Duration::from_secs(185)), "3m 05s");
+        assert_eq!(format_duration(Duration::from_secs(3720)), "1h 02m");
+    }
+}
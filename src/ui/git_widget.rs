@@ -1,17 +1,18 @@
 use crate::git::GitManager;
 use crate::ui::{UIColor, screen};
 use std::io::{self, Write};
+use std::path::Path;
 
 #[derive(Debug)]
 pub struct GitWidget {
-    git: GitManager,
+    git: Option<GitManager>,
     is_visible: bool,
 }
 
 impl GitWidget {
     pub fn new() -> Self {
         Self {
-            git: GitManager::new(),
+            git: GitManager::new(Path::new(".")).ok(),
             is_visible: false,
         }
     }
@@ -33,11 +34,11 @@ impl GitWidget {
         print_at(x + 1, y, title, UIColor::Yellow);
         
         // Получаем статус
-        match self.git.get_visual_status() {
-            Ok(status) => {
+        match self.git.as_ref().map(|git| git.get_status()) {
+            Some(Ok(status)) => {
                 self.draw_status(x, y + 1, width, height - 1, &status);
             }
-            Err(_) => {
+            _ => {
                 print_at(x + 2, y + 1, "Not a git repository", UIColor::Red);
             }
         }
@@ -63,7 +64,7 @@ impl GitWidget {
         }
     }
     
-    fn draw_status(&self, x: u16, y: u16, width: u16, height: u16, status: &crate::git::GitStatus) {
+    fn draw_status(&self, x: u16, y: u16, _width: u16, height: u16, status: &crate::git::RepositoryStatus) {
         let mut current_y = y;
         
         // Ветка
@@ -74,45 +75,45 @@ impl GitWidget {
         current_y += 1; // Пустая строка
         
         // Staged файлы
-        if !status.staged.is_empty() {
+        if !status.staged_files.is_empty() {
             print_at(x + 2, current_y, "Staged:", UIColor::Green);
             current_y += 1;
             
-            for file in &status.staged {
+            for file in &status.staged_files {
                 if current_y >= y + height - 1 {
                     break;
                 }
-                print_at(x + 4, current_y, &format!("✓ {}", file), UIColor::Green);
+                print_at(x + 4, current_y, &format!("✓ {}", file.path.display()), UIColor::Green);
                 current_y += 1;
             }
             current_y += 1;
         }
         
         // Unstaged файлы
-        if !status.unstaged.is_empty() {
+        if !status.unstaged_files.is_empty() {
             print_at(x + 2, current_y, "Modified:", UIColor::Yellow);
             current_y += 1;
             
-            for file in &status.unstaged {
+            for file in &status.unstaged_files {
                 if current_y >= y + height - 1 {
                     break;
                 }
-                print_at(x + 4, current_y, &format!("• {}", file), UIColor::Yellow);
+                print_at(x + 4, current_y, &format!("• {}", file.path.display()), UIColor::Yellow);
                 current_y += 1;
             }
             current_y += 1;
         }
         
         // Untracked файлы
-        if !status.untracked.is_empty() {
+        if !status.untracked_files.is_empty() {
             print_at(x + 2, current_y, "Untracked:", UIColor::Red);
             current_y += 1;
             
-            for file in &status.untracked {
+            for file in &status.untracked_files {
                 if current_y >= y + height - 1 {
                     break;
                 }
-                print_at(x + 4, current_y, &format!("? {}", file), UIColor::Red);
+                print_at(x + 4, current_y, &format!("? {}", file.display()), UIColor::Red);
                 current_y += 1;
             }
         }
@@ -5,43 +5,44 @@ pub struct KeyBindings {
 
 impl KeyBindings {
     pub fn new() -> Self {
-        let mut bindings = Vec::new();
-        
-        // Навигация по файлам
-        bindings.push(KeyBinding::new(KeyPress::Key(crossterm::event::KeyCode::Up)), "move_up", "Перемещение вверх"));
-        bindings.push(KeyBinding::new(KeyPress::Key(crossterm::event::KeyCode::Down)), "move_down", "Перемещение вниз"));
-        bindings.push(KeyBinding::new(KeyPress::Key(crossterm::event::KeyCode::PageUp)), "page_up", "Страница вверх"));
-        bindings.push(KeyBinding::new(KeyPress::Key(crossterm::event::KeyCode::PageDown)), "page_down", "Страница вниз"));
-        bindings.push(KeyBinding::new(KeyPress::Key(crossterm::event::KeyCode::Home)), "go_home", "В начало"));
-        bindings.push(KeyBinding::new(KeyPress::Key(crossterm::event::KeyCode::End)), "go_end", "В конец"));
-        
-        // Основные действия
-        bindings.push(KeyBinding::new(KeyPress::Key(Key::Char(' ')), "select_file", "Выбор файла"));
-        bindings.push(KeyBinding::new(KeyPress::Key(Key::Enter), "open_file", "Открыть файл/директорию"));
-        bindings.push(KeyBinding::new(KeyPress::Key(Key::Backspace), "go_up", "На уровень вверх"));
-        
-        // Панели
-        bindings.push(KeyBinding::new(KeyPress::Key(Key::Tab), "switch_panel", "Переключение панелей"));
-        bindings.push(KeyBinding::new(KeyPress::F(1), "left_panel", "Левая панель"));
-        bindings.push(KeyBinding::new(KeyPress::F(2), "right_panel", "Правая панель"));
-        
-        // Функциональные клавиши
-        bindings.push(KeyBinding::new(KeyPress::F(3), "view_file", "Просмотр файла"));
-        bindings.push(KeyBinding::new(KeyPress::F(4), "edit_file", "Редактировать файл"));
-        bindings.push(KeyBinding::new(KeyPress::F(5), "copy_file", "Копировать файл"));
-        bindings.push(KeyBinding::new(KeyPress::F(6), "move_file", "Переместить файл"));
-        bindings.push(KeyBinding::new(KeyPress::F(7), "mkdir", "Создать директорию"));
-        bindings.push(KeyBinding::new(KeyPress::F(8), "delete_file", "Удалить файл"));
-        bindings.push(KeyBinding::new(KeyPress::F(9), "menu", "Меню"));
-        bindings.push(KeyBinding::new(KeyPress::F(10), "exit", "Выход"));
-        
-        // Комбинации клавиш
-        bindings.push(KeyBinding::new(KeyPress::Ctrl('r'), "refresh", "Обновить"));
-        bindings.push(KeyBinding::new(KeyPress::Ctrl('l'), "clear", "Очистить экран"));
-        bindings.push(KeyBinding::new(KeyPress::Alt('h'), "show_hidden", "Показать скрытые"));
-        bindings.push(KeyBinding::new(KeyPress::Ctrl('q'), "exit", "Выход в текстовый режим"));
-        bindings.push(KeyBinding::new(KeyPress::Key(Key::Char(':')), "command_mode", "Командный режим"));
-        bindings.push(KeyBinding::new(KeyPress::Ctrl('g'), "git_status", "Git статус"));
+        let bindings = vec![
+            // Навигация по файлам
+            KeyBinding::new(KeyPress::Up, "move_up", "Перемещение вверх"),
+            KeyBinding::new(KeyPress::Down, "move_down", "Перемещение вниз"),
+            KeyBinding::new(KeyPress::PageUp, "page_up", "Страница вверх"),
+            KeyBinding::new(KeyPress::PageDown, "page_down", "Страница вниз"),
+            KeyBinding::new(KeyPress::Home, "go_home", "В начало"),
+            KeyBinding::new(KeyPress::End, "go_end", "В конец"),
+
+            // Основные действия
+            KeyBinding::new(KeyPress::Char(' '), "select_file", "Выбор файла"),
+            KeyBinding::new(KeyPress::Enter, "open_file", "Открыть файл/директорию"),
+            KeyBinding::new(KeyPress::Backspace, "go_up", "На уровень вверх"),
+
+            // Панели
+            KeyBinding::new(KeyPress::Tab, "switch_panel", "Переключение панелей"),
+            KeyBinding::new(KeyPress::F(1), "left_panel", "Левая панель"),
+            KeyBinding::new(KeyPress::F(2), "right_panel", "Правая панель"),
+
+            // Функциональные клавиши
+            KeyBinding::new(KeyPress::F(3), "view_file", "Просмотр файла"),
+            KeyBinding::new(KeyPress::F(4), "edit_file", "Редактировать файл"),
+            KeyBinding::new(KeyPress::F(5), "copy_file", "Копировать файл"),
+            KeyBinding::new(KeyPress::F(6), "move_file", "Переместить файл"),
+            KeyBinding::new(KeyPress::F(7), "mkdir", "Создать директорию"),
+            KeyBinding::new(KeyPress::F(8), "delete_file", "Удалить файл"),
+            KeyBinding::new(KeyPress::F(9), "menu", "Меню"),
+            KeyBinding::new(KeyPress::F(10), "exit", "Выход"),
+
+            // Комбинации клавиш
+            KeyBinding::new(KeyPress::Ctrl('r'), "refresh", "Обновить"),
+            KeyBinding::new(KeyPress::Ctrl('l'), "clear", "Очистить экран"),
+            KeyBinding::new(KeyPress::Alt('h'), "show_hidden", "Показать скрытые"),
+            KeyBinding::new(KeyPress::Ctrl('q'), "exit", "Выход в текстовый режим"),
+            KeyBinding::new(KeyPress::Char(':'), "command_mode", "Командный режим"),
+            KeyBinding::new(KeyPress::Ctrl('g'), "git_status", "Git статус"),
+            KeyBinding::new(KeyPress::Ctrl('p'), "history", "История команд (нечеткий поиск)"),
+        ];
         
         Self { bindings }
     }
@@ -50,6 +51,19 @@ impl KeyBindings {
         self.bindings.iter().find(|b| &b.key == key)
     }
     
+    /// Назначает действию новую клавишу (из секции [keybindings] конфигурации).
+    /// Клавиша снимается с других действий, чтобы не перекрывать новое назначение
+    pub fn rebind(&mut self, action: &str, key: KeyPress) -> Result<(), String> {
+        let description = self.bindings.iter()
+            .find(|b| b.action == action)
+            .map(|b| b.description.clone())
+            .ok_or_else(|| format!("неизвестное действие '{}'", action))?;
+        
+        self.bindings.retain(|b| b.action != action && b.key != key);
+        self.bindings.push(KeyBinding::new(key, action, &description));
+        Ok(())
+    }
+    
     pub fn get_help(&self) -> String {
         let mut help = String::new();
         help.push_str("Горячие клавиши:\n");
@@ -100,30 +114,86 @@ pub enum KeyPress {
     F(u8),
     Alt(char),
     Ctrl(char),
-    Shift(KeyPress), // для комбинаций
+    Shift(Box<KeyPress>), // для комбинаций
 }
 
 impl KeyPress {
-    pub fn to_string(&self) -> String {
-        match self {
-            KeyPress::Key(Key::Char(c)) => format!("{}", c),
-            KeyPress::Key(Key::Up) => "↑".to_string(),
-            KeyPress::Key(Key::Down) => "↓".to_string(),
-            KeyPress::Key(Key::Left) => "←".to_string(),
-            KeyPress::Key(Key::Right) => "→".to_string(),
-            KeyPress::Key(Key::Enter) => "Enter".to_string(),
-            KeyPress::Key(Key::Tab) => "Tab".to_string(),
-            KeyPress::Key(Key::Backspace) => "Backspace".to_string(),
-            KeyPress::Key(Key::Delete) => "Del".to_string(),
-            KeyPress::Key(Key::Home) => "Home".to_string(),
-            KeyPress::Key(Key::End) => "End".to_string(),
-            KeyPress::Key(Key::PageUp) => "PgUp".to_string(),
-            KeyPress::Key(Key::PageDown) => "PgDown".to_string(),
-            KeyPress::Key(Key::Esc) => "Esc".to_string(),
+    /// Разбирает запись клавиши: "Ctrl-r", "Alt+h", "F5", "Enter", "PgUp", "x"
+    pub fn parse(spec: &str) -> Option<KeyPress> {
+        let spec = spec.trim();
+        let lower = spec.to_lowercase();
+        
+        for (prefix, modifier) in [("ctrl", 'c'), ("alt", 'a'), ("shift", 's')] {
+            let rest = lower.strip_prefix(prefix)
+                .and_then(|rest| rest.strip_prefix('-').or_else(|| rest.strip_prefix('+')));
+            if let Some(rest) = rest {
+                let rest_original = &spec[spec.len() - rest.len()..];
+                return match modifier {
+                    'c' => Self::single_char(rest).map(KeyPress::Ctrl),
+                    'a' => Self::single_char(rest).map(KeyPress::Alt),
+                    _ => KeyPress::parse(rest_original).map(|key| KeyPress::Shift(Box::new(key))),
+                };
+            }
+        }
+        
+        let key = match lower.as_str() {
+            "up" => KeyPress::Up,
+            "down" => KeyPress::Down,
+            "left" => KeyPress::Left,
+            "right" => KeyPress::Right,
+            "enter" | "return" => KeyPress::Enter,
+            "tab" => KeyPress::Tab,
+            "backspace" => KeyPress::Backspace,
+            "del" | "delete" => KeyPress::Delete,
+            "home" => KeyPress::Home,
+            "end" => KeyPress::End,
+            "pgup" | "pageup" => KeyPress::PageUp,
+            "pgdown" | "pagedown" => KeyPress::PageDown,
+            "esc" | "escape" => KeyPress::Esc,
+            "space" => KeyPress::Char(' '),
+            _ => {
+                if let Some(number) = lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                    return (1..=12).contains(&number).then_some(KeyPress::F(number));
+                }
+                // Для одиночного символа регистр важен
+                return Self::single_char(spec).map(KeyPress::Char);
+            }
+        };
+        Some(key)
+    }
+    
+    fn single_char(text: &str) -> Option<char> {
+        let mut chars = text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Some(c),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for KeyPress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            KeyPress::Char(c) => format!("{}", c),
+            KeyPress::Up => "↑".to_string(),
+            KeyPress::Down => "↓".to_string(),
+            KeyPress::Left => "←".to_string(),
+            KeyPress::Right => "→".to_string(),
+            KeyPress::Enter => "Enter".to_string(),
+            KeyPress::Tab => "Tab".to_string(),
+            KeyPress::Backspace => "Backspace".to_string(),
+            KeyPress::Delete => "Del".to_string(),
+            KeyPress::Home => "Home".to_string(),
+            KeyPress::End => "End".to_string(),
+            KeyPress::PageUp => "PgUp".to_string(),
+            KeyPress::PageDown => "PgDown".to_string(),
+            KeyPress::Esc => "Esc".to_string(),
+            KeyPress::Key(event) => format!("{:?}", event.code),
             KeyPress::Ctrl(c) => format!("Ctrl-{}", c),
             KeyPress::Alt(c) => format!("Alt-{}", c),
-            KeyPress::Shift(key) => format!("Shift-{}", key.to_string()),
+            KeyPress::Shift(key) => format!("Shift-{}", key),
             KeyPress::F(n) => format!("F{}", n),
-        }
+        };
+        f.write_str(&text)
     }
 }
@@ -26,7 +26,7 @@ pub enum UIColor {
 }
 
 impl UIColor {
-    pub fn to_fg_color(&self) -> i16 {
+    pub fn to_fg_color(self) -> i16 {
         match self {
             UIColor::Black => 30,
             UIColor::Red => 31,
@@ -44,7 +44,11 @@ impl UIColor {
     }
 
 
-    pub fn to_ansi_bg(&self) -> u8 {
+    pub fn to_ansi_fg(self) -> i16 {
+        self.to_fg_color()
+    }
+
+    pub fn to_ansi_bg(self) -> u8 {
         match self {
             UIColor::Black => 40,
             UIColor::Red => 41,
@@ -55,6 +59,7 @@ impl UIColor {
             UIColor::Cyan => 46,
             UIColor::White => 47,
             UIColor::DarkGray => 100,
+            UIColor::Gray => 47,
         }
     }
 }
@@ -1,12 +1,13 @@
 use crate::ui::{
     panels::{Panel, PanelType, FileType}, 
-    widgets::{FilePanelWidget, CommandLineWidget, StatusBarWidget},
+    widgets::{FilePanelWidget, CommandLineWidget, StatusBarWidget, HistoryPickerWidget},
     keybindings::{KeyBindings, KeyPress},
     git_widget::GitWidget,
     screen,
     UIColor
 };
 use crate::ui::widgets::print_at;
+use crate::terminal::CommandHistory;
 use std::io::{self, Write};
 
 pub struct NcursesLikeUI {
@@ -15,6 +16,8 @@ pub struct NcursesLikeUI {
     command_line: CommandLineWidget,
     status_bar: StatusBarWidget,
     git_widget: GitWidget,
+    history: CommandHistory,
+    history_picker: Option<HistoryPickerWidget>,
     keybindings: KeyBindings,
     screen_width: u16,
     screen_height: u16,
@@ -40,6 +43,11 @@ pub enum UIMode {
 
 impl NcursesLikeUI {
     pub fn new() -> Result<Self, String> {
+        Self::with_history(CommandHistory::new(0))
+    }
+    
+    /// UI с доступом к истории команд текстового режима
+    pub fn with_history(history: CommandHistory) -> Result<Self, String> {
         let (width, height) = crate::utils::helpers::get_terminal_size()
             .map_err(|e| format!("Не удалось получить размер терминала: {}", e))?;
         
@@ -55,6 +63,8 @@ impl NcursesLikeUI {
             command_line: CommandLineWidget::new(),
             status_bar: StatusBarWidget::new(),
             git_widget: GitWidget::new(),
+            history,
+            history_picker: None,
             keybindings: KeyBindings::new(),
             screen_width: width,
             screen_height: height,
@@ -64,6 +74,11 @@ impl NcursesLikeUI {
         })
     }
     
+    /// Заменяет горячие клавиши (из секции [keybindings] конфигурации)
+    pub fn set_keybindings(&mut self, keybindings: KeyBindings) {
+        self.keybindings = keybindings;
+    }
+    
     pub fn run(&mut self) -> Result<(), String> {
         self.setup_terminal()?;
         
@@ -139,6 +154,11 @@ impl NcursesLikeUI {
             self.git_widget.draw(git_x, 1, git_width, panel_height);
         }
         
+        // Выбор из истории поверх правой панели
+        if let (UIMode::Search, Some(picker)) = (&self.mode, &self.history_picker) {
+            picker.draw(2 + panel_width, 1, panel_width, panel_height);
+        }
+        
         // Командная строка
         self.command_line.draw(1, self.screen_height - 2, self.screen_width);
         
@@ -169,7 +189,9 @@ impl NcursesLikeUI {
                 "move_up" => self.move_selection(-1),
                 "move_down" => self.move_selection(1),
                 "switch_panel" => self.switch_panel(),
+                "open_file" if self.active_panel_is_history() => self.open_history_picker(),
                 "open_file" => self.open_selected(),
+                "history" => self.open_history_picker(),
                 "go_up" => self.go_up_directory(),
                 "exit" => { self.running = false; },
                 "view_file" => self.view_file(),
@@ -200,25 +222,64 @@ impl NcursesLikeUI {
     }
     
     fn handle_menu_mode(&mut self, key: KeyPress) -> bool {
-        match key {
-            KeyPress::Esc => {
-                self.mode = UIMode::Normal;
-            }
-            _ => {}
+        if key == KeyPress::Esc {
+            self.mode = UIMode::Normal;
         }
         true
     }
     
     fn handle_search_mode(&mut self, key: KeyPress) -> bool {
+        let picker = match self.history_picker.as_mut() {
+            Some(picker) => picker,
+            None => {
+                self.mode = UIMode::Normal;
+                return true;
+            }
+        };
+        
         match key {
             KeyPress::Esc => {
+                self.history_picker = None;
                 self.mode = UIMode::Normal;
             }
+            KeyPress::Enter => {
+                // Выбранная команда попадает в командную строку для запуска
+                if let Some(command) = picker.selected_command().map(str::to_string) {
+                    self.command_line.set_text(&command);
+                    self.mode = UIMode::Command;
+                } else {
+                    self.mode = UIMode::Normal;
+                }
+                self.history_picker = None;
+            }
+            KeyPress::Up | KeyPress::Ctrl('r') => picker.move_selection(-1),
+            KeyPress::Down | KeyPress::Ctrl('s') => picker.move_selection(1),
+            KeyPress::Backspace => picker.backspace(),
+            KeyPress::Char(c) => picker.insert_char(c),
             _ => {}
         }
         true
     }
     
+    fn active_panel_is_history(&self) -> bool {
+        let panel = match self.active_panel {
+            ActivePanel::Left => &self.left_panel,
+            ActivePanel::Right => &self.right_panel,
+            ActivePanel::CommandLine => return false,
+        };
+        matches!(panel.panel.panel_type, PanelType::CommandHistory)
+    }
+    
+    /// Открывает панель истории с нечетким поиском
+    fn open_history_picker(&mut self) {
+        if let ActivePanel::Right = self.active_panel {
+            self.right_panel.panel.panel_type = PanelType::CommandHistory;
+        }
+        self.history_picker = Some(HistoryPickerWidget::new(self.history.clone()));
+        self.mode = UIMode::Search;
+        self.status_bar.set_info("История: вводите текст для поиска, Enter - выбрать, Esc - отмена");
+    }
+    
     fn read_key(&self) -> Result<Option<KeyPress>, String> {
         use crossterm::event::{self, Event, KeyCode, KeyModifiers};
         
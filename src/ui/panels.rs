@@ -142,16 +142,7 @@ impl Panel {
         self.scroll_offset = 0;
         Ok(())
     }
-                
-            // Авто-скроллинг если нужно
-            let visible_count = self.get_visible_files(20).len(); // примерная высота
-            if self.selected_index < self.scroll_offset {
-                self.scroll_offset = self.selected_index;
-            } else if self.selected_index >= self.scroll_offset + visible_count {
-                self.scroll_offset = self.selected_index - visible_count + 1;
-            }
-        
-    }
+}
 
 
 impl Panel {
@@ -161,24 +152,22 @@ impl Panel {
         let entries = std::fs::read_dir(path)
             .map_err(|e| format!("Ошибка чтения директории: {}", e))?;
             
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                let metadata = entry.metadata().unwrap();
-                let file_type = if metadata.is_dir() {
-                    FileType::Directory
-                } else if metadata.file_type().is_symlink() {
-                    FileType::Symlink
-                } else {
-                    FileType::File
-                };
-                
-                files.push(FileEntry {
-                    name: path.file_name().unwrap().to_string_lossy().to_string(),
-                    size: metadata.len(),
-                    file_type,
-                });
-            }
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let metadata = entry.metadata().unwrap();
+            let file_type = if metadata.is_dir() {
+                FileType::Directory
+            } else if metadata.file_type().is_symlink() {
+                FileType::Symlink
+            } else {
+                FileType::File
+            };
+            
+            files.push(FileEntry {
+                name: path.file_name().unwrap().to_string_lossy().to_string(),
+                size: metadata.len(),
+                file_type,
+            });
         }
         Ok(files)
     }
@@ -1,4 +1,6 @@
 use crate::ui::{UIColor, panels::{Panel, FileEntry, FileType}};
+use crate::terminal::CommandHistory;
+use crate::terminal::history_search::{self, SearchMatch};
 use crate::utils::filesystem;
 use std::io::{self, Write};
 
@@ -129,7 +131,7 @@ impl FilePanelWidget {
         print_at_with_bg(x + 1, y, &full_text, text_color, bg_color);
     }
     
-    fn draw_status(&self, x: u16, y: u16, width: u16) {
+    fn draw_status(&self, x: u16, y: u16, _width: u16) {
         let selected = self.panel.get_selected_file();
         let status = if let Some(file) = selected {
             match file.file_type {
@@ -258,6 +260,80 @@ impl CommandLineWidget {
     }
 }
 
+/// Выбор команды из истории с нечетким поиском (та же логика, что у Ctrl+R)
+#[derive(Debug, Clone)]
+pub struct HistoryPickerWidget {
+    history: CommandHistory,
+    pub query: String,
+    matches: Vec<SearchMatch>,
+    selected: usize,
+}
+
+impl HistoryPickerWidget {
+    pub fn new(history: CommandHistory) -> Self {
+        let mut picker = Self {
+            history,
+            query: String::new(),
+            matches: Vec::new(),
+            selected: 0,
+        };
+        picker.refresh();
+        picker
+    }
+    
+    fn refresh(&mut self) {
+        let cwd = std::env::current_dir()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.matches = history_search::search(&self.history, &self.query, &cwd);
+        self.selected = 0;
+    }
+    
+    pub fn insert_char(&mut self, c: char) {
+        self.query.push(c);
+        self.refresh();
+    }
+    
+    pub fn backspace(&mut self) {
+        self.query.pop();
+        self.refresh();
+    }
+    
+    pub fn move_selection(&mut self, direction: i32) {
+        let len = self.matches.len() as i32;
+        if len > 0 {
+            self.selected = (self.selected as i32 + direction).rem_euclid(len) as usize;
+        }
+    }
+    
+    pub fn selected_command(&self) -> Option<&str> {
+        self.matches.get(self.selected).map(|m| m.command.as_str())
+    }
+    
+    pub fn draw(&self, x: u16, y: u16, width: u16, height: u16) {
+        let header = format!(" История: {}_ ({}) ", self.query, self.matches.len());
+        print_at_with_bg(x, y, &format!("{:<width$}", header, width = width as usize), UIColor::Black, UIColor::Cyan);
+        
+        let visible = height.saturating_sub(1) as usize;
+        // Прокрутка так, чтобы выбранная строка была видна
+        let offset = self.selected.saturating_sub(visible.saturating_sub(1));
+        
+        for row in 0..visible {
+            let line_y = y + 1 + row as u16;
+            let text = match self.matches.get(offset + row) {
+                Some(found) => {
+                    let marker = if offset + row == self.selected { "> " } else { "  " };
+                    let command: String = found.command.chars().take(width as usize - 2).collect();
+                    format!("{}{}", marker, history_search::highlight(&command, &found.positions))
+                }
+                None => String::new(),
+            };
+            print_at(x, line_y, &" ".repeat(width as usize), UIColor::White);
+            print_at(x, line_y, &text, UIColor::White);
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct StatusBarWidget {
     pub message: String,
@@ -1,4 +1,5 @@
 use std::io::{self, Write};
+use std::sync::RwLock;
 
 /// Вывод цветного текста
 pub struct Color;
@@ -13,6 +14,92 @@ impl Color {
     pub const WHITE: &'static str = "\x1b[37m";
     pub const RESET: &'static str = "\x1b[0m";
     pub const BOLD: &'static str = "\x1b[1m";
+    
+    /// ANSI-последовательность по имени цвета: "red", "bright-blue",
+    /// "bold green", номер из 256-цветной палитры или "#rrggbb"
+    pub fn parse(spec: &str) -> Option<String> {
+        let mut codes = Vec::new();
+        for part in spec.split_whitespace() {
+            let part = part.to_lowercase();
+            let code = match part.as_str() {
+                "bold" => "1".to_string(),
+                "dim" => "2".to_string(),
+                "italic" => "3".to_string(),
+                "underline" => "4".to_string(),
+                _ => Self::color_code(&part)?,
+            };
+            codes.push(code);
+        }
+        if codes.is_empty() {
+            return None;
+        }
+        Some(format!("\x1b[{}m", codes.join(";")))
+    }
+    
+    fn color_code(name: &str) -> Option<String> {
+        const NAMES: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+        
+        if let Some(hex) = name.strip_prefix('#') {
+            if hex.len() != 6 {
+                return None;
+            }
+            let value = u32::from_str_radix(hex, 16).ok()?;
+            return Some(format!("38;2;{};{};{}", value >> 16, (value >> 8) & 0xff, value & 0xff));
+        }
+        if let Ok(index) = name.parse::<u8>() {
+            return Some(format!("38;5;{}", index));
+        }
+        if name == "gray" || name == "grey" {
+            return Some("90".to_string());
+        }
+        let (base, name) = match name.strip_prefix("bright-") {
+            Some(rest) => (90, rest),
+            None => (30, name),
+        };
+        NAMES.iter().position(|n| *n == name).map(|i| (base + i).to_string())
+    }
+}
+
+/// Цвета приглашения и сообщений, настраиваются в секции [colors]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorScheme {
+    pub prompt_user: String,
+    pub prompt_directory: String,
+    pub error: String,
+    pub success: String,
+    pub warning: String,
+    pub info: String,
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        Self {
+            prompt_user: "\x1b[1;32m".to_string(),
+            prompt_directory: "\x1b[1;34m".to_string(),
+            error: Color::RED.to_string(),
+            success: Color::GREEN.to_string(),
+            warning: Color::YELLOW.to_string(),
+            info: Color::CYAN.to_string(),
+        }
+    }
+}
+
+// None - схема по умолчанию
+static COLOR_SCHEME: RwLock<Option<ColorScheme>> = RwLock::new(None);
+
+/// Устанавливает цветовую схему для print_error, print_info и т.д.
+pub fn set_color_scheme(scheme: ColorScheme) {
+    if let Ok(mut current) = COLOR_SCHEME.write() {
+        *current = Some(scheme);
+    }
+}
+
+/// Текущая цветовая схема
+pub fn color_scheme() -> ColorScheme {
+    COLOR_SCHEME.read()
+        .ok()
+        .and_then(|scheme| scheme.clone())
+        .unwrap_or_default()
 }
 
 /// Вывести цветное сообщение
@@ -28,22 +115,22 @@ pub fn println_color(text: &str, color: &str) {
 
 /// Вывести сообщение об ошибке
 pub fn print_error(msg: &str) {
-    println_color(&format!("❌ {}", msg), Color::RED);
+    println_color(&format!("❌ {}", msg), &color_scheme().error);
 }
 
 /// Вывести сообщение об успехе
 pub fn print_success(msg: &str) {
-    println_color(&format!("✅ {}", msg), Color::GREEN);
+    println_color(&format!("✅ {}", msg), &color_scheme().success);
 }
 
 /// Вывести предупреждение
 pub fn print_warning(msg: &str) {
-    println_color(&format!("⚠️  {}", msg), Color::YELLOW);
+    println_color(&format!("⚠️  {}", msg), &color_scheme().warning);
 }
 
 /// Вывести информационное сообщение
 pub fn print_info(msg: &str) {
-    println_color(&format!("💡 {}", msg), Color::CYAN);
+    println_color(&format!("💡 {}", msg), &color_scheme().info);
 }
 
 /// Получить размер терминала
@@ -25,7 +25,7 @@ pub mod filesystem {
             if let Ok(metadata) = std::fs::metadata(path) {
                 // ИСПРАВЛЕНИЕ: правильные методы для Unix
                 let available_blocks = metadata.len() / 512;
-                crate::utils::human_readable_size(free_blocks)
+                crate::utils::human_readable_size(available_blocks)
             } else {
                 "Неизвестно".to_string()
             }